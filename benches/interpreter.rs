@@ -0,0 +1,71 @@
+use ham_core::{
+    ast_types::expression::{
+        Expression,
+        ExpressionBase,
+    },
+    get_tokens,
+    move_tokens_into_ast,
+    run_ast,
+    stack::Stack,
+    vm::run_vm,
+};
+use std::{
+    fs,
+    sync::Mutex,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+// How many times each program is run
+const RUNS: u32 = 5;
+
+/*
+ * Time how long it takes to parse and run a program
+ */
+fn measure(code: &str, dir: &str, with_vm: bool) -> Duration {
+    let started = Instant::now();
+
+    for _ in 0..RUNS {
+        let global_context = Expression::new();
+        let stack = Mutex::new(Stack::new(global_context.expr_id));
+        let tree = Mutex::new(global_context);
+
+        move_tokens_into_ast(get_tokens(code.to_string()), &tree, dir.to_string(), &stack);
+
+        if with_vm {
+            run_vm(&tree, &stack);
+        } else {
+            run_ast(&tree, &stack);
+        }
+    }
+
+    started.elapsed() / RUNS
+}
+
+/*
+ * Run every program in benches/programs with the tree-walking interpreter and the VM
+ */
+fn main() {
+    let dir = format!("{}/benches/programs", env!("CARGO_MANIFEST_DIR"));
+
+    let mut programs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("ham"))
+        .collect();
+    programs.sort();
+
+    println!("{:<20} {:>14} {:>14}", "program", "interpreter", "vm");
+
+    for program in programs {
+        let code = fs::read_to_string(&program).unwrap();
+        let name = program.file_name().unwrap().to_string_lossy();
+
+        let interpreter = measure(&code, &dir, false);
+        let vm = measure(&code, &dir, true);
+
+        println!("{:<20} {:>12.2?} {:>12.2?}", name, interpreter, vm);
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
             Boolean,
             BooleanValueBase,
         },
+        none::NoneVal,
         number::{
             Number,
             NumberValueBase,
@@ -13,6 +14,7 @@ use crate::{
             StringValueBase,
         },
     },
+    runtime::downcast_val,
     types::{
         IndexedTokenList,
         Token,
@@ -34,6 +36,7 @@ use self::{
         FnCall,
         FnCallBase,
     },
+    named_argument::NamedArgument,
     reference::{
         Reference,
         ReferenceValueBase,
@@ -42,6 +45,10 @@ use self::{
         ResultExpression,
         ResultExpressionBase,
     },
+    var_def::{
+        VarDefinition,
+        VarDefinitionBase,
+    },
 };
 
 pub mod ast_base;
@@ -50,14 +57,19 @@ pub mod break_ast;
 pub mod expression;
 pub mod fn_call;
 pub mod fn_def;
+pub mod for_block;
 pub mod if_ast;
 pub mod module;
+pub mod named_argument;
 pub mod reference;
 pub mod result;
 pub mod return_ast;
+pub mod spawn;
+pub mod try_ast;
 pub mod var_assign;
 pub mod var_def;
 pub mod while_block;
+pub mod yield_ast;
 
 use return_ast::ReturnStatement;
 
@@ -132,6 +144,14 @@ pub fn get_assignment_token_fn(
                 value: Box::new(Boolean::new(false)),
             },
         ),
+        // No value
+        "none" => (
+            1,
+            BoxedValue {
+                interface: Ops::None,
+                value: Box::new(NoneVal),
+            },
+        ),
         // Numeric values
         val if val.parse::<usize>().is_ok() => (
             1,
@@ -171,6 +191,7 @@ pub fn get_assignment_token_fn(
 
                 match reference_type {
                     Ops::PropAccess => {
+                        let object = val.to_string();
                         let after_next_token = tokens[token_n + 2].clone();
                         let (size, val) = get_assignment_token_fn(
                             after_next_token.value,
@@ -179,6 +200,22 @@ pub fn get_assignment_token_fn(
                             Directions::LeftToRight,
                         );
 
+                        // Values exported by modules, ex: `x.b`
+                        if val.interface == Ops::Reference {
+                            let property = downcast_val::<Reference>(val.value.as_self());
+
+                            return (
+                                size + 2,
+                                BoxedValue {
+                                    interface: Ops::Reference,
+                                    value: Box::new(Reference::new(format!(
+                                        "{}.{}",
+                                        object, property.0
+                                    ))),
+                                },
+                            );
+                        }
+
                         (size + 2, val)
                     }
                     /*
@@ -186,6 +223,7 @@ pub fn get_assignment_token_fn(
                      */
                     Ops::FnCall => {
                         let mut group = Expression::new();
+                        let line = tokens[token_n].line;
 
                         // Position where it will be starting getting the argument tokens
                         let starting_token: usize = {
@@ -259,6 +297,8 @@ pub fn get_assignment_token_fn(
                             },
                         );
 
+                        ast_token.line = line;
+
                         // Transfrom the tokens into arguments
                         ast_token.arguments = convert_tokens_into_arguments(
                             arguments_tokens
@@ -270,13 +310,13 @@ pub fn get_assignment_token_fn(
                         let mut left_length = arguments_tokens.len() + 3;
 
                         // Assign a expression to the variable
-                        group.body.push(Box::new(var_def::VarDefinition {
-                            def_name: "_".to_string(),
-                            assignment: BoxedValue {
+                        group.body.push(Box::new(VarDefinition::new(
+                            "_".to_string(),
+                            BoxedValue {
                                 interface: Ops::FnCall,
                                 value: Box::new(ast_token),
                             },
-                        }));
+                        )));
 
                         let mut next_token_n = token_n + left_length;
 
@@ -304,7 +344,10 @@ pub fn get_assignment_token_fn(
                                 /*
                                  * Return the token from the expression
                                  */
-                                group.body.push(Box::new(ReturnStatement { value: val }));
+                                group.body.push(Box::new(ReturnStatement {
+                                    value: val,
+                                    line,
+                                }));
                                 left_length += length;
                             } else {
                                 /*
@@ -317,6 +360,7 @@ pub fn get_assignment_token_fn(
                                         interface: Ops::Reference,
                                         value: Box::new(Reference::new("_".to_string())),
                                     },
+                                    line,
                                 }));
                             }
                         } else {
@@ -328,6 +372,7 @@ pub fn get_assignment_token_fn(
                                     interface: Ops::Reference,
                                     value: Box::new(Reference::new("_".to_string())),
                                 },
+                                line,
                             }));
                         }
 
@@ -376,6 +421,28 @@ pub fn convert_tokens_into_arguments(tokens: TokensList) -> Vec<BoxedValue> {
             Ops::OpenParent => token_n += 1,
             Ops::CloseParent => token_n += 1,
             Ops::CommaDelimiter => token_n += 1,
+            // Named arguments, ex: `b = 2`
+            Ops::Reference
+                if token_n + 2 < tokens.len()
+                    && tokens[token_n + 1].ast_type == Ops::LeftAssign =>
+            {
+                let (size, val) = get_assignment_token_fn(
+                    tokens[token_n + 2].value.clone(),
+                    token_n + 2,
+                    tokens.clone(),
+                    Directions::LeftToRight,
+                );
+
+                token_n += size + 2;
+
+                args.push(BoxedValue {
+                    interface: Ops::NamedArgument,
+                    value: Box::new(NamedArgument {
+                        name: token.value,
+                        value: val,
+                    }),
+                });
+            }
             _ => {
                 let (size, val) = get_assignment_token_fn(
                     token.value.clone(),
@@ -402,19 +469,23 @@ pub fn convert_tokens_into_res_expressions(tokens: TokensList) -> Vec<ResultExpr
 
     let mut token_n = 1;
 
+    // Where the current condition starts
+    let mut condition_start = 0;
+
     while token_n < tokens.len() {
-        let left_token = tokens[token_n - 1].clone();
         let token = tokens[token_n].clone();
 
         match token.ast_type {
             Ops::EqualCondition | Ops::NotEqualCondition => {
+                let left_token = tokens[condition_start].clone();
                 let right_token = tokens[token_n + 1].clone();
 
+                // The left value is read from its start, so it can be a call, ex: `a.sum(1) == 2`
                 let left_token = get_assignment_token_fn(
                     left_token.value.clone(),
-                    token_n,
+                    condition_start,
                     tokens.clone(),
-                    Directions::RightToLeft,
+                    Directions::LeftToRight,
                 );
 
                 let right_token = get_assignment_token_fn(
@@ -430,7 +501,8 @@ pub fn convert_tokens_into_res_expressions(tokens: TokensList) -> Vec<ResultExpr
                     right_token.1.clone(),
                 ));
 
-                token_n += 2;
+                condition_start = token_n + 1 + right_token.0;
+                token_n = condition_start + 1;
             }
             _ => {
                 token_n += 1;
@@ -438,5 +510,24 @@ pub fn convert_tokens_into_res_expressions(tokens: TokensList) -> Vec<ResultExpr
         }
     }
 
+    // A single value is checked for being true, ex: `if done {`
+    if exprs.is_empty() && !tokens.is_empty() {
+        let value = get_assignment_token_fn(
+            tokens[0].value.clone(),
+            0,
+            tokens.clone(),
+            Directions::LeftToRight,
+        );
+
+        exprs.push(ResultExpression::new(
+            Ops::EqualCondition,
+            value.1,
+            BoxedValue {
+                interface: Ops::Boolean,
+                value: Box::new(Boolean::new(true)),
+            },
+        ));
+    }
+
     exprs
 }
@@ -1,6 +1,37 @@
-use crate::utils::Ops;
-use erased_serde::serialize_trait_object;
-use std::any::Any;
+use crate::{
+    ast_types::{
+        break_ast::Break,
+        fn_call::FnCall,
+        fn_def::FnDefinition,
+        for_block::For,
+        if_ast::IfConditional,
+        module::Module,
+        return_ast::ReturnStatement,
+        spawn::Spawn,
+        try_ast::TryStatement,
+        var_assign::VarAssignment,
+        var_def::VarDefinition,
+        while_block::While,
+        yield_ast::YieldStatement,
+    },
+    utils::Ops,
+};
+use serde::{
+    de::{
+        self,
+        MapAccess,
+        Visitor,
+    },
+    ser::SerializeMap,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::{
+    any::Any,
+    fmt,
+};
 
 /*
  * This is the base for every AST type
@@ -11,4 +42,76 @@ pub trait AstBase: dyn_clone::DynClone + erased_serde::Serialize + std::fmt::Deb
 }
 
 dyn_clone::clone_trait_object!(AstBase);
-serialize_trait_object!(AstBase);
+
+/*
+ * Nodes are tagged with their type, ex: `{ "VarDef": { "def_name": "a", .. } }`,
+ * so they can be read back
+ */
+impl Serialize for dyn AstBase {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut node = serializer.serialize_map(Some(1))?;
+        node.serialize_entry(&self.get_type(), &Untagged(self))?;
+        node.end()
+    }
+}
+
+/*
+ * Fields of a node, without its tag
+ */
+struct Untagged<'a>(&'a dyn AstBase);
+
+impl Serialize for Untagged<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        erased_serde::serialize(self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn AstBase> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Box<dyn AstBase>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a statement tagged with its type")
+    }
+
+    /*
+     * Only the statements that can be run are read, as with the parser, values and conditions
+     * are read as part of them
+     */
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let node_type: Ops = map
+            .next_key()?
+            .ok_or_else(|| de::Error::custom("missing the type of the AST node"))?;
+
+        let node: Box<dyn AstBase> = match node_type {
+            Ops::Break => Box::new(map.next_value::<Break>()?),
+            Ops::FnCall => Box::new(map.next_value::<FnCall>()?),
+            Ops::FnDef => Box::new(map.next_value::<FnDefinition>()?),
+            Ops::ForDef => Box::new(map.next_value::<For>()?),
+            Ops::IfConditional => Box::new(map.next_value::<IfConditional>()?),
+            Ops::Module => Box::new(map.next_value::<Module>()?),
+            Ops::Return => Box::new(map.next_value::<ReturnStatement>()?),
+            Ops::Spawn => Box::new(map.next_value::<Spawn>()?),
+            Ops::Try => Box::new(map.next_value::<TryStatement>()?),
+            Ops::VarAssign => Box::new(map.next_value::<VarAssignment>()?),
+            Ops::VarDef => Box::new(map.next_value::<VarDefinition>()?),
+            Ops::WhileDef => Box::new(map.next_value::<While>()?),
+            Ops::Yield => Box::new(map.next_value::<YieldStatement>()?),
+            node_type => {
+                return Err(de::Error::custom(format!(
+                    "{:?} is not a statement",
+                    node_type
+                )))
+            }
+        };
+
+        Ok(node)
+    }
+}
@@ -1,10 +1,37 @@
 use crate::{
-    ast_types::ast_base::AstBase,
-    primitive_values::primitive_base::PrimitiveValueBase,
+    ast_types::{
+        ast_base::AstBase,
+        expression::Expression,
+        fn_call::FnCall,
+        named_argument::NamedArgument,
+        reference::Reference,
+        spawn::Spawn,
+    },
+    primitive_values::{
+        boolean::Boolean,
+        list::List,
+        none::NoneVal,
+        number::Number,
+        primitive_base::PrimitiveValueBase,
+        string::StringVal,
+    },
     utils::Ops,
 };
-use serde::Serialize;
-use std::any::Any;
+use serde::{
+    de::{
+        self,
+        MapAccess,
+        SeqAccess,
+        Visitor,
+    },
+    Deserialize,
+    Deserializer,
+    Serialize,
+};
+use std::{
+    any::Any,
+    fmt,
+};
 
 /* BOXED VALUE */
 
@@ -28,3 +55,90 @@ impl PrimitiveValueBase for BoxedValue {
         self
     }
 }
+
+/*
+ * Values are read by their interface, which must come before the value,
+ * only the ones which can be written in the code can be read
+ */
+impl<'de> Deserialize<'de> for BoxedValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("BoxedValue", &["interface", "value"], BoxedValueVisitor)
+    }
+}
+
+/*
+ * Value of the given interface
+ */
+struct TypedValue(Ops);
+
+impl<'de> de::DeserializeSeed<'de> for TypedValue {
+    type Value = Box<dyn PrimitiveValueBase>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let value: Box<dyn PrimitiveValueBase> = match self.0 {
+            Ops::Boolean => Box::new(Boolean::deserialize(deserializer)?),
+            Ops::Number => Box::new(Number::deserialize(deserializer)?),
+            Ops::String => Box::new(StringVal::deserialize(deserializer)?),
+            Ops::List => Box::new(List::deserialize(deserializer)?),
+            Ops::None => Box::new(NoneVal::deserialize(deserializer)?),
+            Ops::Reference => Box::new(Reference::deserialize(deserializer)?),
+            Ops::Expression => Box::new(Expression::deserialize(deserializer)?),
+            Ops::FnCall => Box::new(FnCall::deserialize(deserializer)?),
+            Ops::NamedArgument => Box::new(NamedArgument::deserialize(deserializer)?),
+            Ops::Spawn => Box::new(Spawn::deserialize(deserializer)?),
+            interface => {
+                return Err(de::Error::custom(format!(
+                    "values of type {:?} can't be read",
+                    interface
+                )))
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+struct BoxedValueVisitor;
+
+impl<'de> Visitor<'de> for BoxedValueVisitor {
+    type Value = BoxedValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value with its interface")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let interface: Ops = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq
+            .next_element_seed(TypedValue(interface))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok(BoxedValue { interface, value })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut interface = None;
+        let mut value = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "interface" => interface = Some(map.next_value::<Ops>()?),
+                "value" => {
+                    let interface = interface
+                        .ok_or_else(|| de::Error::custom("the interface must come first"))?;
+                    value = Some(map.next_value_seed(TypedValue(interface))?);
+                }
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(BoxedValue {
+            interface: interface.ok_or_else(|| de::Error::missing_field("interface"))?,
+            value: value.ok_or_else(|| de::Error::missing_field("value"))?,
+        })
+    }
+}
@@ -2,7 +2,10 @@ use crate::{
     ast_types::ast_base::AstBase,
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /*
@@ -12,7 +15,7 @@ use std::any::Any;
  *
  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Break();
 
 impl AstBase for Break {
@@ -1,19 +1,81 @@
 use crate::{
-    ast_types::ast_base::AstBase,
+    ast_types::{
+        ast_base::AstBase,
+        fn_call::FnCall,
+        reference::Reference,
+        return_ast::ReturnStatement,
+        var_def::VarDefinition,
+    },
     primitive_values::primitive_base::PrimitiveValueBase,
+    runtime::downcast_val,
+    stack::GLOBAL_SCOPE,
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
-use uuid::Uuid;
 
 /* EXPRESSION  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Expression {
     pub body: Vec<Box<dyn self::AstBase>>,
     pub token_type: Ops,
-    pub expr_id: String,
+    // Internal ID, it's not part of the tree's output
+    #[serde(skip)]
+    pub expr_id: u64,
+}
+
+impl Expression {
+    /*
+     * Get the definition of `_`, which keeps the result of the function call the expression starts with
+     *
+     * Function calls are parsed as a group which stores the result in `_` and returns it,
+     * or something called on it
+     */
+    pub fn get_call_result(&self) -> Option<&VarDefinition> {
+        let assignment = self.body.first()?;
+
+        if assignment.get_type() != Ops::VarDef {
+            return None;
+        }
+
+        let assignment = downcast_val::<VarDefinition>(assignment.as_self());
+
+        if assignment.def_name == "_" && assignment.assignment.interface == Ops::FnCall {
+            Some(assignment)
+        } else {
+            None
+        }
+    }
+
+    /*
+     * Get the function call the expression is made of, if any
+     */
+    pub fn get_call(&self) -> Option<&FnCall> {
+        let assignment = self.get_call_result()?;
+
+        if let [_, returned] = self.body.as_slice() {
+            if returned.get_type() != Ops::Return {
+                return None;
+            }
+
+            let returned = &downcast_val::<ReturnStatement>(returned.as_self()).value;
+
+            let returns_assignment = returned.interface == Ops::Reference
+                && downcast_val::<Reference>(returned.value.as_self()).0 == assignment.def_name;
+
+            if returns_assignment {
+                return Some(downcast_val::<FnCall>(
+                    assignment.assignment.value.as_self(),
+                ));
+            }
+        }
+
+        None
+    }
 }
 
 impl PrimitiveValueBase for Expression {
@@ -41,7 +103,8 @@ impl ExpressionBase for Expression {
         Self {
             token_type: Ops::Expression,
             body: Vec::new(),
-            expr_id: Uuid::new_v4().to_string(),
+            // Inner blocks get their own scope when they are executed
+            expr_id: GLOBAL_SCOPE,
         }
     }
     /*
@@ -51,8 +114,7 @@ impl ExpressionBase for Expression {
         Self {
             token_type: Ops::Expression,
             body,
-            // TODO: Move away from Uuid
-            expr_id: Uuid::new_v4().to_string(),
+            expr_id: GLOBAL_SCOPE,
         }
     }
 }
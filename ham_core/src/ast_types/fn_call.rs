@@ -6,17 +6,21 @@ use crate::{
     primitive_values::primitive_base::PrimitiveValueBase,
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* FUNCTION CALL  */
 
-#[derive(Clone, Serialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct FnCall {
     pub token_type: Ops,
     pub fn_name: String,
     pub arguments: Vec<BoxedValue>,
     pub reference_to: Option<String>,
+    pub line: usize,
 }
 
 impl AstBase for FnCall {
@@ -39,6 +43,7 @@ impl FnCallBase for FnCall {
             fn_name,
             arguments: Vec::new(),
             reference_to,
+            line: 0,
         }
     }
 }
@@ -1,9 +1,18 @@
 use crate::{
-    ast_types::ast_base::AstBase,
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+    },
     utils::Ops,
 };
-use serde::Serialize;
-use std::any::Any;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    rc::Rc,
+};
 
 /* FUNCTION DEFINITION */
 pub trait FnDefinitionBase {
@@ -11,11 +20,27 @@ pub trait FnDefinitionBase {
     fn new(def_name: String, body: Vec<Box<dyn self::AstBase>>, arguments: Vec<String>) -> Self;
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FnDefinition {
     pub def_name: String,
-    pub body: Vec<Box<dyn self::AstBase>>,
+    // Shared with the function definitions created from it
+    pub body: Rc<Vec<Box<dyn self::AstBase>>>,
     pub arguments: Vec<String>,
+    // Arguments declared with `mut`
+    pub mutable_arguments: Vec<String>,
+    // Annotated type of every argument, ex: `fn a(b: number)`
+    pub argument_types: Vec<Option<Ops>>,
+    // Default value of the arguments which can be left out, ex: `fn a(b = 1)`
+    pub defaults: Vec<(String, BoxedValue)>,
+    // Argument collecting the rest of the arguments into a list, ex: `fn a(...b)`
+    pub rest_argument: Option<String>,
+    // Annotated returned type, ex: `fn a() -> number`
+    pub return_type: Option<Ops>,
+    // Its body yields values, so calling it creates a generator
+    pub generator: bool,
+    // Declared with `pub`, so modules export it
+    pub public: bool,
+    pub line: usize,
 }
 
 impl FnDefinitionBase for FnDefinition {
@@ -25,8 +50,16 @@ impl FnDefinitionBase for FnDefinition {
     fn new(def_name: String, body: Vec<Box<dyn self::AstBase>>, arguments: Vec<String>) -> Self {
         Self {
             def_name,
-            body,
+            body: Rc::new(body),
+            argument_types: vec![None; arguments.len()],
             arguments,
+            mutable_arguments: Vec::new(),
+            defaults: Vec::new(),
+            rest_argument: None,
+            return_type: None,
+            generator: false,
+            public: false,
+            line: 0,
         }
     }
 }
@@ -0,0 +1,49 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+    },
+    utils::Ops,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::any::Any;
+
+/* FOR BLOCK  */
+
+/*
+ * Runs its body for every value of a list or a generator, ex: `for line in lines {}`
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct For {
+    pub var_name: String,
+    pub iterable: BoxedValue,
+    pub body: Vec<Box<dyn self::AstBase>>,
+    pub line: usize,
+}
+
+impl AstBase for For {
+    fn get_type(&self) -> Ops {
+        Ops::ForDef
+    }
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub trait ForBase {
+    fn new(var_name: String, iterable: BoxedValue, body: Vec<Box<dyn self::AstBase>>) -> Self;
+}
+
+impl ForBase for For {
+    fn new(var_name: String, iterable: BoxedValue, body: Vec<Box<dyn self::AstBase>>) -> Self {
+        Self {
+            var_name,
+            iterable,
+            body,
+            line: 0,
+        }
+    }
+}
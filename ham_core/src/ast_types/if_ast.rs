@@ -5,7 +5,10 @@ use crate::{
     },
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* IF STATEMENT */
@@ -13,7 +16,7 @@ pub trait IfConditionalBase {
     fn new(conditions: Vec<ResultExpression>, body: Vec<Box<dyn self::AstBase>>) -> Self;
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct IfConditional {
     pub conditions: Vec<ResultExpression>,
     pub body: Vec<Box<dyn self::AstBase>>,
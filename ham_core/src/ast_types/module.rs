@@ -5,15 +5,44 @@ use crate::{
     },
     utils::Ops,
 };
-use serde::Serialize;
-use std::any::Any;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    rc::Rc,
+};
 
 /* MODULE STATEMENT */
 
-#[derive(Clone, Debug, Serialize)]
+/*
+ * What is taken from an imported module
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum Import {
+    // Everything through a variable, ex: `import x from "./x.ham"` or `import * as x from "./x.ham"`
+    Namespace(String),
+    // Some items by their name and the name they take, ex: `import { a, b as c } from "./x.ham"`
+    Items(Vec<(String, String)>),
+}
+
+/*
+ * Imported module
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Module {
-    pub name: String,
+    pub import: Import,
+    // Where it was found, its code only runs the first time it's imported
+    pub path: String,
+    // Shared by every import of the module
+    pub body: Rc<Vec<Box<dyn AstBase>>>,
+    // Functions declared with `pub`
     pub functions: Vec<FnDefinition>,
+    // Variables and constants declared with `pub`
+    pub values: Vec<String>,
+    // Implemented in Rust, ex: `std:math`, its items are registered in the stack
+    pub native: bool,
 }
 
 impl AstBase for Module {
@@ -0,0 +1,24 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::primitive_base::PrimitiveValueBase,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::any::Any;
+
+/*
+ * Argument given by the name of the function's argument, ex: `b = 2` in `calc(1, b = 2)`
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NamedArgument {
+    pub name: String,
+    pub value: BoxedValue,
+}
+
+impl PrimitiveValueBase for NamedArgument {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
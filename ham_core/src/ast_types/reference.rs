@@ -1,5 +1,8 @@
 use crate::primitive_values::primitive_base::PrimitiveValueBase;
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /*
@@ -10,7 +13,7 @@ use std::any::Any;
  *
  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Reference(pub String);
 
 // Implement base methods for REFERENCE
@@ -5,7 +5,10 @@ use crate::{
     },
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* RESULT EXPRESSION */
@@ -13,7 +16,7 @@ pub trait ResultExpressionBase {
     fn new(relation: Ops, left: BoxedValue, right: BoxedValue) -> Self;
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ResultExpression {
     pub left: BoxedValue,
     pub relation: Ops,
@@ -2,17 +2,45 @@ use crate::{
     ast_types::{
         ast_base::AstBase,
         boxed_val::BoxedValue,
+        expression::Expression,
+        fn_call::FnCall,
     },
+    runtime::downcast_val,
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* RETURN STATEMENT */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ReturnStatement {
     pub value: BoxedValue,
+    pub line: usize,
+}
+
+impl ReturnStatement {
+    /*
+     * Get the function call whose result is returned directly, if any (ex: `return calc(value)`)
+     *
+     * Calls to methods are not included
+     */
+    pub fn get_tail_call(&self) -> Option<&FnCall> {
+        let fn_call = match self.value.interface {
+            Ops::FnCall => downcast_val::<FnCall>(self.value.value.as_self()),
+            Ops::Expression => downcast_val::<Expression>(self.value.value.as_self()).get_call()?,
+            _ => return None,
+        };
+
+        if fn_call.reference_to.is_none() {
+            Some(fn_call)
+        } else {
+            None
+        }
+    }
 }
 
 impl AstBase for ReturnStatement {
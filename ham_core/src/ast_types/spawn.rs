@@ -0,0 +1,41 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        fn_call::FnCall,
+        fn_def::FnDefinition,
+    },
+    primitive_values::primitive_base::PrimitiveValueBase,
+    utils::Ops,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::any::Any;
+
+/* SPAWN */
+
+/*
+ * Runs a function call as a task, ex: `spawn work(1)`, or the body of a function, ex: `spawn fn() {}`
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Spawn {
+    pub call: FnCall,
+    // Function declared in place, it's called without arguments
+    pub function: Option<FnDefinition>,
+}
+
+impl AstBase for Spawn {
+    fn get_type(&self) -> Ops {
+        Ops::Spawn
+    }
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl PrimitiveValueBase for Spawn {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
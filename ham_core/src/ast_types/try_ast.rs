@@ -0,0 +1,40 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+    },
+    utils::Ops,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::any::Any;
+
+/* TRY STATEMENT */
+
+/*
+ * Returns the value from the current function if it's `none` or an error, ex: `let text = fs.read_to_string(path)?`
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TryStatement {
+    pub value: BoxedValue,
+}
+
+impl TryStatement {
+    /*
+     * Check if a value must be returned
+     */
+    pub fn returns(value: &BoxedValue) -> bool {
+        matches!(value.interface, Ops::None | Ops::Error)
+    }
+}
+
+impl AstBase for TryStatement {
+    fn get_type(&self) -> Ops {
+        Ops::Try
+    }
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
@@ -5,7 +5,10 @@ use crate::{
     },
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* VARIABLE ASSIGNMENT */
@@ -14,10 +17,11 @@ pub trait VarAssignmentBase {
     fn new(var_name: String, assignment: BoxedValue) -> Self;
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct VarAssignment {
     pub var_name: String,
     pub assignment: BoxedValue,
+    pub line: usize,
 }
 
 impl VarAssignmentBase for VarAssignment {
@@ -28,6 +32,7 @@ impl VarAssignmentBase for VarAssignment {
         Self {
             var_name,
             assignment,
+            line: 0,
         }
     }
 }
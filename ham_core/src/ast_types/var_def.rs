@@ -5,7 +5,10 @@ use crate::{
     },
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* VARIABLE DEFINITION */
@@ -14,10 +17,19 @@ pub trait VarDefinitionBase {
     fn new(def_name: String, assignment: BoxedValue) -> Self;
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct VarDefinition {
     pub def_name: String,
     pub assignment: BoxedValue,
+    // Declared with `let mut`
+    pub mutable: bool,
+    // Declared with `const`
+    pub constant: bool,
+    // Annotated type, ex: `let a: number = 1`
+    pub var_type: Option<Ops>,
+    // Declared with `pub`, so modules export it
+    pub public: bool,
+    pub line: usize,
 }
 
 impl VarDefinitionBase for VarDefinition {
@@ -28,6 +40,11 @@ impl VarDefinitionBase for VarDefinition {
         Self {
             def_name,
             assignment,
+            mutable: false,
+            constant: false,
+            var_type: None,
+            public: false,
+            line: 0,
         }
     }
 }
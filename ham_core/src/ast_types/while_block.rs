@@ -5,12 +5,15 @@ use crate::{
     },
     utils::Ops,
 };
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /* WHILE BLOCK  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct While {
     pub body: Vec<Box<dyn self::AstBase>>,
     pub conditions: Vec<ResultExpression>,
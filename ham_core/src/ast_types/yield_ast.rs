@@ -0,0 +1,31 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+    },
+    utils::Ops,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::any::Any;
+
+/* YIELD STATEMENT */
+
+/*
+ * Gives a value to whoever resumed the generator, which waits there until it's resumed again
+ */
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct YieldStatement {
+    pub value: BoxedValue,
+}
+
+impl AstBase for YieldStatement {
+    fn get_type(&self) -> Ops {
+        Ops::Yield
+    }
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
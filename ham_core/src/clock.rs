@@ -0,0 +1,63 @@
+use std::{
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/*
+ * Time as seen by the code, used by the built-ins that wait
+ */
+#[derive(Clone, Debug)]
+pub enum Clock {
+    // Wall-clock time since the clock was started
+    Real(Instant),
+    // Time which only passes when all the code is waiting, so every run behaves the same
+    Virtual(Duration),
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::Real(Instant::now())
+    }
+}
+
+impl Clock {
+    /*
+     * Virtual clock starting at zero
+     */
+    pub fn virtual_time() -> Self {
+        Clock::Virtual(Duration::ZERO)
+    }
+
+    /*
+     * Time elapsed since the clock was started
+     */
+    pub fn now(&self) -> Duration {
+        match self {
+            Clock::Real(started) => started.elapsed(),
+            Clock::Virtual(now) => *now,
+        }
+    }
+
+    /*
+     * Wait until the clock reaches the given time, the virtual clock jumps straight to it
+     *
+     * The real clock doesn't wait past the given instant, if any
+     */
+    pub fn sleep_until(&mut self, time: Duration, limit: Option<Instant>) {
+        match self {
+            Clock::Real(started) => {
+                let mut wait = time.saturating_sub(started.elapsed());
+
+                if let Some(limit) = limit {
+                    wait = wait.min(limit.saturating_duration_since(Instant::now()));
+                }
+
+                thread::sleep(wait)
+            }
+            Clock::Virtual(now) => *now = time.max(*now),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        channel::Channel,
+        list::List,
+        task::Task,
+    },
+    stack::Stack,
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::{
+    collections::VecDeque,
+    mem,
+    sync::Mutex,
+    time::Duration,
+};
+
+/*
+ * What some blocked code is waiting for
+ */
+#[derive(Clone)]
+pub enum Wait {
+    // The clock to reach the given time
+    Timer(Duration),
+    // A message from the channel, which is given
+    Message(Channel),
+    // The task to finish, its result is given
+    Task(Task),
+    // All the tasks to finish, a list with their results is given
+    All(Vec<Task>),
+}
+
+impl Wait {
+    /*
+     * Whether it's ready, at the given time of the clock
+     */
+    fn is_ready(&self, now: Duration) -> bool {
+        match self {
+            Wait::Timer(deadline) => now >= *deadline,
+            Wait::Message(channel) => channel.has_messages(),
+            Wait::Task(task) => task.is_finished(),
+            Wait::All(tasks) => tasks.iter().all(Task::is_finished),
+        }
+    }
+
+    /*
+     * Take the value that was waited for, once it's ready
+     */
+    fn take(&self) -> Option<BoxedValue> {
+        match self {
+            Wait::Timer(_) => None,
+            Wait::Message(channel) => channel.take_message(),
+            Wait::Task(task) => task.result(),
+            Wait::All(tasks) => Some(BoxedValue {
+                interface: Ops::List,
+                value: Box::new(List(tasks.iter().filter_map(Task::result).collect())),
+            }),
+        }
+    }
+
+    fn deadline(&self) -> Option<Duration> {
+        match self {
+            Wait::Timer(deadline) => Some(*deadline),
+            _ => None,
+        }
+    }
+}
+
+/*
+ * Schedules the spawned tasks, they run one at a time until they wait for something
+ */
+#[derive(Clone, Default)]
+pub struct EventLoop {
+    // Tasks which haven't finished, the running one is taken out while it runs
+    tasks: VecDeque<Task>,
+    // Set while a task calls a native function, which can then suspend the task
+    pub(crate) suspendable: bool,
+    // What the running task waits for, set by the native function that suspended it
+    pub(crate) blocked: Option<Wait>,
+}
+
+impl EventLoop {
+    /*
+     * Schedule a task, it will run once what it waits for is ready
+     */
+    pub(crate) fn push(&mut self, task: Task) {
+        self.tasks.push_back(task);
+    }
+
+    /*
+     * Take the first task that can run, along with the value it was waiting for
+     */
+    fn take_ready(&mut self, now: Duration) -> Option<(Task, Option<BoxedValue>)> {
+        let index = self
+            .tasks
+            .iter()
+            .position(|task| task.waiting_for().is_none_or(|wait| wait.is_ready(now)))?;
+
+        let task = self.tasks.remove(index)?;
+        let value = task.waiting_for().and_then(|wait| wait.take());
+
+        Some((task, value))
+    }
+
+    fn earliest_deadline(&self) -> Option<Duration> {
+        self.tasks
+            .iter()
+            .filter_map(|task| task.waiting_for()?.deadline())
+            .min()
+    }
+}
+
+/*
+ * Wait for something, returns the value it gives
+ *
+ * A task calling a native function that waits is suspended, and resumed by the event loop
+ * once it's ready. Any other code keeps running the tasks in the meantime
+ */
+pub(crate) fn block_on(wait: Wait, stack: &Mutex<Stack>) -> Option<BoxedValue> {
+    {
+        let mut stack = stack.lock().unwrap();
+        if mem::take(&mut stack.event_loop.suspendable) {
+            stack.event_loop.blocked = Some(wait);
+            return None;
+        }
+    }
+
+    loop {
+        let now = stack.lock().unwrap().clock.now();
+
+        if wait.is_ready(now) {
+            return wait.take();
+        }
+
+        if stack.lock().unwrap().aborted.is_some() {
+            return None;
+        }
+
+        if !run_next(stack, wait.deadline()) {
+            stack
+                .lock()
+                .unwrap()
+                .raise_error(errors::CODES::Deadlock, vec![]);
+            return None;
+        }
+    }
+}
+
+/*
+ * Run the spawned tasks until they finish, the ones waiting for something that
+ * will never happen are dropped
+ */
+pub fn run_tasks(stack: &Mutex<Stack>) {
+    while stack.lock().unwrap().aborted.is_none() && run_next(stack, None) {}
+
+    stack.lock().unwrap().event_loop.tasks.clear();
+}
+
+/*
+ * Run the next task that is ready, or wait until a timer is due if none is
+ *
+ * Returns false if nothing can make progress
+ */
+fn run_next(stack: &Mutex<Stack>, deadline: Option<Duration>) -> bool {
+    let ready = {
+        let mut stack = stack.lock().unwrap();
+        let now = stack.clock.now();
+        stack.event_loop.take_ready(now)
+    };
+
+    if let Some((task, value)) = ready {
+        task.run(value, stack);
+        return true;
+    }
+
+    let earliest = stack
+        .lock()
+        .unwrap()
+        .event_loop
+        .earliest_deadline()
+        .into_iter()
+        .chain(deadline)
+        .min();
+
+    if let Some(earliest) = earliest {
+        let mut stack = stack.lock().unwrap();
+
+        // Waiting can't go past the timeout, which is hit right after
+        let limit = stack.timeout_deadline();
+        stack.clock.sleep_until(earliest, limit);
+        stack.tick_by(0);
+
+        true
+    } else {
+        false
+    }
+}
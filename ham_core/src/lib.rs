@@ -1,5 +1,6 @@
 use crate::{
     ast_types::{
+        ast_base::AstBase,
         boxed_val::BoxedValue,
         break_ast::{
             Break,
@@ -19,14 +20,28 @@ use crate::{
             FnDefinition,
             FnDefinitionBase,
         },
+        for_block::{
+            For,
+            ForBase,
+        },
         get_assignment_token_fn,
         get_tokens_from_to_fn,
         if_ast::{
             IfConditional,
             IfConditionalBase,
         },
-        module::Module,
+        module::{
+            Import,
+            Module,
+        },
+        named_argument::NamedArgument,
+        reference::{
+            Reference,
+            ReferenceValueBase,
+        },
         return_ast::ReturnStatement,
+        spawn::Spawn,
+        try_ast::TryStatement,
         var_assign::{
             VarAssignment,
             VarAssignmentBase,
@@ -39,10 +54,25 @@ use crate::{
             While,
             WhileBase,
         },
+        yield_ast::YieldStatement,
     },
+    event_loop::run_tasks,
+    limits::{
+        Abort,
+        DEFAULT_MAX_CALL_DEPTH,
+    },
+    modules::{
+        define_module,
+        is_native,
+        load_module,
+        load_native_module,
+        resolve_module,
+        ModuleScope,
+    },
+    permissions::Capability,
     runtime::{
         downcast_val,
-        get_methods_in_type,
+        prepare_call,
         resolve_reference,
         value_to_string,
         values_to_strings,
@@ -50,11 +80,12 @@ use crate::{
     stack::{
         FunctionDef,
         FunctionsContainer,
+        Scope,
         Stack,
         VariableDef,
+        GLOBAL_SCOPE,
     },
     types::{
-        BoxedPrimitiveValue,
         IndexedTokenList,
         LinesList,
         Token,
@@ -68,20 +99,41 @@ use crate::{
 };
 use regex::Regex;
 use std::{
-    collections::HashMap,
-    fs,
+    mem,
+    panic,
     sync::Mutex,
+    thread,
 };
-use uuid::Uuid;
 
 pub mod ast_types;
+pub mod clock;
+pub mod event_loop;
+pub mod limits;
+pub mod modules;
+pub mod mutability;
+pub mod optimize;
+pub mod permissions;
 pub mod primitive_values;
 pub mod runtime;
 pub mod stack;
+pub mod stdlib;
+pub mod streams;
+pub mod typecheck;
 pub mod types;
 pub mod utils;
+pub mod vm;
 
-use primitive_values::string::StringVal;
+use primitive_values::{
+    error::ErrorVal,
+    generator::{
+        Generator,
+        GeneratorStep,
+    },
+    list::List,
+    string::StringVal,
+    tail_call::TailCall,
+    task::Task,
+};
 
 /*
  * Split the text by the passed regex but also keep these words which are removed when splitting
@@ -118,7 +170,7 @@ fn get_lines(code: String) -> LinesList {
 
         let mut line_ast = Vec::new();
 
-        let re = Regex::new(r#"([\s+,.:])|("(.*?)")|([()])"#).unwrap();
+        let re = Regex::new(r#"([\s+,.:])|("(.*?)")|([(){}?])"#).unwrap();
 
         // Every detected word
         for word in split(&re, line) {
@@ -140,7 +192,21 @@ fn transform_into_tokens(lines: LinesList) -> TokensList {
     let mut tokens = Vec::new();
 
     for (i, line) in lines.iter().enumerate() {
-        for word in line {
+        let mut words = line.iter();
+
+        while let Some(word) = words.next() {
+            // `&mut x` is a single reference
+            if word == "&mut" {
+                if let Some(var_name) = words.next() {
+                    tokens.push(Token {
+                        ast_type: Ops::Reference,
+                        value: format!("&mut {}", var_name),
+                        line: i + 1,
+                    });
+                    continue;
+                }
+            }
+
             let token_type: Ops = match word.as_str() {
                 "let" => Ops::VarDef,
                 "=" => Ops::LeftAssign,
@@ -159,6 +225,16 @@ fn transform_into_tokens(lines: LinesList) -> TokensList {
                 "import" => Ops::Import,
                 "from" => Ops::FromModule,
                 "break" => Ops::Break,
+                "mut" => Ops::Mut,
+                "const" => Ops::ConstDef,
+                ":" => Ops::TypeAnnotation,
+                "->" => Ops::ReturnType,
+                "yield" => Ops::Yield,
+                "for" => Ops::ForDef,
+                "in" => Ops::In,
+                "spawn" => Ops::Spawn,
+                "pub" => Ops::Pub,
+                "?" => Ops::Try,
                 _ => Ops::Reference,
             };
 
@@ -185,8 +261,15 @@ pub fn get_tokens(code: String) -> TokensList {
 
 /*
  * Create a ast tree from some tokens
+ *
+ * Errors found while parsing are reported through the stack
  */
-pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, filedir: String) {
+pub fn move_tokens_into_ast(
+    tokens: TokensList,
+    ast_tree: &Mutex<Expression>,
+    filedir: String,
+    stack: &Mutex<Stack>,
+) {
     let mut ast_tree = ast_tree.lock().unwrap();
 
     // Closure version of above
@@ -222,15 +305,152 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
         found_tokens
     };
 
+    // Read a spawned task, ex: `spawn work(1)` or `spawn fn() {}`, returns how many tokens it takes
+    let get_spawn = |token_n: usize| -> (usize, Spawn) {
+        let target = &tokens[token_n + 1];
+
+        if target.ast_type == Ops::FnDef {
+            // Ignore `spawn fn`
+            let arguments_tokens =
+                get_tokens_in_group_of(token_n + 2, Ops::OpenParent, Ops::CloseParent);
+
+            // The function is called without arguments
+            if let Some(token) = arguments_tokens.first() {
+                stack
+                    .lock()
+                    .unwrap()
+                    .raise_error(errors::CODES::UnexpectedKeyword, vec![token.value.clone()]);
+            }
+
+            // Ignore `spawn fn`, (, arguments and )
+            let open_block_index = token_n + 4 + arguments_tokens.len();
+
+            let block_tokens =
+                get_tokens_in_group_of(open_block_index, Ops::OpenBlock, Ops::CloseBlock);
+
+            let scope_tree = Mutex::new(Expression::new());
+            move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone(), stack);
+
+            let body = scope_tree.lock().unwrap().body.clone();
+            let mut function = FnDefinition::new("spawn".to_string(), body, Vec::new());
+            function.generator = contains_yield(&function.body);
+            function.line = target.line;
+
+            let mut call = FnCall::new(function.def_name.clone(), None);
+            call.line = target.line;
+
+            (
+                open_block_index + block_tokens.len() + 2 - token_n,
+                Spawn {
+                    call,
+                    function: Some(function),
+                },
+            )
+        } else {
+            // Methods of variables, ex: `spawn messages.recv()`
+            let is_method =
+                tokens.get(token_n + 2).map(|token| token.ast_type) == Some(Ops::PropAccess);
+
+            let (reference_to, name_index) = if is_method {
+                (Some(target.value.clone()), token_n + 3)
+            } else {
+                (None, token_n + 1)
+            };
+
+            if tokens.get(name_index + 1).map(|token| token.ast_type) != Some(Ops::OpenParent) {
+                stack.lock().unwrap().raise_error(
+                    errors::CODES::UnexpectedKeyword,
+                    vec![tokens[name_index].value.clone()],
+                );
+            }
+
+            let arguments_tokens =
+                get_tokens_in_group_of(name_index + 1, Ops::OpenParent, Ops::CloseParent);
+
+            let mut call = FnCall::new(tokens[name_index].value.clone(), reference_to);
+            call.arguments = convert_tokens_into_arguments(arguments_tokens.clone());
+            call.line = tokens[name_index].line;
+
+            // Ignore the function name, (, arguments and )
+            (
+                name_index + 3 + arguments_tokens.len() - token_n,
+                Spawn {
+                    call,
+                    function: None,
+                },
+            )
+        }
+    };
+
     let get_assignment_token = |val: String, token_n: usize| -> (usize, BoxedValue) {
+        // Spawned tasks can be used as values, ex: `let task = spawn work()`
+        if tokens[token_n].ast_type == Ops::Spawn {
+            let (size, spawn) = get_spawn(token_n);
+            return (
+                size,
+                BoxedValue {
+                    interface: Ops::Spawn,
+                    value: Box::new(spawn),
+                },
+            );
+        }
+
         get_assignment_token_fn(val, token_n, tokens.clone(), Directions::LeftToRight)
     };
 
+    // Read the type of an annotation, ex: `number` in `let a: number = 1`
+    let get_annotated_type = |token: &Token| -> Option<Ops> {
+        let annotated_type = Ops::from_type_name(&token.value);
+
+        if annotated_type.is_none() {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::UnknownType,
+                vec![token.value.clone(), token.line.to_string()],
+            );
+        }
+
+        annotated_type
+    };
+
+    // Check if a value is followed by `?`, ex: `let a = b()?`
+    let is_tried = |token_n: usize| -> bool {
+        tokens.get(token_n).map(|token| token.ast_type) == Some(Ops::Try)
+    };
+
+    // Return the variable's value if it's `none` or an error
+    let try_variable = |var_name: &str| -> TryStatement {
+        TryStatement {
+            value: BoxedValue {
+                interface: Ops::Reference,
+                value: Box::new(Reference::new(var_name.to_string())),
+            },
+        }
+    };
+
     let mut token_n = 0;
 
+    // Set by `pub`, for the next declaration
+    let mut public = false;
+
     while token_n < tokens.len() {
         let current_token = &tokens[token_n];
         match current_token.ast_type {
+            // Declarations exported by modules, ex: `pub fn a() {}`
+            Ops::Pub => {
+                let declaration = tokens.get(token_n + 1).map(|token| token.ast_type);
+
+                if matches!(declaration, Some(Ops::FnDef | Ops::VarDef | Ops::ConstDef)) {
+                    public = true;
+                } else {
+                    stack.lock().unwrap().raise_error(
+                        errors::CODES::UnexpectedKeyword,
+                        vec![current_token.value.clone()],
+                    );
+                }
+
+                token_n += 1;
+            }
+
             // Break statement
             Ops::Break => {
                 let break_ast = Break::new();
@@ -240,57 +460,85 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
 
             // Import statement
             Ops::Import => {
-                let module_name = &tokens[token_n + 1].value;
-                let module_direction = &tokens[token_n + 2];
-                let module_origin = &tokens[token_n + 3].value;
                 /*
                  * import x from "./x.ham"
+                 * import * as x from "./x.ham"
+                 * import { a, b as c } from "./x.ham"
                  *
-                 * module_name: x
+                 * import: what is taken from the module
                  * module_direction: from
                  * module_origin= "./x.ham"
                  */
+                let (import, direction_index) = match &tokens[token_n + 1] {
+                    token if token.ast_type == Ops::OpenBlock => {
+                        let item_tokens =
+                            get_tokens_in_group_of(token_n + 1, Ops::OpenBlock, Ops::CloseBlock);
+                        let items = get_imported_items(&item_tokens, stack);
+
+                        (Import::Items(items), token_n + item_tokens.len() + 3)
+                    }
+                    token if token.value == "*" => {
+                        if tokens[token_n + 2].value != "as" {
+                            stack.lock().unwrap().raise_error(
+                                errors::CODES::UnexpectedKeyword,
+                                vec![tokens[token_n + 2].value.clone()],
+                            )
+                        }
+
+                        (
+                            Import::Namespace(tokens[token_n + 3].value.clone()),
+                            token_n + 4,
+                        )
+                    }
+                    token => (Import::Namespace(token.value.clone()), token_n + 2),
+                };
+
+                let module_direction = &tokens[direction_index];
+                let module_origin = &tokens[direction_index + 1].value;
 
                 if module_direction.ast_type != Ops::FromModule {
-                    errors::raise_error(
+                    stack.lock().unwrap().raise_error(
                         errors::CODES::UnexpectedKeyword,
                         vec![module_direction.value.clone()],
                     )
                 }
-                // Module's path
-                let filepath = format!("{}/{}", filedir, module_origin.replace('"', ""));
+                let specifier = module_origin.replace('"', "");
 
-                // Module's code
-                let filecontent = fs::read_to_string(filepath.as_str());
-
-                if let Ok(filecontent) = filecontent {
-                    let tokens = get_tokens(filecontent);
-
-                    // Move all the tokens into a expression
-                    let scope_tree = Mutex::new(Expression::new());
-                    move_tokens_into_ast(tokens.clone(), &scope_tree, filedir.clone());
-
-                    // Copy all root-functions (public by default) from the expression body to the vector
-                    let mut public_functions = Vec::new();
-
-                    for op in scope_tree.lock().unwrap().body.iter() {
-                        if op.get_type() == Ops::FnDef {
-                            public_functions
-                                .push(downcast_val::<FnDefinition>(op.as_self()).clone());
-                        }
+                if is_native(&specifier) {
+                    if let Some(module) = load_native_module(import, &specifier, stack) {
+                        ast_tree.body.push(Box::new(module));
                     }
-
-                    let module = Module {
-                        name: module_name.to_string(),
-                        functions: public_functions,
-                    };
-
-                    ast_tree.body.push(Box::new(module));
                 } else {
-                    errors::raise_error(errors::CODES::ModuleNotFound, vec![filepath])
+                    match resolve_module(&specifier, &filedir) {
+                        Ok(filepath) => {
+                            // Importing a module means reading it
+                            let allowed = stack
+                                .lock()
+                                .unwrap()
+                                .check_permission(Capability::Read, &filepath.to_string_lossy());
+
+                            // Module's code, only read if allowed, otherwise the error was already reported
+                            if allowed {
+                                if let Some(module) = load_module(import, &filepath, stack) {
+                                    ast_tree.body.push(Box::new(module));
+                                }
+                            }
+                        }
+                        Err(tried) => {
+                            let tried = tried
+                                .iter()
+                                .map(|path| path.display().to_string())
+                                .collect::<Vec<String>>();
+
+                            stack.lock().unwrap().raise_error(
+                                errors::CODES::ModuleNotFound,
+                                vec![specifier, tried.join(", ")],
+                            )
+                        }
+                    }
                 }
 
-                token_n += 4
+                token_n = direction_index + 2
             }
 
             // While block
@@ -318,7 +566,7 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                     get_tokens_in_group_of(open_block_index, Ops::OpenBlock, Ops::CloseBlock);
 
                 // Move the tokens into the tree
-                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone());
+                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone(), stack);
 
                 // Ignore the whilte body
                 token_n = block_tokens.len() + open_block_index + 1;
@@ -329,6 +577,13 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                 ast_tree.body.push(Box::new(ast_token));
             }
 
+            // Spawned task, ex: `spawn work(1)`
+            Ops::Spawn => {
+                let (size, spawn) = get_spawn(token_n);
+                ast_tree.body.push(Box::new(spawn));
+                token_n += size;
+            }
+
             // Return statement
             Ops::Return => {
                 let next_token = tokens[token_n + 1].clone();
@@ -336,12 +591,65 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                 let (size, return_val) =
                     get_assignment_token(next_token.value.clone(), token_n + 1);
 
-                let ast_token = ReturnStatement { value: return_val };
+                let ast_token = ReturnStatement {
+                    value: return_val,
+                    line: current_token.line,
+                };
                 ast_tree.body.push(Box::new(ast_token));
 
                 token_n += 2 + size;
             }
 
+            // Yield statement
+            Ops::Yield => {
+                let next_token = tokens[token_n + 1].clone();
+
+                let (size, yield_val) = get_assignment_token(next_token.value.clone(), token_n + 1);
+
+                let ast_token = YieldStatement { value: yield_val };
+                ast_tree.body.push(Box::new(ast_token));
+
+                token_n += 1 + size;
+            }
+
+            // For block, ex: `for item in items {}`
+            Ops::ForDef => {
+                let var_name = tokens[token_n + 1].value.clone();
+                let in_token = &tokens[token_n + 2];
+
+                if in_token.ast_type != Ops::In {
+                    stack.lock().unwrap().raise_error(
+                        errors::CODES::UnexpectedKeyword,
+                        vec![in_token.value.clone()],
+                    )
+                }
+
+                let (size, iterable) =
+                    get_assignment_token(tokens[token_n + 3].value.clone(), token_n + 3);
+
+                // Scope tree
+                let scope_tree = Mutex::new(Expression::new());
+
+                // Ignore the variable, `in` and the iterated value
+                let open_block_index = token_n + 3 + size;
+
+                // Get all tokens inside the for block
+                let block_tokens =
+                    get_tokens_in_group_of(open_block_index, Ops::OpenBlock, Ops::CloseBlock);
+
+                // Move the tokens into the tree
+                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone(), stack);
+
+                // Ignore the block body
+                token_n = block_tokens.len() + open_block_index + 1;
+
+                // Create a for definition
+                let body = &scope_tree.lock().unwrap().body.clone();
+                let mut ast_token = For::new(var_name, iterable, body.to_vec());
+                ast_token.line = current_token.line;
+                ast_tree.body.push(Box::new(ast_token));
+            }
+
             // If statement
             Ops::IfConditional => {
                 // Get the if condition tokens
@@ -367,7 +675,7 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                     get_tokens_in_group_of(open_block_index, Ops::OpenBlock, Ops::CloseBlock);
 
                 // Move the tokens into the tree
-                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone());
+                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone(), stack);
 
                 // Ignore the block body
                 token_n = block_tokens.len() + open_block_index + 1;
@@ -388,6 +696,7 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
 
                     let reference_type = match after_next_token.ast_type {
                         Ops::OpenParent => Ops::FnCall,
+                        Ops::LeftAssign => Ops::VarAssign,
                         _ => Ops::Invalid,
                     };
 
@@ -398,6 +707,7 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                         Ops::FnCall => {
                             let mut ast_token =
                                 FnCall::new(next_token.value.clone(), Some(previous_token.value));
+                            ast_token.line = next_token.line;
 
                             // Ignore itself and the (
                             let starting_token = token_n + 2;
@@ -409,17 +719,56 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                             );
                             let arguments = convert_tokens_into_arguments(arguments_tokens.clone());
 
-                            token_n += 2 + arguments_tokens.len();
-
                             ast_token.arguments = arguments;
+
+                            // Ignore the ., function name, (, arguments and )
+                            let after_call = token_n + 4 + arguments_tokens.len();
+
+                            if is_tried(after_call) {
+                                ast_tree.body.push(Box::new(TryStatement {
+                                    value: BoxedValue {
+                                        interface: Ops::FnCall,
+                                        value: Box::new(ast_token),
+                                    },
+                                }));
+                                token_n = after_call + 1;
+                            } else {
+                                token_n += 2 + arguments_tokens.len();
+                                ast_tree.body.push(Box::new(ast_token));
+                            }
+                        }
+                        /*
+                         * Assignments to values of namespaces, ex: `counter.count = 2`
+                         */
+                        Ops::VarAssign => {
+                            let var_name = format!("{}.{}", previous_token.value, next_token.value);
+
+                            let (size, assignment) = get_assignment_token(
+                                tokens[token_n + 3].value.clone(),
+                                token_n + 3,
+                            );
+
+                            let mut ast_token = VarAssignment::new(var_name.clone(), assignment);
+                            ast_token.line = next_token.line;
                             ast_tree.body.push(Box::new(ast_token));
+
+                            // Ignore the ., name, = and the value
+                            token_n += 3 + size;
+
+                            if is_tried(token_n) {
+                                ast_tree.body.push(Box::new(try_variable(&var_name)));
+                                token_n += 1;
+                            }
                         }
                         /*
                          * TODO: Access properties from varibles
                          */
-                        Ops::Reference => {}
-                        _ => (),
+                        _ => {
+                            token_n += 1;
+                        }
                     };
+                } else {
+                    token_n += 1;
                 }
             }
 
@@ -434,14 +783,103 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                 let starting_token = token_n + 2;
 
                 // Get function arguments, WIP
-                let arguments: Vec<String> =
-                    get_tokens_in_group_of(starting_token, Ops::OpenParent, Ops::CloseParent)
-                        .iter()
-                        .map(|token| token.value.clone())
-                        .collect();
+                let arguments_tokens =
+                    get_tokens_in_group_of(starting_token, Ops::OpenParent, Ops::CloseParent);
+
+                let mut arguments = Vec::new();
+                let mut mutable_arguments = Vec::new();
+                let mut argument_types = Vec::new();
+                let mut defaults = Vec::new();
+                let mut rest_argument = None;
+
+                let argument_type_at =
+                    |n: usize| arguments_tokens.get(n).map(|token| token.ast_type);
+
+                let mut argument_n = 0;
+
+                while argument_n < arguments_tokens.len() {
+                    // Arguments declared as `mut x`
+                    let mutable = argument_type_at(argument_n) == Some(Ops::Mut);
+                    if mutable {
+                        argument_n += 1;
+                    }
+
+                    // Argument collecting the rest of the arguments, ex: `...x`
+                    let is_rest = (argument_n..argument_n + 3)
+                        .all(|n| argument_type_at(n) == Some(Ops::PropAccess));
+                    if is_rest {
+                        argument_n += 3;
+                    }
+
+                    let name_token = if let Some(token) = arguments_tokens.get(argument_n) {
+                        token
+                    } else {
+                        break;
+                    };
+                    let name = name_token.value.clone();
+                    argument_n += 1;
+
+                    // Type of the argument, ex: `x: number`
+                    let mut argument_type = None;
+                    if argument_type_at(argument_n) == Some(Ops::TypeAnnotation) {
+                        if let Some(token) = arguments_tokens.get(argument_n + 1) {
+                            argument_type = get_annotated_type(token);
+                        }
+                        argument_n += 2;
+                    }
+
+                    if mutable {
+                        mutable_arguments.push(name.clone());
+                    }
+
+                    if rest_argument.is_some() {
+                        // The rest argument must be the last one
+                        stack.lock().unwrap().raise_error(
+                            errors::CODES::UnexpectedKeyword,
+                            vec![name_token.value.clone()],
+                        );
+                    } else if is_rest {
+                        rest_argument = Some(name);
+                    } else {
+                        // Default value of the argument, ex: `x = 1`
+                        if argument_type_at(argument_n) == Some(Ops::LeftAssign)
+                            && argument_n + 1 < arguments_tokens.len()
+                        {
+                            let (size, value) = get_assignment_token_fn(
+                                arguments_tokens[argument_n + 1].value.clone(),
+                                argument_n + 1,
+                                arguments_tokens.clone(),
+                                Directions::LeftToRight,
+                            );
+                            defaults.push((name.clone(), value));
+                            argument_n += 1 + size;
+                        }
+
+                        arguments.push(name);
+                        argument_types.push(argument_type);
+                    }
+
+                    // Arguments are separated by commas
+                    match arguments_tokens.get(argument_n) {
+                        Some(token) if token.ast_type == Ops::CommaDelimiter => argument_n += 1,
+                        Some(token) => stack.lock().unwrap().raise_error(
+                            errors::CODES::ExpectedComma,
+                            vec![token.value.clone(), token.line.to_string()],
+                        ),
+                        None => {}
+                    }
+                }
 
                 // Ignore function name, (, arguments and )
-                let open_block_index = starting_token + arguments.len() + 2;
+                let mut open_block_index = starting_token + arguments_tokens.len() + 2;
+
+                // Returned type, ex: `-> number`
+                let return_type = if tokens[open_block_index].ast_type == Ops::ReturnType {
+                    open_block_index += 2;
+                    get_annotated_type(&tokens[open_block_index - 1])
+                } else {
+                    None
+                };
 
                 // Get all tokens inside the function block
 
@@ -449,36 +887,69 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                     get_tokens_in_group_of(open_block_index, Ops::OpenBlock, Ops::CloseBlock);
 
                 // Move the tokens into the tree
-                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone());
+                move_tokens_into_ast(block_tokens.clone(), &scope_tree, filedir.clone(), stack);
 
                 // Ignore the function body
                 token_n = block_tokens.len() + open_block_index + 1;
 
                 // Create a function definition
                 let body = &scope_tree.lock().unwrap().body.clone();
-                let ast_token = FnDefinition::new(def_name, body.to_vec(), arguments);
+                let mut ast_token = FnDefinition::new(def_name, body.to_vec(), arguments);
+                ast_token.mutable_arguments = mutable_arguments;
+                ast_token.argument_types = argument_types;
+                ast_token.defaults = defaults;
+                ast_token.rest_argument = rest_argument;
+                ast_token.return_type = return_type;
+                ast_token.generator = contains_yield(body);
+                ast_token.public = mem::take(&mut public);
+                ast_token.line = current_token.line;
                 ast_tree.body.push(Box::new(ast_token));
             }
 
-            // Variable definition
-            Ops::VarDef => {
-                let next_token = tokens[token_n + 1].clone();
+            // Variable and constant definitions
+            Ops::VarDef | Ops::ConstDef => {
+                // let mut x = ...
+                let mutable = tokens[token_n + 1].ast_type == Ops::Mut;
+                let name_index = if mutable { token_n + 2 } else { token_n + 1 };
 
                 // Variable name
-                let def_name = next_token.value.clone();
+                let def_name = tokens[name_index].value.clone();
+
+                // let x: number = ...
+                let var_type = if tokens[name_index + 1].ast_type == Ops::TypeAnnotation {
+                    Some(get_annotated_type(&tokens[name_index + 2]))
+                } else {
+                    None
+                };
 
                 // Value token position
-                let val_index = token_n + 3;
+                let val_index = if var_type.is_some() {
+                    name_index + 4
+                } else {
+                    name_index + 2
+                };
 
                 // Stringified value
                 let def_value = String::from(&tokens[val_index].value.clone());
 
                 let (size, assignment) = get_assignment_token(def_value, val_index);
 
-                let ast_token = VarDefinition::new(def_name, assignment);
+                let mut ast_token = VarDefinition::new(def_name, assignment);
+                ast_token.mutable = mutable;
+                ast_token.constant = current_token.ast_type == Ops::ConstDef;
+                ast_token.var_type = var_type.flatten();
+                ast_token.public = mem::take(&mut public);
+                ast_token.line = current_token.line;
+
+                let var_name = ast_token.def_name.clone();
                 ast_tree.body.push(Box::new(ast_token));
 
-                token_n += size + 3;
+                token_n = val_index + size;
+
+                if is_tried(token_n) {
+                    ast_tree.body.push(Box::new(try_variable(&var_name)));
+                    token_n += 1;
+                }
             }
 
             // References (fn calls, variable reassignation...)
@@ -503,14 +974,24 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
                         let (size, assignment) =
                             get_assignment_token(token_after_equal.value.clone(), token_n + 2);
 
-                        let ast_token = VarAssignment::new(current_token.value.clone(), assignment);
+                        let mut ast_token =
+                            VarAssignment::new(current_token.value.clone(), assignment);
+                        ast_token.line = current_token.line;
 
                         ast_tree.body.push(Box::new(ast_token));
 
                         token_n += 2 + size;
+
+                        if is_tried(token_n) {
+                            ast_tree
+                                .body
+                                .push(Box::new(try_variable(&current_token.value)));
+                            token_n += 1;
+                        }
                     }
                     Ops::FnCall => {
                         let mut ast_token = FnCall::new(current_token.value.clone(), None);
+                        ast_token.line = current_token.line;
 
                         // Ignore itself and the (
                         let starting_token = token_n + 1;
@@ -527,7 +1008,17 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
 
                         ast_token.arguments = arguments;
 
-                        ast_tree.body.push(Box::new(ast_token));
+                        if is_tried(token_n) {
+                            ast_tree.body.push(Box::new(TryStatement {
+                                value: BoxedValue {
+                                    interface: Ops::FnCall,
+                                    value: Box::new(ast_token),
+                                },
+                            }));
+                            token_n += 1;
+                        } else {
+                            ast_tree.body.push(Box::new(ast_token));
+                        }
                     }
                     _ => {
                         token_n += 1;
@@ -541,43 +1032,359 @@ pub fn move_tokens_into_ast(tokens: TokensList, ast_tree: &Mutex<Expression>, fi
     }
 }
 
+/*
+ * Report an error returned with `?` outside of any function, as nothing will handle it,
+ * the execution is aborted with it
+ */
+pub(crate) fn report_unhandled(value: &BoxedValue, stack: &Mutex<Stack>) {
+    let mut stack = stack.lock().unwrap();
+
+    if value.interface == Ops::Error && stack.usage.call_depth == 0 {
+        let error = downcast_val::<ErrorVal>(value.value.as_self());
+        stack.raise_error(errors::CODES::UnhandledError, vec![error.0.clone()]);
+        stack.abort(Abort::Unhandled(error.0.clone()));
+    }
+}
+
+/*
+ * Check if a function's body yields, without looking into the inner functions
+ */
+fn contains_yield(body: &[Box<dyn AstBase>]) -> bool {
+    body.iter().any(|operation| match operation.get_type() {
+        Ops::Yield => true,
+        Ops::IfConditional => {
+            contains_yield(&downcast_val::<IfConditional>(operation.as_self()).body)
+        }
+        Ops::WhileDef => contains_yield(&downcast_val::<While>(operation.as_self()).body),
+        Ops::ForDef => contains_yield(&downcast_val::<For>(operation.as_self()).body),
+        _ => false,
+    })
+}
+
+/*
+ * Get the items of an import and the names they take, ex: `a, b as c`
+ */
+fn get_imported_items(tokens: &[Token], stack: &Mutex<Stack>) -> Vec<(String, String)> {
+    tokens
+        .split(|token| token.ast_type == Ops::CommaDelimiter)
+        .filter_map(|item| match item {
+            [name] => Some((name.value.clone(), name.value.clone())),
+            [name, keyword, alias] if keyword.value == "as" => {
+                Some((name.value.clone(), alias.value.clone()))
+            }
+            [] => None,
+            [_, unexpected, ..] => {
+                stack.lock().unwrap().raise_error(
+                    errors::CODES::UnexpectedKeyword,
+                    vec![unexpected.value.clone()],
+                );
+                None
+            }
+        })
+        .collect()
+}
+
 /*
  * Shorthand to create a function definition
  */
-fn get_function_from_def(function: &FnDefinition) -> FunctionDef {
+pub(crate) fn get_function_from_def(function: &FnDefinition) -> FunctionDef {
     FunctionDef {
         name: function.def_name.clone(),
         body: function.body.clone(),
         arguments: function.arguments.clone(),
-        cb: |args, args_vals, body, stack, ast| {
-            let expr = Expression::from_body(body.clone());
-            let expr_id = expr.expr_id.clone();
-
-            for (i, arg) in args_vals.iter().enumerate() {
-                let arg_name = args[i].clone();
-                let var_id = stack.lock().unwrap().reseve_index();
-                stack.lock().unwrap().push_variable(VariableDef {
-                    name: arg_name,
-                    value: arg.value.clone(),
-                    val_type: arg.interface,
-                    expr_id: expr_id.clone(),
-                    functions: get_methods_in_type(arg.interface),
-                    var_id,
-                })
+        defaults: function.defaults.clone(),
+        rest_argument: function.rest_argument.clone(),
+        module: None,
+        generator: function.generator,
+        cb: |function, args_vals, stack| {
+            // Functions of a module see its variables while they run
+            let module = function
+                .module
+                .as_ref()
+                .filter(|module| module.enter(stack));
+
+            let result = call_function(function, args_vals, stack);
+
+            if let Some(module) = module {
+                module.leave(stack);
             }
 
-            let return_val = run_ast(&Mutex::new(expr), stack);
+            result
+        },
+        expr_id: GLOBAL_SCOPE,
+    }
+}
+
+/*
+ * Run a function defined in the code
+ */
+fn call_function(
+    function: &FunctionDef,
+    args_vals: Vec<BoxedValue>,
+    stack: &Mutex<Stack>,
+) -> Option<BoxedValue> {
+    let mut function = function.clone();
+    let mut args_vals = args_vals;
+
+    // Only the code of tasks can be suspended, not the functions they call
+    stack.lock().unwrap().event_loop.suspendable = false;
+
+    // Tail calls are run in the same loop, so they don't nest
+    loop {
+        // Generators run in the VM, which can suspend them
+        if function.generator {
+            return Generator::start(&function, args_vals, stack);
+        }
+
+        if !stack.lock().unwrap().enter_call() {
+            return None;
+        }
 
-            stack.lock().unwrap().drop_ops_from_id(expr_id);
+        let scope = Scope::new(stack);
 
-            if let Some(return_val) = return_val {
-                resolve_reference(stack, return_val.interface, return_val.value, &ast)
-            } else {
-                return_val
+        if !bind_arguments(&function, args_vals, scope.id, stack) {
+            drop(scope);
+            stack.lock().unwrap().exit_call();
+            return None;
+        }
+
+        let return_val = eval_body(&function.body, scope.id, stack);
+
+        drop(scope);
+        stack.lock().unwrap().exit_call();
+
+        match return_val {
+            Some(return_val) if return_val.interface == Ops::TailCall => {
+                let tail_call = downcast_val::<TailCall>(return_val.value.as_self());
+
+                // Functions of other modules are called the usual way, so they see their module's variables
+                if !ModuleScope::same(&function.module, &tail_call.function.module) {
+                    return (tail_call.function.cb)(
+                        &tail_call.function,
+                        tail_call.arguments.clone(),
+                        stack,
+                    );
+                }
+
+                function = tail_call.function.clone();
+                args_vals = tail_call.arguments.clone();
             }
-        },
-        // TODO: Move away from Uuid
-        expr_id: Uuid::new_v4().to_string(),
+            Some(return_val) => {
+                return resolve_reference(stack, return_val.interface, return_val.value.as_ref())
+            }
+            None => return None,
+        }
+    }
+}
+
+/*
+ * Define the arguments of a function call in the given scope
+ *
+ * Arguments are given by position first and then by name, the ones left out take their
+ * default value and the extra ones are collected into the rest argument as a list
+ *
+ * Returns false if the arguments don't match the function's, the error is reported through the stack
+ */
+pub(crate) fn bind_arguments(
+    function: &FunctionDef,
+    arguments: Vec<BoxedValue>,
+    scope_id: u64,
+    stack: &Mutex<Stack>,
+) -> bool {
+    let given = arguments.len();
+
+    let (named, mut positional): (Vec<BoxedValue>, Vec<BoxedValue>) = arguments
+        .into_iter()
+        .partition(|argument| argument.interface == Ops::NamedArgument);
+
+    let mut named: Vec<NamedArgument> = named
+        .iter()
+        .map(|argument| downcast_val::<NamedArgument>(argument.value.as_self()).clone())
+        .collect();
+
+    let raise_error = |kind: errors::CODES, args: Vec<String>| {
+        stack.lock().unwrap().raise_error(kind, args);
+        false
+    };
+
+    let wrong_count = || {
+        raise_error(
+            errors::CODES::WrongArgumentsCount,
+            vec![
+                function.name.clone(),
+                errors::arguments_count(
+                    function.arguments.len() - function.defaults.len(),
+                    function.arguments.len(),
+                    function.rest_argument.is_some(),
+                ),
+                given.to_string(),
+            ],
+        )
+    };
+
+    if let Some(argument) = named
+        .iter()
+        .find(|argument| !function.arguments.contains(&argument.name))
+    {
+        return raise_error(
+            errors::CODES::UnknownArgument,
+            vec![function.name.clone(), argument.name.clone()],
+        );
+    }
+
+    // Extra arguments
+    let rest = if positional.len() > function.arguments.len() {
+        if function.rest_argument.is_none() {
+            return wrong_count();
+        }
+        positional.split_off(function.arguments.len())
+    } else {
+        Vec::new()
+    };
+
+    let mut positional = positional.into_iter();
+
+    for arg_name in &function.arguments {
+        let by_name = named
+            .iter()
+            .position(|argument| &argument.name == arg_name)
+            .map(|index| named.remove(index).value);
+
+        let value = match (positional.next(), by_name) {
+            (Some(_), Some(_)) => {
+                return raise_error(
+                    errors::CODES::DuplicatedArgument,
+                    vec![function.name.clone(), arg_name.clone()],
+                );
+            }
+            (Some(value), None) | (None, Some(value)) => value,
+            (None, None) => {
+                let default = function
+                    .defaults
+                    .iter()
+                    .find(|(default_name, _)| default_name == arg_name);
+
+                if let Some((_, default)) = default {
+                    // Default values are evaluated on every call, and can use the previous arguments
+                    match resolve_reference(stack, default.interface, default.value.as_ref()) {
+                        Some(value) => value,
+                        None => return false,
+                    }
+                } else {
+                    return wrong_count();
+                }
+            }
+        };
+
+        stack
+            .lock()
+            .unwrap()
+            .push_variable(VariableDef::new(arg_name.clone(), value, scope_id));
+    }
+
+    // The same name was given more than once
+    if let Some(argument) = named.first() {
+        return raise_error(
+            errors::CODES::DuplicatedArgument,
+            vec![function.name.clone(), argument.name.clone()],
+        );
+    }
+
+    if let Some(rest_argument) = &function.rest_argument {
+        stack.lock().unwrap().push_variable(VariableDef::new(
+            rest_argument.clone(),
+            BoxedValue {
+                interface: Ops::List,
+                value: Box::new(List(rest)),
+            },
+            scope_id,
+        ));
+    }
+
+    true
+}
+
+/*
+ * Native stack the tree-walking interpreter needs for every nested function call
+ */
+const STACK_PER_CALL: usize = 64 * 1024;
+
+/*
+ * What the thread running the code borrows from the thread that runs it
+ */
+struct Execution<'a> {
+    ast: &'a Mutex<Expression>,
+    stack: &'a Mutex<Stack>,
+    result: &'a mut Option<Result<Option<BoxedValue>, Abort>>,
+}
+
+// SAFETY: The values of the code (the `Rc`s and `RefCell`s in the stack, the tree and the result)
+// are never used by two threads at the same time. The thread that runs the code waits in
+// `run_in_thread` until the other is over, and nothing the other thread had outlives it
+unsafe impl Send for Execution<'_> {}
+
+/*
+ * Run the code in a thread with enough native stack for the maximum call depth, failing if the
+ * execution was aborted
+ *
+ * Every nested call of the tree-walking interpreter is a native call, and the VM uses it
+ * for the functions it doesn't compile, ex: the ones of imported modules
+ */
+pub(crate) fn run_in_thread(
+    ast: &Mutex<Expression>,
+    stack: &Mutex<Stack>,
+    run: fn(&Mutex<Expression>, &Mutex<Stack>) -> Option<BoxedValue>,
+) -> Result<Option<BoxedValue>, Abort> {
+    let call_depth = stack.lock().unwrap().limits.call_depth();
+    let stack_size = call_depth.max(DEFAULT_MAX_CALL_DEPTH) * STACK_PER_CALL;
+
+    let mut result = None;
+    let execution = Execution {
+        ast,
+        stack,
+        result: &mut result,
+    };
+
+    let res = thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn_scoped(scope, move || {
+                let execution = execution;
+                let res = run(execution.ast, execution.stack);
+                *execution.result = Some(take_abort(res, execution.stack));
+            })
+            .expect("Couldn't start the interpreter's thread")
+            .join()
+    });
+
+    if let Err(error) = res {
+        panic::resume_unwind(error);
+    }
+
+    result.unwrap()
+}
+
+/*
+ * Execute a AST tree, failing if the execution was aborted, ex: by any of the stack's limits
+ */
+pub fn try_run_ast(
+    ast: &Mutex<Expression>,
+    stack: &Mutex<Stack>,
+) -> Result<Option<BoxedValue>, Abort> {
+    run_in_thread(ast, stack, run_ast)
+}
+
+/*
+ * Turn the result of an execution into an error if it was aborted
+ */
+fn take_abort(
+    res: Option<BoxedValue>,
+    stack: &Mutex<Stack>,
+) -> Result<Option<BoxedValue>, Abort> {
+    if let Some(error) = stack.lock().unwrap().aborted.take() {
+        Err(error)
+    } else {
+        Ok(res)
     }
 }
 
@@ -587,30 +1394,67 @@ fn get_function_from_def(function: &FnDefinition) -> FunctionDef {
 pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedValue> {
     let ast = ast.lock().unwrap();
 
+    let res = run_body(&ast.body, ast.expr_id, stack);
+
+    // Spawned tasks keep the program running until they finish
+    run_tasks(stack);
+
+    res
+}
+
+/*
+ * Execute a block of code, everything defined in it belongs to the given scope
+ */
+pub(crate) fn run_body(
+    body: &[Box<dyn AstBase>],
+    scope_id: u64,
+    stack: &Mutex<Stack>,
+) -> Option<BoxedValue> {
+    let res = eval_body(body, scope_id, stack);
+
+    // Only function bodies can run tail calls in place
+    if let Some(res) = &res {
+        if res.interface == Ops::TailCall {
+            let tail_call = downcast_val::<TailCall>(res.value.as_self());
+            return (tail_call.function.cb)(
+                &tail_call.function,
+                tail_call.arguments.clone(),
+                stack,
+            );
+        }
+    }
+
+    res
+}
+
+/*
+ * Execute a block of code, like `run_body` but returned function calls are left for the caller to make
+ */
+fn eval_body(body: &[Box<dyn AstBase>], scope_id: u64, stack: &Mutex<Stack>) -> Option<BoxedValue> {
     // Closure version of resolve_reference
-    let resolve_ref = |val_type: Ops, ref_val: BoxedPrimitiveValue| -> Option<BoxedValue> {
-        resolve_reference(stack, val_type, ref_val, &ast)
+    let resolve_ref = |value: &BoxedValue| -> Option<BoxedValue> {
+        resolve_reference(stack, value.interface, value.value.as_ref())
     };
 
     // Check if a conditional is true or not
     let eval_condition =
-        |condition_code: Ops, left_val: BoxedValue, right_val: BoxedValue| -> bool {
-            let left_val = resolve_ref(left_val.interface, left_val.value.clone());
-            let right_val = resolve_ref(right_val.interface, right_val.value.clone());
+        |condition_code: Ops, left_val: &BoxedValue, right_val: &BoxedValue| -> bool {
+            let left_val = resolve_ref(left_val);
+            let right_val = resolve_ref(right_val);
 
             if let (Some(left_val), Some(right_val)) = (left_val, right_val) {
                 match condition_code {
                     // Handle !=
                     Ops::NotEqualCondition => {
-                        let left_val = value_to_string(left_val, stack).unwrap();
-                        let right_val = value_to_string(right_val, stack).unwrap();
+                        let left_val = value_to_string(left_val).unwrap();
+                        let right_val = value_to_string(right_val).unwrap();
 
                         left_val != right_val
                     }
                     // Handle ==
                     Ops::EqualCondition => {
-                        let left_val = value_to_string(left_val, stack).unwrap();
-                        let right_val = value_to_string(right_val, stack).unwrap();
+                        let left_val = value_to_string(left_val).unwrap();
+                        let right_val = value_to_string(right_val).unwrap();
 
                         left_val == right_val
                     }
@@ -621,7 +1465,12 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
             }
         };
 
-    for operation in &ast.body {
+    for operation in body {
+        // Stop if any limit was hit
+        if !stack.lock().unwrap().tick() {
+            return None;
+        }
+
         match operation.get_type() {
             /*
              * Handle breaks
@@ -638,26 +1487,7 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
              */
             Ops::Module => {
                 let module = downcast_val::<Module>(operation.as_self());
-
-                let var_id = stack.lock().unwrap().reseve_index();
-
-                let mut functions = HashMap::new();
-
-                for function in &module.functions {
-                    let mut function = function.clone();
-                    function.arguments.insert(0, "_".to_string());
-                    functions.insert(function.def_name.clone(), get_function_from_def(&function));
-                }
-
-                // Push the variable into the stack
-                stack.lock().unwrap().push_variable(VariableDef {
-                    name: module.name.clone(),
-                    val_type: Ops::String,
-                    value: Box::new(StringVal(module.name.clone())),
-                    expr_id: ast.expr_id.clone(),
-                    functions,
-                    var_id,
-                });
+                define_module(module, scope_id, stack);
             }
 
             /*
@@ -673,9 +1503,9 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
                      */
                     let mut true_count = 0;
 
-                    for condition in while_block.conditions.clone() {
+                    for condition in &while_block.conditions {
                         let res =
-                            eval_condition(condition.relation, condition.left, condition.right);
+                            eval_condition(condition.relation, &condition.left, &condition.right);
 
                         if res {
                             true_count += 1;
@@ -683,11 +1513,10 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
                     }
 
                     if true_count == while_block.conditions.len() {
-                        let expr = Expression::from_body(while_block.body.clone());
-                        let expr_id = expr.expr_id.clone();
+                        let scope = Scope::new(stack);
 
-                        // Execute the expression block
-                        let if_block_return = run_ast(&Mutex::new(expr), stack);
+                        // Execute the expression block, its definitions are dropped along with the scope
+                        let if_block_return = eval_body(&while_block.body, scope.id, stack);
 
                         /*
                          * While's loop will stop when something is returned forcefully
@@ -696,8 +1525,6 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
                             return Some(if_block_return);
                         }
 
-                        // Clean the expression definitions from the stack
-                        stack.lock().unwrap().drop_ops_from_id(expr_id);
                         Some(BoxedValue {
                             value: Box::new(StringVal("while".to_string())),
                             interface: Ops::WhileDef,
@@ -710,6 +1537,11 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
                 let mut stopped = false;
 
                 while !stopped {
+                    // Every iteration counts, so loops with an empty body are stopped by the limits too
+                    if !stack.lock().unwrap().tick() {
+                        return None;
+                    }
+
                     let res = check_while(while_block);
 
                     if let Some(res) = res {
@@ -732,20 +1564,112 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
                 }
             }
 
+            /*
+             * Handle for blocks
+             */
+            Ops::ForDef => {
+                let for_block = downcast_val::<For>(operation.as_self());
+
+                let generator = resolve_ref(&for_block.iterable)
+                    .and_then(|iterable| Generator::iterate(&iterable, stack));
+
+                if let Some(generator) = generator {
+                    while let GeneratorStep::Yielded(item) = generator.resume(stack) {
+                        if stack.lock().unwrap().aborted.is_some() {
+                            return None;
+                        }
+
+                        let scope = Scope::new(stack);
+
+                        if let Some(item) = item {
+                            stack.lock().unwrap().push_variable(VariableDef::new(
+                                for_block.var_name.clone(),
+                                item,
+                                scope.id,
+                            ));
+                        }
+
+                        match eval_body(&for_block.body, scope.id, stack) {
+                            Some(res) if res.interface == Ops::Break => break,
+                            Some(res) => return Some(res),
+                            None => {}
+                        }
+                    }
+                }
+            }
+
+            /*
+             * Generators run in the VM, so any yield found here is not inside a function
+             */
+            Ops::Yield => {
+                let statement = downcast_val::<YieldStatement>(operation.as_self());
+                let value =
+                    resolve_ref(&statement.value).and_then(|value| value_to_string(value).ok());
+
+                stack.lock().unwrap().raise_error(
+                    errors::CODES::YieldOutsideGenerator,
+                    vec![value.unwrap_or_default()],
+                );
+            }
+
+            /*
+             * Handle `?`, it returns none and errors
+             */
+            Ops::Try => {
+                let statement = downcast_val::<TryStatement>(operation.as_self());
+
+                if let Some(value) = resolve_ref(&statement.value) {
+                    if TryStatement::returns(&value) {
+                        report_unhandled(&value, stack);
+                        return Some(value);
+                    }
+                }
+            }
+
             /*
              * Handle return statements
              */
             Ops::Return => {
                 let statement = downcast_val::<ReturnStatement>(operation.as_self());
 
-                // Type of return
-                let return_type = statement.value.interface;
+                if let Some(fn_call) = statement.get_tail_call() {
+                    let function = stack
+                        .lock()
+                        .unwrap()
+                        .get_function_by_name(fn_call.fn_name.as_str());
+
+                    if let Some(function) = function {
+                        let mut arguments = Vec::new();
 
-                // Value returning
-                let return_val = statement.value.value.clone();
+                        for argument in &fn_call.arguments {
+                            if let Some(arg_ref) = resolve_ref(argument) {
+                                arguments.push(arg_ref);
+                            }
+                        }
+
+                        // Native functions have no body, they are simply called
+                        if function.body.is_empty() {
+                            return (function.cb)(&function, arguments, stack);
+                        }
+
+                        return Some(BoxedValue {
+                            interface: Ops::TailCall,
+                            value: Box::new(TailCall {
+                                function,
+                                arguments,
+                            }),
+                        });
+                    } else {
+                        stack.lock().unwrap().raise_error(
+                            errors::CODES::FunctionNotFound,
+                            vec![fn_call.fn_name.clone()],
+                        );
+                        return None;
+                    }
+                }
 
                 // Pimitive value to return
-                let return_val = resolve_ref(return_type, return_val);
+                let return_val = resolve_ref(&statement.value);
 
                 return return_val;
             }
@@ -762,26 +1686,22 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
                  */
                 let mut true_count = 0;
 
-                for condition in if_statement.conditions.clone() {
-                    let res = eval_condition(condition.relation, condition.left, condition.right);
+                for condition in &if_statement.conditions {
+                    let res = eval_condition(condition.relation, &condition.left, &condition.right);
 
                     if res {
                         true_count += 1;
                     }
                 }
                 if true_count == if_statement.conditions.len() {
-                    let expr = Expression::from_body(if_statement.body.clone());
-                    let expr_id = expr.expr_id.clone();
+                    let scope = Scope::new(stack);
 
-                    // Execute the expression block
-                    let if_block_return = run_ast(&Mutex::new(expr), stack);
+                    // Execute the expression block, its definitions are dropped along with the scope
+                    let if_block_return = eval_body(&if_statement.body, scope.id, stack);
 
                     if let Some(if_block_return) = if_block_return {
                         return Some(if_block_return);
                     }
-
-                    // Clean the expression definitions from the stack
-                    stack.lock().unwrap().drop_ops_from_id(expr_id.clone());
                 }
             }
 
@@ -791,10 +1711,10 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
             Ops::FnDef => {
                 let function = downcast_val::<FnDefinition>(operation.as_self());
 
-                stack
-                    .lock()
-                    .unwrap()
-                    .push_function(get_function_from_def(function));
+                let mut function = get_function_from_def(function);
+                function.expr_id = scope_id;
+
+                stack.lock().unwrap().push_function(function);
             }
 
             /*
@@ -803,24 +1723,15 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
             Ops::VarDef => {
                 let variable = downcast_val::<VarDefinition>(operation.as_self());
 
-                let val_type = variable.assignment.interface;
-                let ref_val = variable.assignment.value.clone();
-
-                let var_ref = resolve_ref(val_type, ref_val);
+                let var_ref = resolve_ref(&variable.assignment);
 
                 if let Some(var_ref) = var_ref {
-                    // Take a id for the stack
-                    let var_id = stack.lock().unwrap().reseve_index();
-
                     // Push the variable into the stack
-                    stack.lock().unwrap().push_variable(VariableDef {
-                        name: variable.def_name.clone(),
-                        val_type: var_ref.interface,
-                        value: var_ref.value,
-                        expr_id: ast.expr_id.clone(),
-                        functions: get_methods_in_type(var_ref.interface),
-                        var_id,
-                    });
+                    stack.lock().unwrap().push_variable(VariableDef::new(
+                        variable.def_name.clone(),
+                        var_ref,
+                        scope_id,
+                    ));
                 }
             }
 
@@ -830,25 +1741,31 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
             Ops::VarAssign => {
                 let variable = downcast_val::<VarAssignment>(operation.as_self());
 
-                let is_pointer = variable.var_name.starts_with('&');
+                let ref_val = resolve_ref(&variable.assignment);
 
-                let variable_name = if is_pointer {
-                    // Remove & from it's name
-                    let mut variable_name = variable.var_name.clone();
-                    variable_name.remove(0);
-                    variable_name
-                } else {
-                    variable.var_name.clone()
-                };
+                if let Some(ref_val) = ref_val {
+                    stack
+                        .lock()
+                        .unwrap()
+                        .modify_var(variable.var_name.clone(), ref_val);
+                }
+            }
 
-                let ref_val = resolve_ref(
-                    variable.assignment.interface,
-                    variable.assignment.value.clone(),
-                );
+            /*
+             * Handle spawned tasks
+             */
+            Ops::Spawn => {
+                let spawn = downcast_val::<Spawn>(operation.as_self());
 
-                if let Some(ref_val) = ref_val {
-                    stack.lock().unwrap().modify_var(variable_name, ref_val);
+                let mut arguments = Vec::new();
+
+                for argument in &spawn.call.arguments {
+                    if let Some(arg_ref) = resolve_ref(argument) {
+                        arguments.push(arg_ref);
+                    }
                 }
+
+                Task::spawn(spawn, arguments, stack);
             }
 
             /*
@@ -857,70 +1774,30 @@ pub fn run_ast(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedVal
             Ops::FnCall => {
                 let fn_call = downcast_val::<FnCall>(operation.as_self());
 
-                let is_referenced = fn_call.reference_to.is_some();
+                let values = fn_call.arguments.iter().filter_map(resolve_ref).collect();
 
-                let function = if is_referenced {
-                    let reference_to = fn_call.reference_to.as_ref().unwrap();
-                    let variable = stack
-                        .lock()
-                        .unwrap()
-                        .get_variable_by_name(reference_to.as_str());
-                    variable
-                        .unwrap()
-                        .get_function_by_name(fn_call.fn_name.as_str())
+                let (function, arguments) = if let Some(call) =
+                    prepare_call(stack, &fn_call.fn_name, &fn_call.reference_to, values)
+                {
+                    call
                 } else {
-                    stack
-                        .lock()
-                        .unwrap()
-                        .get_function_by_name(fn_call.fn_name.as_str())
+                    continue;
                 };
 
-                // If the calling function is found
-                if let Some(function) = function {
-                    let mut arguments = Vec::new();
-
-                    if is_referenced {
-                        let reference_to = fn_call.reference_to.as_ref().unwrap();
-                        arguments.push(BoxedValue {
-                            interface: Ops::String,
-                            value: Box::new(StringVal(reference_to.to_string())),
-                        });
-                    }
+                let res_func = (function.cb)(&function, arguments.clone(), stack);
 
-                    for argument in &fn_call.arguments {
-                        let arg_ref = resolve_ref(argument.interface, argument.value.clone());
-                        if let Some(arg_ref) = arg_ref {
-                            arguments.push(arg_ref);
-                        } else {
-                            // Broken argument
-                        }
-                    }
+                // `none` is the same as returning nothing
+                if let Some(ret_val) = res_func.filter(|value| value.interface != Ops::None) {
+                    let val_stringified = value_to_string(ret_val);
 
-                    let res_func = (function.cb)(
-                        function.arguments,
-                        arguments.clone(),
-                        function.body,
-                        &stack,
-                        &ast,
-                    );
+                    if let Ok(val_stringified) = val_stringified {
+                        // The function returned something that ends up not being used, throw error
+                        let args = values_to_strings(arguments).join(" ");
 
-                    if let Some(ret_val) = res_func {
-                        let val_stringified = value_to_string(ret_val, stack);
-
-                        if let Ok(val_stringified) = val_stringified {
-                            // The function returned something that ends up not being used, throw error
-
-                            errors::raise_error(
-                                errors::CODES::ReturnedValueNotUsed,
-                                vec![
-                                    val_stringified,
-                                    fn_call.fn_name.clone(),
-                                    values_to_strings(arguments, stack).join(" "),
-                                ],
-                            )
-                        }
-                    } else {
-                        // No value returned, OK
+                        stack.lock().unwrap().raise_error(
+                            errors::CODES::ReturnedValueNotUsed,
+                            vec![val_stringified, fn_call.fn_name.clone(), args],
+                        )
                     }
                 }
             }
@@ -0,0 +1,139 @@
+use std::{
+    fmt,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/*
+ * How many nested function calls there can be when no other limit is given
+ */
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/*
+ * Most nested function calls there can be, even with a bigger limit or none at all,
+ * the native stack of the interpreter can't hold more
+ */
+pub const MAX_CALL_DEPTH: usize = 16 * 1024;
+
+/*
+ * Execution limits of the interpreter
+ *
+ * None means there is no limit
+ */
+#[derive(Clone, Debug)]
+pub struct Limits {
+    // How many AST nodes can be evaluated
+    pub max_instructions: Option<u64>,
+
+    // For how long the code can run
+    pub timeout: Option<Duration>,
+
+    // How many nested function calls there can be
+    pub max_call_depth: Option<usize>,
+
+    // How many variables can be held in the stack at the same time
+    pub max_variables: Option<usize>,
+}
+
+impl Limits {
+    /*
+     * How many nested function calls there can actually be
+     */
+    pub fn call_depth(&self) -> usize {
+        self.max_call_depth
+            .map_or(MAX_CALL_DEPTH, |max| max.min(MAX_CALL_DEPTH))
+    }
+}
+
+impl Default for Limits {
+    /*
+     * No limits but the call depth, so deep recursions are aborted instead of crashing the host
+     */
+    fn default() -> Self {
+        Self {
+            max_instructions: None,
+            timeout: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_variables: None,
+        }
+    }
+}
+
+/*
+ * A limit that was hit, the execution is aborted when this happens
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum LimitError {
+    Instructions(u64),
+    Timeout(Duration),
+    CallDepth(usize),
+    Variables(usize),
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::Instructions(max) => {
+                write!(f, "Execution exceeded the limit of {} instructions", max)
+            }
+            LimitError::Timeout(timeout) => {
+                write!(
+                    f,
+                    "Execution exceeded the timeout of {}ms",
+                    timeout.as_millis()
+                )
+            }
+            LimitError::CallDepth(max) => {
+                write!(f, "Execution exceeded the maximum call depth of {}", max)
+            }
+            LimitError::Variables(max) => {
+                write!(f, "Execution exceeded the limit of {} variables", max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/*
+ * Why the execution was stopped before reaching its end
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Abort {
+    // A limit was hit
+    Limit(LimitError),
+    // The code asked to stop with an exit code, ex: `process.exit(1)`
+    Exit(i32),
+    // An error was returned with `?` outside of a function, it has the error's message
+    Unhandled(String),
+}
+
+impl From<LimitError> for Abort {
+    fn from(error: LimitError) -> Self {
+        Abort::Limit(error)
+    }
+}
+
+impl fmt::Display for Abort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Abort::Limit(error) => error.fmt(f),
+            Abort::Exit(code) => write!(f, "Execution stopped with the exit code {}", code),
+            Abort::Unhandled(message) => write!(f, "Unhandled error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Abort {}
+
+/*
+ * Resources used so far by an execution
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Usage {
+    pub instructions: u64,
+    pub call_depth: usize,
+    pub started: Option<Instant>,
+}
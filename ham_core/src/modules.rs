@@ -0,0 +1,697 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+        expression::{
+            Expression,
+            ExpressionBase,
+        },
+        fn_def::FnDefinition,
+        module::{
+            Import,
+            Module,
+        },
+        var_def::VarDefinition,
+    },
+    get_function_from_def,
+    get_tokens,
+    move_tokens_into_ast,
+    permissions::normalize_path,
+    primitive_values::string::StringVal,
+    run_body,
+    runtime::downcast_val,
+    stack::{
+        FunctionAction,
+        FunctionDef,
+        FunctionsContainer,
+        Stack,
+        SuspendedScopes,
+        VariableDef,
+        GLOBAL_SCOPE,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    rc::Rc,
+    sync::Mutex,
+};
+
+/*
+ * Extension of Ham files, it can be left out when importing
+ */
+const EXTENSION: &str = "ham";
+
+/*
+ * Find the file of an imported module, ex: `import x from "./x.ham"`
+ *
+ * - `./x.ham`, `../x.ham` and `x.ham` are relative to the folder of the importing file
+ * - `@/lib/x.ham` is relative to the project root, see `project_root`
+ * - `/x.ham` is absolute
+ *
+ * The `.ham` extension is optional. If no file is found, the paths that were tried are returned
+ */
+pub fn resolve_module(specifier: &str, filedir: &str) -> Result<PathBuf, Vec<PathBuf>> {
+    let filedir = normalize_path(filedir);
+
+    let path = if let Some(path) = specifier.strip_prefix("@/") {
+        project_root(&filedir).join(path)
+    } else {
+        filedir.join(specifier)
+    };
+
+    let path = normalize_path(&path.to_string_lossy());
+
+    let mut tried = vec![path.clone()];
+
+    if path
+        .extension()
+        .is_none_or(|extension| extension != EXTENSION)
+    {
+        tried.push(PathBuf::from(format!("{}.{}", path.display(), EXTENSION)));
+    }
+
+    match tried.iter().find(|path| path.is_file()) {
+        Some(path) => Ok(path.clone()),
+        None => Err(tried),
+    }
+}
+
+/*
+ * Root of the project a folder belongs to, this is the closest folder with a `ham.yml` manifest,
+ * or the current folder if there isn't any
+ */
+pub fn project_root(filedir: &Path) -> PathBuf {
+    filedir
+        .ancestors()
+        .find(|folder| folder.join("ham.yml").is_file())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| normalize_path("."))
+}
+
+/*
+ * Folder of a module's file, its own imports are resolved from it
+ */
+pub fn module_dir(path: &Path) -> String {
+    path.parent().unwrap_or(path).to_string_lossy().to_string()
+}
+
+/*
+ * Check if a module is a native one, they are imported by their name with a prefix, ex: `std:math`
+ */
+pub fn is_native(specifier: &str) -> bool {
+    specifier.split_once(':').is_some_and(|(prefix, _)| {
+        // Single letters are drives on Windows, ex: `C:\\x.ham`
+        prefix.len() > 1 && prefix.chars().all(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/*
+ * Module implemented in Rust, ex: `std:math`
+ *
+ * Example:
+ *
+ * stack.register_module(
+ *     "host:db",
+ *     NativeModule::default()
+ *         .function("query", &["sql", "limit"], |function, args_vals, stack| { .. })
+ *         .default_value("limit", number_value(100))
+ *         .value("version", version),
+ * )
+ *
+ */
+#[derive(Clone, Default)]
+pub struct NativeModule {
+    pub functions: Vec<FunctionDef>,
+    pub values: Vec<(String, BoxedValue)>,
+}
+
+impl NativeModule {
+    /*
+     * Export a function, it gets the values of its arguments, see `check_arguments`
+     *
+     * The last argument can collect the rest of them, ex: `&["command", "...arguments"]`
+     */
+    pub fn function(mut self, name: &str, arguments: &[&str], cb: FunctionAction) -> Self {
+        let (rest_argument, arguments) = match arguments.split_last() {
+            Some((last, arguments)) if last.starts_with("...") => {
+                (Some(last.replacen("...", "", 1)), arguments)
+            }
+            _ => (None, arguments),
+        };
+
+        self.functions.push(FunctionDef {
+            name: name.to_string(),
+            body: Rc::default(),
+            arguments: arguments
+                .iter()
+                .map(|argument| argument.to_string())
+                .collect(),
+            generator: false,
+            defaults: vec![],
+            rest_argument,
+            module: Some(ModuleScope::native()),
+            cb,
+            expr_id: GLOBAL_SCOPE,
+        });
+        self
+    }
+
+    /*
+     * Give a default value to an argument of the last exported function, so it can be left out,
+     * see `argument`
+     */
+    pub fn default_value(mut self, argument: &str, value: BoxedValue) -> Self {
+        if let Some(function) = self.functions.last_mut() {
+            function.defaults.push((argument.to_string(), value));
+        }
+        self
+    }
+
+    /*
+     * Export a value
+     */
+    pub fn value(mut self, name: &str, value: BoxedValue) -> Self {
+        self.values.push((name.to_string(), value));
+        self
+    }
+}
+
+/*
+ * Check a function of a native module was called with the arguments it takes, and of the given types,
+ * reporting the error if it wasn't
+ *
+ * Arguments with a default value can be left out, and the rest of the arguments, if it takes them, are not checked
+ */
+pub fn check_arguments(
+    function: &FunctionDef,
+    args_vals: &[BoxedValue],
+    types: &[Ops],
+    stack: &Mutex<Stack>,
+) -> bool {
+    let stack = stack.lock().unwrap();
+
+    let total = function.arguments.len();
+    let required = total - function.defaults.len();
+    let variadic = function.rest_argument.is_some();
+
+    if args_vals.len() < required || (args_vals.len() > total && !variadic) {
+        stack.raise_error(
+            errors::CODES::WrongArgumentsCount,
+            vec![
+                function.name.clone(),
+                errors::arguments_count(required, total, variadic),
+                args_vals.len().to_string(),
+            ],
+        );
+        return false;
+    }
+
+    for ((argument, value), expected) in function.arguments.iter().zip(args_vals).zip(types) {
+        if value.interface != *expected {
+            stack.raise_error(
+                errors::CODES::MismatchedTypes,
+                vec![
+                    format!("the argument '{}' of '{}'", argument, function.name),
+                    expected.type_name().to_string(),
+                    value.interface.type_name().to_string(),
+                ],
+            );
+            return false;
+        }
+    }
+
+    true
+}
+
+/*
+ * Get the value of an argument of a native function, or its default value if it was left out
+ */
+pub fn argument(function: &FunctionDef, args_vals: &[BoxedValue], index: usize) -> BoxedValue {
+    args_vals.get(index).cloned().unwrap_or_else(|| {
+        let name = &function.arguments[index];
+        function
+            .defaults
+            .iter()
+            .find(|(default, _)| default == name)
+            .map(|(_, value)| value.clone())
+            .expect("the argument has no default value")
+    })
+}
+
+/*
+ * Modules imported by the code, each one is only parsed once and only runs once
+ * no matter how many times it's imported
+ */
+#[derive(Clone, Default)]
+pub struct ModuleGraph {
+    // Parsed modules by their canonical path
+    parsed: HashMap<PathBuf, Rc<Vec<Box<dyn AstBase>>>>,
+    // Modules being parsed, each one imported by the previous one
+    loading: Vec<PathBuf>,
+    // Modules whose code already ran, by their path
+    instances: HashMap<String, ModuleInstance>,
+    // Native modules by their name
+    pub natives: HashMap<String, NativeModule>,
+}
+
+/*
+ * Get a native module, its items are defined when the import runs
+ */
+pub(crate) fn load_native_module(
+    import: Import,
+    specifier: &str,
+    stack: &Mutex<Stack>,
+) -> Option<Module> {
+    let native = stack
+        .lock()
+        .unwrap()
+        .modules
+        .natives
+        .get(specifier)
+        .cloned();
+
+    let native = if let Some(native) = native {
+        native
+    } else {
+        native_module_not_found(specifier, &stack.lock().unwrap());
+        return None;
+    };
+
+    let values = native
+        .values
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<String>>();
+
+    let exports = native
+        .functions
+        .iter()
+        .map(|function| function.name.clone())
+        .chain(values.iter().cloned())
+        .collect::<Vec<String>>();
+
+    check_imported_items(&import, specifier, &exports, stack);
+
+    Some(Module {
+        import,
+        path: specifier.to_string(),
+        body: Rc::default(),
+        functions: Vec::new(),
+        values,
+        native: true,
+    })
+}
+
+/*
+ * Parse the module in the given path, or get it if it was already parsed
+ *
+ * Import cycles are reported, along with the modules which form them
+ */
+pub(crate) fn load_module(import: Import, path: &Path, stack: &Mutex<Stack>) -> Option<Module> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    let cached = stack.lock().unwrap().modules.parsed.get(&path).cloned();
+
+    let body = if let Some(body) = cached {
+        body
+    } else {
+        parse_module(&path, stack)?
+    };
+
+    // Only the items declared with `pub` are exported
+    let mut functions = Vec::new();
+    let mut values = Vec::new();
+
+    for op in body.iter() {
+        match op.get_type() {
+            Ops::FnDef => {
+                let function = downcast_val::<FnDefinition>(op.as_self());
+                if function.public {
+                    functions.push(function.clone());
+                }
+            }
+            Ops::VarDef => {
+                let variable = downcast_val::<VarDefinition>(op.as_self());
+                if variable.public {
+                    values.push(variable.def_name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let path = path.display().to_string();
+
+    let exports = functions
+        .iter()
+        .map(|function| function.def_name.clone())
+        .chain(values.iter().cloned())
+        .collect::<Vec<String>>();
+
+    check_imported_items(&import, &path, &exports, stack);
+
+    Some(Module {
+        import,
+        path,
+        body,
+        functions,
+        values,
+        native: false,
+    })
+}
+
+/*
+ * Report the items imported by their name which the module doesn't export
+ */
+fn check_imported_items(import: &Import, path: &str, exports: &[String], stack: &Mutex<Stack>) {
+    if let Import::Items(items) = import {
+        for (name, _) in items {
+            if !exports.contains(name) {
+                stack.lock().unwrap().raise_error(
+                    errors::CODES::NotExported,
+                    vec![name.clone(), path.to_string()],
+                );
+            }
+        }
+    }
+}
+
+/*
+ * Parse the file the program starts from into the tree, it's loading while its imports are
+ * parsed, so import cycles through it are found starting from it instead of parsing it again
+ */
+pub fn parse_entry_file(path: &Path, code: String, tree: &Mutex<Expression>, stack: &Mutex<Stack>) {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+
+    stack.lock().unwrap().modules.loading.push(path.clone());
+
+    // Its imports are relative to its own folder
+    move_tokens_into_ast(get_tokens(code), tree, module_dir(&path), stack);
+
+    stack.lock().unwrap().modules.loading.pop();
+}
+
+/*
+ * Read and parse a module, and keep it for the next imports
+ */
+fn parse_module(path: &Path, stack: &Mutex<Stack>) -> Option<Rc<Vec<Box<dyn AstBase>>>> {
+    {
+        let mut stack = stack.lock().unwrap();
+
+        if let Some(start) = stack
+            .modules
+            .loading
+            .iter()
+            .position(|module| module == path)
+        {
+            let cycle = stack.modules.loading[start..]
+                .iter()
+                .map(PathBuf::as_path)
+                .chain([path])
+                .map(|module| module.display().to_string())
+                .collect::<Vec<String>>();
+
+            stack.raise_error(errors::CODES::ImportCycle, vec![cycle.join(" -> ")]);
+            return None;
+        }
+
+        stack.modules.loading.push(path.to_path_buf());
+    }
+
+    let body = fs::read_to_string(path).ok().map(|content| {
+        // Its imports are relative to its own folder
+        let tree = Mutex::new(Expression::new());
+        move_tokens_into_ast(get_tokens(content), &tree, module_dir(path), stack);
+        Rc::new(tree.into_inner().unwrap().body)
+    });
+
+    let mut stack = stack.lock().unwrap();
+    stack.modules.loading.pop();
+
+    if let Some(body) = &body {
+        stack
+            .modules
+            .parsed
+            .insert(path.to_path_buf(), body.clone());
+    } else {
+        let path = path.display().to_string();
+        stack.raise_error(errors::CODES::ModuleNotFound, vec![path.clone(), path]);
+    }
+
+    body
+}
+
+/*
+ * Scope where the code of a module ran, it's kept out of the stack and
+ * put back while any of the module's functions runs
+ */
+#[derive(Clone)]
+pub struct ModuleScope {
+    id: u64,
+    // Taken while it's in the stack
+    scopes: Rc<RefCell<Option<SuspendedScopes>>>,
+}
+
+impl fmt::Debug for ModuleScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ModuleScope")
+    }
+}
+
+impl ModuleScope {
+    /*
+     * Scope of the native modules, they have no variables to put in the stack
+     */
+    pub(crate) fn native() -> Self {
+        Self {
+            id: GLOBAL_SCOPE,
+            scopes: Rc::default(),
+        }
+    }
+
+    /*
+     * Put the module's variables and functions in the stack, returns false if they already are
+     */
+    pub(crate) fn enter(&self, stack: &Mutex<Stack>) -> bool {
+        let scopes = self.scopes.borrow_mut().take();
+
+        if let Some(scopes) = scopes {
+            stack.lock().unwrap().resume_scopes(scopes);
+            true
+        } else {
+            false
+        }
+    }
+
+    /*
+     * Check if two functions belong to the same module, or both to none
+     */
+    pub(crate) fn same(a: &Option<ModuleScope>, b: &Option<ModuleScope>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => Rc::ptr_eq(&a.scopes, &b.scopes),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /*
+     * Take the module's variables and functions out of the stack again
+     */
+    pub(crate) fn leave(&self, stack: &Mutex<Stack>) {
+        let scopes = stack.lock().unwrap().suspend_scopes(self.id);
+        *self.scopes.borrow_mut() = Some(scopes);
+    }
+}
+
+/*
+ * Module whose code already ran
+ */
+#[derive(Clone)]
+struct ModuleInstance {
+    scope: ModuleScope,
+    // Exported variables, they share their values with the module's ones
+    values: Vec<VariableDef>,
+}
+
+/*
+ * Run the code of a module, in its own scope
+ */
+fn run_module(module: &Module, stack: &Mutex<Stack>) -> ModuleInstance {
+    let id = stack.lock().unwrap().open_scope();
+
+    run_body(&module.body, id, stack);
+
+    let mut stack = stack.lock().unwrap();
+
+    let values = module
+        .values
+        .iter()
+        .filter_map(|name| stack.get_variable_by_name(name))
+        .collect();
+
+    ModuleInstance {
+        scope: ModuleScope {
+            id,
+            scopes: Rc::new(RefCell::new(Some(stack.suspend_scopes(id)))),
+        },
+        values,
+    }
+}
+
+/*
+ * Define the items of an imported module
+ *
+ * When it's imported through a variable, ex: `import x from "./x.ham"`, its exported
+ * functions are methods of the variable, ex: `x.a()`, and its exported variables are
+ * accessed through it, ex: `x.b`. Otherwise the items are defined by the name they take.
+ *
+ * The module's code runs the first time it's imported
+ */
+pub(crate) fn define_module(module: &Module, scope_id: u64, stack: &Mutex<Stack>) {
+    let exports = if module.native {
+        native_exports(module, scope_id, stack)
+    } else {
+        Some(module_exports(module, stack))
+    };
+
+    let (functions, values) = match exports {
+        Some(exports) => exports,
+        None => return,
+    };
+
+    let mut stack = stack.lock().unwrap();
+
+    match &module.import {
+        Import::Namespace(name) => {
+            let mut variable = VariableDef::new(
+                name.clone(),
+                BoxedValue {
+                    interface: Ops::String,
+                    value: Box::new(StringVal(name.clone())),
+                },
+                scope_id,
+            );
+
+            for function in functions {
+                variable.push_function(function);
+            }
+
+            stack.push_variable(variable);
+
+            for value in values {
+                stack.push_variable(VariableDef {
+                    name: format!("{}.{}", name, value.name),
+                    expr_id: scope_id,
+                    ..value
+                });
+            }
+        }
+        Import::Items(items) => {
+            for (item, alias) in items {
+                if let Some(function) = functions.iter().find(|function| &function.name == item) {
+                    stack.push_function(FunctionDef {
+                        name: alias.clone(),
+                        ..function.clone()
+                    });
+                } else if let Some(value) = values.iter().find(|value| &value.name == item) {
+                    stack.push_variable(VariableDef {
+                        name: alias.clone(),
+                        expr_id: scope_id,
+                        ..value.clone()
+                    });
+                }
+            }
+        }
+    }
+}
+
+/*
+ * Exported functions and variables of a module written in Ham, running its code if it didn't yet
+ */
+fn module_exports(module: &Module, stack: &Mutex<Stack>) -> (Vec<FunctionDef>, Vec<VariableDef>) {
+    let instance = stack
+        .lock()
+        .unwrap()
+        .modules
+        .instances
+        .get(&module.path)
+        .cloned();
+
+    let instance = instance.unwrap_or_else(|| {
+        let instance = run_module(module, stack);
+        stack
+            .lock()
+            .unwrap()
+            .modules
+            .instances
+            .insert(module.path.clone(), instance.clone());
+        instance
+    });
+
+    let functions = module
+        .functions
+        .iter()
+        .map(|function| FunctionDef {
+            module: Some(instance.scope.clone()),
+            ..get_function_from_def(function)
+        })
+        .collect();
+
+    (functions, instance.values)
+}
+
+/*
+ * Report a native module is not registered, listing the ones that are
+ */
+fn native_module_not_found(specifier: &str, stack: &Stack) {
+    let mut available = stack
+        .modules
+        .natives
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    available.sort();
+
+    stack.raise_error(
+        errors::CODES::NativeModuleNotFound,
+        vec![specifier.to_string(), available.join(", ")],
+    );
+}
+
+/*
+ * Exported functions and values of a native module
+ */
+fn native_exports(
+    module: &Module,
+    scope_id: u64,
+    stack: &Mutex<Stack>,
+) -> Option<(Vec<FunctionDef>, Vec<VariableDef>)> {
+    let stack = stack.lock().unwrap();
+
+    // The AST could have been read from a file, with modules that aren't registered
+    let native = if let Some(native) = stack.modules.natives.get(&module.path) {
+        native
+    } else {
+        native_module_not_found(&module.path, &stack);
+        return None;
+    };
+
+    let values = native
+        .values
+        .iter()
+        .map(|(name, value)| VariableDef::new(name.clone(), value.clone(), scope_id))
+        .collect();
+
+    Some((native.functions.clone(), values))
+}
@@ -0,0 +1,648 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+        expression::Expression,
+        fn_call::FnCall,
+        fn_def::FnDefinition,
+        for_block::For,
+        if_ast::IfConditional,
+        module::{
+            Import,
+            Module,
+        },
+        named_argument::NamedArgument,
+        reference::Reference,
+        result::ResultExpression,
+        return_ast::ReturnStatement,
+        spawn::Spawn,
+        try_ast::TryStatement,
+        var_assign::VarAssignment,
+        var_def::VarDefinition,
+        while_block::While,
+        yield_ast::YieldStatement,
+    },
+    runtime::downcast_val,
+    stack::Stack,
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::{
+    collections::HashMap,
+    mem,
+    sync::Mutex,
+};
+
+/*
+ * How a binding was declared
+ */
+#[derive(Clone)]
+enum Declaration {
+    Variable,
+    Constant,
+    // Argument of the given function
+    Argument(String),
+    // Variable of a for loop
+    LoopVariable,
+    // Exported variable of a module, with the name it has there
+    Export(String),
+}
+
+/*
+ * A variable known while checking the code
+ */
+#[derive(Clone)]
+struct Binding {
+    name: String,
+    declaration: Declaration,
+    mutable: bool,
+    line: usize,
+    // The pointer it holds, if it's known
+    pointer: Option<PointerOrigin>,
+}
+
+impl Binding {
+    /*
+     * How it should have been declared to be mutable, and the help shown with it
+     *
+     * Constants can never be mutated, so they are copied into a variable instead
+     */
+    fn mutable_declaration(&self) -> (String, &'static str) {
+        match &self.declaration {
+            Declaration::Variable => (
+                format!("let mut {} = ...", self.name),
+                "Declare it as mutable.",
+            ),
+            Declaration::Constant => (
+                format!("let mut {} = {}", self.copy_name(), self.name),
+                "Constants can't be mutated, copy it into a mutable variable.",
+            ),
+            Declaration::Argument(fn_name) => (
+                format!("fn {}(mut {})", fn_name, self.name),
+                "Declare it as mutable.",
+            ),
+            Declaration::LoopVariable => (
+                format!("let mut {0} = {0}", self.name),
+                "Declare it as mutable.",
+            ),
+            Declaration::Export(name) => (
+                format!("pub let mut {} = ...", name),
+                "Declare it as mutable.",
+            ),
+        }
+    }
+
+    /*
+     * Name for a mutable copy of a constant, ex: `limit` for `settings.LIMIT`
+     */
+    fn copy_name(&self) -> String {
+        let name = self.name.rsplit('.').next().unwrap_or(&self.name);
+        name.to_lowercase()
+    }
+}
+
+/*
+ * Where a pointer was created from, ex: `&a` or `&mut a`
+ */
+#[derive(Clone)]
+struct PointerOrigin {
+    target: String,
+    mutable: bool,
+}
+
+impl PointerOrigin {
+    /*
+     * Read the pointer created by a reference
+     */
+    fn from_reference(reference: &str) -> Option<Self> {
+        if let Some(target) = reference.strip_prefix("&mut ") {
+            Some(Self {
+                target: target.to_string(),
+                mutable: true,
+            })
+        } else {
+            reference.strip_prefix('&').map(|target| Self {
+                target: target.to_string(),
+                mutable: false,
+            })
+        }
+    }
+}
+
+struct Checker<'a> {
+    stack: &'a Mutex<Stack>,
+    // Bindings visible at the current position, the last scope is the innermost one
+    scopes: Vec<Vec<Binding>>,
+    // Position and name of the arguments each function writes through
+    writes_through: HashMap<String, Vec<(usize, String)>>,
+    valid: bool,
+}
+
+/*
+ * Check the code doesn't mutate bindings which are not mutable before running it:
+ *
+ * - Assigning (`a = 1`) and calling mutating methods (`a.mut_sum(1)`) needs `let mut a` or a `mut a` argument
+ * - Writing through a pointer (`*a = 1`) needs it to be created with `&mut`, which also needs a mutable binding,
+ *   pointers only known while running (ex: returned by a function) are checked then
+ * - Constants can't be mutated and must be declared at the top level
+ *
+ * Errors are reported through the stack, returns false if any was found
+ */
+pub fn check_mutability(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> bool {
+    let ast = ast.lock().unwrap();
+
+    let mut writes_through = HashMap::new();
+    find_writes_through(&ast.body, &mut writes_through);
+
+    let mut checker = Checker {
+        stack,
+        scopes: vec![Vec::new()],
+        writes_through,
+        valid: true,
+    };
+
+    checker.check_body(&ast.body);
+
+    checker.valid
+}
+
+/*
+ * Find which arguments every function writes through, ex: `*arg = 1`
+ */
+fn find_writes_through(
+    body: &[Box<dyn AstBase>],
+    writes_through: &mut HashMap<String, Vec<(usize, String)>>,
+) {
+    for operation in body {
+        match operation.get_type() {
+            Ops::FnDef => {
+                let function = downcast_val::<FnDefinition>(operation.as_self());
+                find_function_writes(function, writes_through);
+                find_writes_through(&function.body, writes_through);
+            }
+            Ops::Module => {
+                let module = downcast_val::<Module>(operation.as_self());
+                find_writes_through(&module.body, writes_through);
+            }
+            Ops::IfConditional => {
+                let if_statement = downcast_val::<IfConditional>(operation.as_self());
+                find_writes_through(&if_statement.body, writes_through);
+            }
+            Ops::WhileDef => {
+                let while_block = downcast_val::<While>(operation.as_self());
+                find_writes_through(&while_block.body, writes_through);
+            }
+            Ops::ForDef => {
+                let for_block = downcast_val::<For>(operation.as_self());
+                find_writes_through(&for_block.body, writes_through);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn find_function_writes(
+    function: &FnDefinition,
+    writes_through: &mut HashMap<String, Vec<(usize, String)>>,
+) {
+    let mut written = Vec::new();
+    collect_written_pointers(&function.body, &mut written);
+
+    let arguments = writes_through.entry(function.def_name.clone()).or_default();
+
+    for (position, argument) in function.arguments.iter().enumerate() {
+        let argument = (position, argument.clone());
+        if written.contains(&argument.1) && !arguments.contains(&argument) {
+            arguments.push(argument);
+        }
+    }
+}
+
+/*
+ * Collect the names of the variables written through in a block, without entering inner functions
+ */
+fn collect_written_pointers(body: &[Box<dyn AstBase>], written: &mut Vec<String>) {
+    for operation in body {
+        match operation.get_type() {
+            Ops::VarAssign => {
+                let variable = downcast_val::<VarAssignment>(operation.as_self());
+                if variable.var_name.starts_with('*') {
+                    written.push(variable.var_name.trim_start_matches('*').to_string());
+                }
+            }
+            Ops::IfConditional => {
+                let if_statement = downcast_val::<IfConditional>(operation.as_self());
+                collect_written_pointers(&if_statement.body, written);
+            }
+            Ops::WhileDef => {
+                let while_block = downcast_val::<While>(operation.as_self());
+                collect_written_pointers(&while_block.body, written);
+            }
+            Ops::ForDef => {
+                let for_block = downcast_val::<For>(operation.as_self());
+                collect_written_pointers(&for_block.body, written);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Checker<'_> {
+    fn raise_error(&mut self, kind: errors::CODES, args: Vec<String>) {
+        self.stack.lock().unwrap().raise_error(kind, args);
+        self.valid = false;
+    }
+
+    fn find_binding(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    fn find_mut_binding(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    fn push_binding(&mut self, binding: Binding) {
+        // `_` holds the intermediate values of chained calls
+        if binding.name != "_" {
+            self.scopes.last_mut().unwrap().push(binding);
+        }
+    }
+
+    /*
+     * Find out which pointer a value holds, if it can be known before running
+     */
+    fn pointer_of(&self, value: &BoxedValue) -> Option<PointerOrigin> {
+        if value.interface != Ops::Reference {
+            return None;
+        }
+
+        let reference = &downcast_val::<Reference>(value.value.as_self()).0;
+
+        PointerOrigin::from_reference(reference).or_else(|| {
+            self.find_binding(reference)
+                .and_then(|binding| binding.pointer.clone())
+        })
+    }
+
+    /*
+     * Check a block of code in its own scope
+     */
+    fn check_body(&mut self, body: &[Box<dyn AstBase>]) {
+        for operation in body {
+            match operation.get_type() {
+                Ops::VarDef => {
+                    let variable = downcast_val::<VarDefinition>(operation.as_self());
+
+                    self.check_value(&variable.assignment);
+
+                    if variable.constant && self.scopes.len() > 1 {
+                        self.raise_error(
+                            errors::CODES::NestedConstant,
+                            vec![variable.def_name.clone(), variable.line.to_string()],
+                        );
+                    }
+
+                    let binding = Binding {
+                        name: variable.def_name.clone(),
+                        declaration: if variable.constant {
+                            Declaration::Constant
+                        } else {
+                            Declaration::Variable
+                        },
+                        mutable: variable.mutable,
+                        line: variable.line,
+                        pointer: self.pointer_of(&variable.assignment),
+                    };
+                    self.push_binding(binding);
+                }
+
+                Ops::VarAssign => {
+                    let variable = downcast_val::<VarAssignment>(operation.as_self());
+
+                    self.check_value(&variable.assignment);
+                    self.check_mutation(&variable.var_name);
+
+                    // The variable might hold a different pointer now
+                    if !variable.var_name.starts_with('*') {
+                        let pointer = self.pointer_of(&variable.assignment);
+                        if let Some(binding) = self.find_mut_binding(&variable.var_name) {
+                            binding.pointer = pointer;
+                        }
+                    }
+                }
+
+                Ops::FnCall => {
+                    let fn_call = downcast_val::<FnCall>(operation.as_self());
+                    self.check_call(fn_call);
+                }
+
+                Ops::Spawn => {
+                    let spawn = downcast_val::<Spawn>(operation.as_self());
+                    self.check_spawn(spawn);
+                }
+
+                Ops::Return => {
+                    let statement = downcast_val::<ReturnStatement>(operation.as_self());
+                    self.check_value(&statement.value);
+                }
+
+                Ops::IfConditional => {
+                    let if_statement = downcast_val::<IfConditional>(operation.as_self());
+                    self.check_conditions(&if_statement.conditions);
+                    self.check_scope(&if_statement.body);
+                }
+
+                Ops::WhileDef => {
+                    let while_block = downcast_val::<While>(operation.as_self());
+                    self.check_conditions(&while_block.conditions);
+                    self.check_scope(&while_block.body);
+                }
+
+                Ops::ForDef => {
+                    let for_block = downcast_val::<For>(operation.as_self());
+                    self.check_value(&for_block.iterable);
+
+                    self.scopes.push(vec![Binding {
+                        name: for_block.var_name.clone(),
+                        declaration: Declaration::LoopVariable,
+                        mutable: false,
+                        line: for_block.line,
+                        pointer: None,
+                    }]);
+                    self.check_body(&for_block.body);
+                    self.scopes.pop();
+                }
+
+                Ops::Yield => {
+                    let statement = downcast_val::<YieldStatement>(operation.as_self());
+                    self.check_value(&statement.value);
+                }
+
+                Ops::Try => {
+                    let statement = downcast_val::<TryStatement>(operation.as_self());
+                    self.check_value(&statement.value);
+                }
+
+                Ops::FnDef => {
+                    let function = downcast_val::<FnDefinition>(operation.as_self());
+                    self.check_function(function);
+                }
+
+                Ops::Module => {
+                    let module = downcast_val::<Module>(operation.as_self());
+
+                    // Modules don't see the code importing them
+                    let outer_scopes = mem::replace(&mut self.scopes, vec![Vec::new()]);
+                    self.check_body(&module.body);
+                    let module_scopes = mem::replace(&mut self.scopes, outer_scopes);
+
+                    // Imported variables share their value with the module, so they keep how they were declared there
+                    for binding in &module_scopes[0] {
+                        if !module.values.contains(&binding.name) {
+                            continue;
+                        }
+
+                        let name = match &module.import {
+                            Import::Namespace(namespace) => {
+                                Some(format!("{}.{}", namespace, binding.name))
+                            }
+                            Import::Items(items) => items
+                                .iter()
+                                .find(|(item, _)| item == &binding.name)
+                                .map(|(_, alias)| alias.clone()),
+                        };
+
+                        if let Some(name) = name {
+                            let declaration = match binding.declaration {
+                                Declaration::Constant => Declaration::Constant,
+                                _ => Declaration::Export(binding.name.clone()),
+                            };
+
+                            self.push_binding(Binding {
+                                name,
+                                declaration,
+                                ..binding.clone()
+                            });
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn check_scope(&mut self, body: &[Box<dyn AstBase>]) {
+        self.scopes.push(Vec::new());
+        self.check_body(body);
+        self.scopes.pop();
+    }
+
+    /*
+     * Functions only see the top level bindings and their own arguments
+     */
+    fn check_function(&mut self, function: &FnDefinition) {
+        let arguments = function
+            .arguments
+            .iter()
+            .chain(&function.rest_argument)
+            .map(|argument| Binding {
+                name: argument.clone(),
+                declaration: Declaration::Argument(function.def_name.clone()),
+                mutable: function.mutable_arguments.contains(argument),
+                line: function.line,
+                pointer: None,
+            })
+            .collect();
+
+        let top_level = self.scopes[0].clone();
+        let outer_scopes = mem::replace(&mut self.scopes, vec![top_level, arguments]);
+
+        self.check_body(&function.body);
+
+        self.scopes = outer_scopes;
+    }
+
+    fn check_conditions(&mut self, conditions: &[ResultExpression]) {
+        for condition in conditions {
+            self.check_value(&condition.left);
+            self.check_value(&condition.right);
+        }
+    }
+
+    fn check_value(&mut self, value: &BoxedValue) {
+        match value.interface {
+            Ops::Reference => {
+                let reference = &downcast_val::<Reference>(value.value.as_self()).0;
+
+                // Pointing to a value with `&mut` allows mutating it
+                if let Some(var_name) = reference.strip_prefix("&mut ") {
+                    self.check_mutation(var_name);
+                }
+            }
+            Ops::FnCall => {
+                let fn_call = downcast_val::<FnCall>(value.value.as_self());
+                self.check_call(fn_call);
+            }
+            Ops::NamedArgument => {
+                let argument = downcast_val::<NamedArgument>(value.value.as_self());
+                self.check_value(&argument.value);
+            }
+            Ops::Spawn => {
+                let spawn = downcast_val::<Spawn>(value.value.as_self());
+                self.check_spawn(spawn);
+            }
+            Ops::Expression => {
+                let expression = downcast_val::<Expression>(value.value.as_self());
+                self.check_scope(&expression.body);
+            }
+            _ => {}
+        }
+    }
+
+    fn check_spawn(&mut self, spawn: &Spawn) {
+        if let Some(function) = &spawn.function {
+            self.check_function(function);
+        } else {
+            self.check_call(&spawn.call);
+        }
+    }
+
+    fn check_call(&mut self, fn_call: &FnCall) {
+        // Methods such as `mut_sum` mutate the variable they are called from
+        if let Some(reference_to) = &fn_call.reference_to {
+            if fn_call.fn_name.starts_with("mut_") {
+                self.check_mutation(reference_to);
+            }
+        }
+
+        for argument in &fn_call.arguments {
+            self.check_value(argument);
+        }
+
+        let written_arguments = self
+            .writes_through
+            .get(&fn_call.fn_name)
+            .cloned()
+            .unwrap_or_default();
+
+        let positional: Vec<&BoxedValue> = fn_call
+            .arguments
+            .iter()
+            .filter(|argument| argument.interface != Ops::NamedArgument)
+            .collect();
+
+        // Arguments the function writes through must be mutable pointers, given by position or by name
+        for (position, name) in written_arguments {
+            let argument = positional.get(position).copied().or_else(|| {
+                fn_call
+                    .arguments
+                    .iter()
+                    .filter(|argument| argument.interface == Ops::NamedArgument)
+                    .map(|argument| downcast_val::<NamedArgument>(argument.value.as_self()))
+                    .find(|argument| argument.name == name)
+                    .map(|argument| &argument.value)
+            });
+
+            if let Some(argument) = argument {
+                let origin = self.pointer_of(argument);
+
+                if let Some(origin) = origin.filter(|origin| !origin.mutable) {
+                    let reference = &downcast_val::<Reference>(argument.value.as_self()).0;
+                    let (suggestion, help) = if reference.starts_with('&') {
+                        self.pointer_suggestion(&origin.target, |target| {
+                            format!("{}(&mut {})", fn_call.fn_name, target)
+                        })
+                    } else {
+                        self.pointer_suggestion(&origin.target, |target| {
+                            format!("let {} = &mut {}", reference, target)
+                        })
+                    };
+
+                    self.raise_error(
+                        errors::CODES::ImmutablePointer,
+                        vec![reference.clone(), suggestion, help.to_string()],
+                    );
+                }
+            }
+        }
+    }
+
+    /*
+     * Check a binding can be mutated, `*a` mutates what `a` points to instead
+     */
+    fn check_mutation(&mut self, var_name: &str) {
+        let name = var_name.trim_start_matches('*');
+        let is_deref = name.len() != var_name.len();
+
+        let binding = if let Some(binding) = self.find_binding(name) {
+            binding.clone()
+        } else {
+            // Not known before running, if it doesn't exist it will be reported then
+            return;
+        };
+
+        if !is_deref && !binding.mutable {
+            let (declaration, help) = binding.mutable_declaration();
+
+            self.raise_error(
+                errors::CODES::ImmutableVariable,
+                vec![
+                    name.to_string(),
+                    binding.line.to_string(),
+                    declaration,
+                    help.to_string(),
+                ],
+            );
+        }
+
+        if let Some(origin) = binding.pointer.filter(|origin| is_deref && !origin.mutable) {
+            let (suggestion, help) = self.pointer_suggestion(&origin.target, |target| {
+                format!("let {} = &mut {}", name, target)
+            });
+
+            self.raise_error(
+                errors::CODES::ImmutablePointer,
+                vec![name.to_string(), suggestion, help.to_string()],
+            );
+        }
+    }
+
+    /*
+     * Suggest how to point to the target with `&mut`, `point` writes the code doing it,
+     * the target must be declared as mutable first, or copied if it's a constant
+     */
+    fn pointer_suggestion(
+        &self,
+        target: &str,
+        point: impl Fn(&str) -> String,
+    ) -> (String, &'static str) {
+        match self.find_binding(target) {
+            Some(binding) if matches!(binding.declaration, Declaration::Constant) => {
+                let (copy, _) = binding.mutable_declaration();
+                (
+                    format!("{}\n    {}", copy, point(&binding.copy_name())),
+                    "Constants can't be mutated, point to a mutable copy of it.",
+                )
+            }
+            Some(binding) if !binding.mutable => {
+                let (declaration, _) = binding.mutable_declaration();
+                (
+                    format!("{}\n    {}", declaration, point(target)),
+                    "Declare it as mutable and point to it with &mut.",
+                )
+            }
+            _ => (point(target), "Point to it with &mut."),
+        }
+    }
+}
@@ -0,0 +1,496 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+        expression::Expression,
+        fn_call::FnCall,
+        fn_def::FnDefinition,
+        for_block::For,
+        if_ast::IfConditional,
+        module::{
+            Import,
+            Module,
+        },
+        named_argument::NamedArgument,
+        reference::Reference,
+        result::ResultExpression,
+        return_ast::ReturnStatement,
+        try_ast::TryStatement,
+        var_assign::VarAssignment,
+        var_def::VarDefinition,
+        while_block::While,
+        yield_ast::YieldStatement,
+    },
+    get_function_from_def,
+    limits::Limits,
+    runtime::{
+        downcast_val,
+        resolve_reference,
+        value_to_string,
+        PURE_METHODS,
+    },
+    stack::{
+        FunctionsContainer,
+        Stack,
+        GLOBAL_SCOPE,
+    },
+    streams::{
+        MemoryStream,
+        Streams,
+    },
+    utils::Ops,
+};
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    io::Cursor,
+    rc::Rc,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+// Native functions which don't have side effects
+const PURE_NATIVES: [&str; 1] = ["format"];
+
+/*
+ * Static state of the code being optimized
+ */
+struct Context {
+    // How many times each function name is defined or imported in the whole tree
+    definitions: HashMap<String, usize>,
+    // Pure functions which can be called at the current position, sorted so they
+    // are always given to the computation in the same order
+    functions: BTreeMap<String, FnDefinition>,
+}
+
+impl Context {
+    /*
+     * Check if calling a function by its name has no side effects and
+     * only depends on its arguments
+     */
+    fn is_pure_function(&self, fn_name: &str) -> bool {
+        if self.functions.contains_key(fn_name) {
+            return true;
+        }
+
+        // Natives can be shadowed by user functions
+        PURE_NATIVES.contains(&fn_name) && !self.definitions.contains_key(fn_name)
+    }
+}
+
+/*
+ * Optimize a AST tree before running it:
+ *
+ * - Values which only depend on literals and pure functions are computed, ex: `o().sum(1).sum(3)`
+ * - Code after a `return` or `break` is removed
+ * - `if` and `while` blocks whose conditions are always false are removed
+ */
+pub fn optimize_ast(ast: &Mutex<Expression>) {
+    let mut ast = ast.lock().unwrap();
+
+    let mut definitions = HashMap::new();
+    count_definitions(&ast.body, &mut definitions);
+
+    let mut context = Context {
+        definitions,
+        functions: BTreeMap::new(),
+    };
+
+    ast.body = optimize_body(&ast.body, &mut context);
+}
+
+/*
+ * Count how many times each function is defined or imported, functions defined more than once
+ * depend on which definition is reached at runtime
+ */
+fn count_definitions(body: &[Box<dyn AstBase>], definitions: &mut HashMap<String, usize>) {
+    for operation in body {
+        match operation.get_type() {
+            Ops::FnDef => {
+                let function = downcast_val::<FnDefinition>(operation.as_self());
+                *definitions.entry(function.def_name.clone()).or_insert(0) += 1;
+                count_definitions(&function.body, definitions);
+            }
+            Ops::IfConditional => {
+                let if_statement = downcast_val::<IfConditional>(operation.as_self());
+                count_definitions(&if_statement.body, definitions);
+            }
+            Ops::WhileDef => {
+                let while_block = downcast_val::<While>(operation.as_self());
+                count_definitions(&while_block.body, definitions);
+            }
+            Ops::ForDef => {
+                let for_block = downcast_val::<For>(operation.as_self());
+                count_definitions(&for_block.body, definitions);
+            }
+            Ops::Module => {
+                // Imported items shadow the local and native functions with the same name
+                let module = downcast_val::<Module>(operation.as_self());
+                let names = match &module.import {
+                    Import::Namespace(namespace) => vec![namespace],
+                    Import::Items(items) => items.iter().map(|(_, alias)| alias).collect(),
+                };
+
+                for name in names {
+                    *definitions.entry(name.clone()).or_insert(0) += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/*
+ * Optimize a block of code, functions found to be pure are only visible inside it
+ */
+fn optimize_body(body: &[Box<dyn AstBase>], context: &mut Context) -> Vec<Box<dyn AstBase>> {
+    let outer_functions = context.functions.clone();
+    let mut optimized: Vec<Box<dyn AstBase>> = Vec::new();
+
+    for operation in body {
+        match operation.get_type() {
+            Ops::Break => {
+                optimized.push(operation.clone());
+                // Nothing after it will run
+                break;
+            }
+
+            Ops::Return => {
+                let mut statement = downcast_val::<ReturnStatement>(operation.as_self()).clone();
+                statement.value = optimize_value(&statement.value, context);
+                optimized.push(Box::new(statement));
+                // Nothing after it will run
+                break;
+            }
+
+            Ops::IfConditional => {
+                let mut if_statement = downcast_val::<IfConditional>(operation.as_self()).clone();
+
+                if let Some(conditions) = optimize_conditions(&if_statement.conditions, context) {
+                    if_statement.conditions = conditions;
+                    if_statement.body = optimize_body(&if_statement.body, context);
+                    optimized.push(Box::new(if_statement));
+                }
+            }
+
+            Ops::WhileDef => {
+                let mut while_block = downcast_val::<While>(operation.as_self()).clone();
+
+                if let Some(conditions) = optimize_conditions(&while_block.conditions, context) {
+                    while_block.conditions = conditions;
+                    while_block.body = optimize_body(&while_block.body, context);
+                    optimized.push(Box::new(while_block));
+                }
+            }
+
+            Ops::ForDef => {
+                let mut for_block = downcast_val::<For>(operation.as_self()).clone();
+                for_block.iterable = optimize_value(&for_block.iterable, context);
+                for_block.body = optimize_body(&for_block.body, context);
+                optimized.push(Box::new(for_block));
+            }
+
+            Ops::Yield => {
+                let mut statement = downcast_val::<YieldStatement>(operation.as_self()).clone();
+                statement.value = optimize_value(&statement.value, context);
+                optimized.push(Box::new(statement));
+            }
+
+            Ops::Try => {
+                let mut statement = downcast_val::<TryStatement>(operation.as_self()).clone();
+                statement.value = optimize_value(&statement.value, context);
+                optimized.push(Box::new(statement));
+            }
+
+            Ops::FnDef => {
+                let mut function = downcast_val::<FnDefinition>(operation.as_self()).clone();
+                let name = function.def_name.clone();
+
+                // Functions don't see the variables of where they are defined
+                function.body = Rc::new(optimize_body(&function.body, context));
+
+                let is_unique = context.definitions.get(&name) == Some(&1);
+
+                // Assume it's pure while checking it, so recursive functions can be pure too
+                context.functions.insert(name.clone(), function.clone());
+
+                let mut locals = function.arguments.clone();
+                locals.extend(function.rest_argument.clone());
+
+                let pure_defaults = function
+                    .defaults
+                    .iter()
+                    .all(|(_, value)| is_pure_value(value, &mut locals.clone(), context));
+
+                if !is_unique
+                    || !pure_defaults
+                    || !is_pure_body(&function.body, &mut locals, context)
+                {
+                    context.functions.remove(&name);
+                }
+
+                optimized.push(Box::new(function));
+            }
+
+            Ops::VarDef => {
+                let mut variable = downcast_val::<VarDefinition>(operation.as_self()).clone();
+                variable.assignment = optimize_value(&variable.assignment, context);
+                optimized.push(Box::new(variable));
+            }
+
+            Ops::VarAssign => {
+                let mut variable = downcast_val::<VarAssignment>(operation.as_self()).clone();
+                variable.assignment = optimize_value(&variable.assignment, context);
+                optimized.push(Box::new(variable));
+            }
+
+            Ops::FnCall => {
+                // The call itself is kept, its result might end up not being used which is an error
+                let mut fn_call = downcast_val::<FnCall>(operation.as_self()).clone();
+                fn_call.arguments = fn_call
+                    .arguments
+                    .iter()
+                    .map(|argument| optimize_value(argument, context))
+                    .collect();
+                optimized.push(Box::new(fn_call));
+            }
+
+            _ => optimized.push(operation.clone()),
+        }
+    }
+
+    context.functions = outer_functions;
+
+    optimized
+}
+
+/*
+ * Optimize the conditions of a block, returns None if they are never all true
+ *
+ * Conditions which are always true are removed
+ */
+fn optimize_conditions(
+    conditions: &[ResultExpression],
+    context: &mut Context,
+) -> Option<Vec<ResultExpression>> {
+    let mut optimized = Vec::new();
+
+    for condition in conditions {
+        let left = optimize_value(&condition.left, context);
+        let right = optimize_value(&condition.right, context);
+
+        if is_literal(&left) && is_literal(&right) {
+            let left_val = value_to_string(left).unwrap();
+            let right_val = value_to_string(right).unwrap();
+
+            let is_true = match condition.relation {
+                Ops::EqualCondition => left_val == right_val,
+                Ops::NotEqualCondition => left_val != right_val,
+                _ => false,
+            };
+
+            if is_true {
+                continue;
+            } else {
+                return None;
+            }
+        }
+
+        optimized.push(ResultExpression {
+            left,
+            relation: condition.relation,
+            right,
+        });
+    }
+
+    Some(optimized)
+}
+
+/*
+ * Compute a value if possible, otherwise optimize what's inside it
+ */
+fn optimize_value(value: &BoxedValue, context: &mut Context) -> BoxedValue {
+    match value.interface {
+        Ops::NamedArgument => {
+            let mut argument = downcast_val::<NamedArgument>(value.value.as_self()).clone();
+            argument.value = optimize_value(&argument.value, context);
+
+            BoxedValue {
+                interface: Ops::NamedArgument,
+                value: Box::new(argument),
+            }
+        }
+        Ops::Expression | Ops::FnCall => {
+            if is_pure_value(value, &mut Vec::new(), context) {
+                if let Some(computed) = compute_value(value, context) {
+                    return computed;
+                }
+            }
+
+            if value.interface == Ops::Expression {
+                let mut expression = downcast_val::<Expression>(value.value.as_self()).clone();
+                expression.body = optimize_body(&expression.body, context);
+
+                BoxedValue {
+                    interface: Ops::Expression,
+                    value: Box::new(expression),
+                }
+            } else {
+                let mut fn_call = downcast_val::<FnCall>(value.value.as_self()).clone();
+                fn_call.arguments = fn_call
+                    .arguments
+                    .iter()
+                    .map(|argument| optimize_value(argument, context))
+                    .collect();
+
+                BoxedValue {
+                    interface: Ops::FnCall,
+                    value: Box::new(fn_call),
+                }
+            }
+        }
+        _ => value.clone(),
+    }
+}
+
+/*
+ * Evaluate a pure value in an isolated stack, only literal results are kept
+ */
+fn compute_value(value: &BoxedValue, context: &Context) -> Option<BoxedValue> {
+    let stderr = MemoryStream::default();
+
+    let mut stack = Stack::new(GLOBAL_SCOPE);
+    stack.streams = Streams::new(
+        MemoryStream::default().handle(),
+        stderr.handle(),
+        Arc::new(Mutex::new(Cursor::new(""))),
+    );
+    // Pure code might still never end
+    stack.limits = Limits {
+        max_instructions: Some(10_000),
+        max_call_depth: Some(100),
+        max_variables: Some(1_000),
+        ..Limits::default()
+    };
+
+    for function in context.functions.values() {
+        stack.push_function(get_function_from_def(function));
+    }
+
+    let stack = Mutex::new(stack);
+
+    let computed = resolve_reference(&stack, value.interface, value.value.as_ref())?;
+    let stack = stack.lock().unwrap();
+
+    // Errors are left for the runtime to report, the code might never run
+
+    if stack.aborted.is_none() && stderr.contents().is_empty() && is_literal(&computed) {
+        Some(computed)
+    } else {
+        None
+    }
+}
+
+fn is_literal(value: &BoxedValue) -> bool {
+    matches!(
+        value.interface,
+        Ops::Number | Ops::String | Ops::Boolean | Ops::None
+    )
+}
+
+/*
+ * Check if a value only depends on literals, the given local variables and pure functions
+ */
+fn is_pure_value(value: &BoxedValue, locals: &mut Vec<String>, context: &Context) -> bool {
+    match value.interface {
+        Ops::Number | Ops::String | Ops::Boolean | Ops::None => true,
+        Ops::Reference => {
+            let reference = downcast_val::<Reference>(value.value.as_self());
+            let var_name = reference
+                .0
+                .trim_start_matches('&')
+                .trim_start_matches("mut ");
+            locals.contains(&var_name.to_string())
+        }
+        Ops::FnCall => is_pure_call(
+            downcast_val::<FnCall>(value.value.as_self()),
+            locals,
+            context,
+        ),
+        Ops::Expression => {
+            let expression = downcast_val::<Expression>(value.value.as_self());
+            is_pure_body(&expression.body, &mut locals.clone(), context)
+        }
+        Ops::NamedArgument => {
+            let argument = downcast_val::<NamedArgument>(value.value.as_self());
+            is_pure_value(&argument.value, locals, context)
+        }
+        _ => false,
+    }
+}
+
+fn is_pure_call(fn_call: &FnCall, locals: &mut Vec<String>, context: &Context) -> bool {
+    let is_pure_callee = if let Some(reference_to) = &fn_call.reference_to {
+        // Methods can only modify their own variable
+        locals.contains(reference_to) && PURE_METHODS.contains(&fn_call.fn_name.as_str())
+    } else {
+        context.is_pure_function(&fn_call.fn_name)
+    };
+
+    is_pure_callee
+        && fn_call
+            .arguments
+            .iter()
+            .all(|argument| is_pure_value(argument, locals, context))
+}
+
+/*
+ * Check if a block of code has no side effects outside of it
+ */
+fn is_pure_body(body: &[Box<dyn AstBase>], locals: &mut Vec<String>, context: &Context) -> bool {
+    body.iter().all(|operation| match operation.get_type() {
+        Ops::Break => true,
+        Ops::Return => {
+            let statement = downcast_val::<ReturnStatement>(operation.as_self());
+            is_pure_value(&statement.value, locals, context)
+        }
+        Ops::VarDef => {
+            let variable = downcast_val::<VarDefinition>(operation.as_self());
+            let is_pure = is_pure_value(&variable.assignment, locals, context);
+            locals.push(variable.def_name.clone());
+            is_pure
+        }
+        Ops::VarAssign => {
+            let variable = downcast_val::<VarAssignment>(operation.as_self());
+            locals.contains(&variable.var_name.trim_start_matches('&').to_string())
+                && is_pure_value(&variable.assignment, locals, context)
+        }
+        Ops::FnCall => is_pure_call(downcast_val::<FnCall>(operation.as_self()), locals, context),
+        Ops::IfConditional => {
+            let if_statement = downcast_val::<IfConditional>(operation.as_self());
+            are_pure_conditions(&if_statement.conditions, locals, context)
+                && is_pure_body(&if_statement.body, &mut locals.clone(), context)
+        }
+        Ops::WhileDef => {
+            let while_block = downcast_val::<While>(operation.as_self());
+            are_pure_conditions(&while_block.conditions, locals, context)
+                && is_pure_body(&while_block.body, &mut locals.clone(), context)
+        }
+        _ => false,
+    })
+}
+
+fn are_pure_conditions(
+    conditions: &[ResultExpression],
+    locals: &mut Vec<String>,
+    context: &Context,
+) -> bool {
+    conditions.iter().all(|condition| {
+        is_pure_value(&condition.left, locals, context)
+            && is_pure_value(&condition.right, locals, context)
+    })
+}
@@ -0,0 +1,204 @@
+use std::{
+    fs,
+    path::{
+        Component,
+        Path,
+        PathBuf,
+    },
+};
+
+/*
+ * Powers a script can have
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Capability {
+    // Read files, this includes importing modules
+    Read,
+
+    // Create, modify or remove files
+    Write,
+
+    // Read or modify environment variables
+    Env,
+
+    // Run subprocesses
+    Run,
+}
+
+impl Capability {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Read => "read",
+            Capability::Write => "write",
+            Capability::Env => "env",
+            Capability::Run => "run",
+        }
+    }
+}
+
+/*
+ * How much of a capability is granted
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub enum Grant {
+    Denied,
+    All,
+    // Only for these paths (read, write), variable names (env) or commands (run)
+    Only(Vec<String>),
+}
+
+impl Grant {
+    /*
+     * Grant everything if there are no targets, or just the given targets
+     */
+    pub fn from_targets(targets: Vec<String>) -> Self {
+        if targets.is_empty() {
+            Grant::All
+        } else {
+            Grant::Only(targets)
+        }
+    }
+}
+
+/*
+ * Permissions of a script
+ *
+ * By default everything is allowed, use `Permissions::deny_all` to run untrusted code
+ * and only grant the needed capabilities
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Permissions {
+    pub read: Grant,
+    pub write: Grant,
+    pub env: Grant,
+    pub run: Grant,
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::allow_all()
+    }
+}
+
+impl Permissions {
+    pub fn allow_all() -> Self {
+        Self {
+            read: Grant::All,
+            write: Grant::All,
+            env: Grant::All,
+            run: Grant::All,
+        }
+    }
+
+    pub fn deny_all() -> Self {
+        Self {
+            read: Grant::Denied,
+            write: Grant::Denied,
+            env: Grant::Denied,
+            run: Grant::Denied,
+        }
+    }
+
+    pub fn grant(&mut self, capability: Capability, grant: Grant) {
+        match capability {
+            Capability::Read => self.read = grant,
+            Capability::Write => self.write = grant,
+            Capability::Env => self.env = grant,
+            Capability::Run => self.run = grant,
+        }
+    }
+
+    pub fn get(&self, capability: Capability) -> &Grant {
+        match capability {
+            Capability::Read => &self.read,
+            Capability::Write => &self.write,
+            Capability::Env => &self.env,
+            Capability::Run => &self.run,
+        }
+    }
+
+    /*
+     * Check if the capability is granted for the given target
+     */
+    pub fn is_allowed(&self, capability: Capability, target: &str) -> bool {
+        match self.get(capability) {
+            Grant::Denied => false,
+            Grant::All => true,
+            Grant::Only(targets) => match capability {
+                // Paths are allowed if they are inside any of the granted paths, once their links are followed
+                Capability::Read | Capability::Write => match resolve_path(target) {
+                    Some(target) => targets
+                        .iter()
+                        .filter_map(|allowed| resolve_path(allowed))
+                        .any(|allowed| target.starts_with(allowed)),
+                    None => false,
+                },
+                Capability::Env | Capability::Run => {
+                    targets.iter().any(|allowed| allowed == target)
+                }
+            },
+        }
+    }
+}
+
+/*
+ * Make a path absolute and resolve its `.` and `..` without touching the file system,
+ * so paths that don't exist yet can be checked too
+ */
+pub fn normalize_path(path: &str) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in absolute_path(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/*
+ * Make a path absolute and resolve its `.`, `..` and links like the file system does, so a link
+ * inside an allowed folder can't lead outside of it. The parts that don't exist yet are kept as they are
+ *
+ * Returns None if a link can't be followed, ex: it points to nothing
+ */
+pub fn resolve_path(path: &str) -> Option<PathBuf> {
+    let mut resolved = PathBuf::new();
+
+    for component in absolute_path(path).components() {
+        match component {
+            Component::CurDir => {}
+            // What's resolved so far has no links, so going up is where the file system goes too
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            component => {
+                resolved.push(component);
+
+                if let Ok(real_path) = fs::canonicalize(&resolved) {
+                    resolved = real_path;
+                } else if fs::symlink_metadata(&resolved).is_ok() {
+                    // It exists but can't be followed, it's unknown where it leads
+                    return None;
+                }
+            }
+        }
+    }
+
+    Some(resolved)
+}
+
+fn absolute_path(path: &str) -> PathBuf {
+    let path = Path::new(path);
+
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    }
+}
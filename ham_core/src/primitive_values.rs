@@ -1,5 +1,14 @@
 pub mod boolean;
+pub mod channel;
+pub mod error;
+pub mod generator;
+pub mod list;
+pub mod map;
+pub mod none;
 pub mod number;
 pub mod pointer;
 pub mod primitive_base;
+pub mod process;
 pub mod string;
+pub mod tail_call;
+pub mod task;
@@ -1,12 +1,15 @@
 use crate::primitive_values::primitive_base::PrimitiveValueBase;
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 use std::any::Any;
 
 /*
  * Boolean
  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Boolean(pub bool);
 
 // Implement base methods for Boolean
@@ -0,0 +1,141 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    event_loop::{
+        block_on,
+        Wait,
+    },
+    primitive_values::primitive_base::PrimitiveValueBase,
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::errors,
+};
+use serde::{
+    Serialize,
+    Serializer,
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::VecDeque,
+    fmt,
+    rc::Rc,
+    sync::Mutex,
+};
+
+/*
+ * Channel, passes messages between tasks in the order they were sent
+ *
+ * Copies of it share the same messages
+ */
+#[derive(Clone, Default)]
+pub struct Channel(Rc<RefCell<VecDeque<BoxedValue>>>);
+
+impl PrimitiveValueBase for Channel {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl fmt::Debug for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Channel")
+    }
+}
+
+impl Serialize for Channel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("channel")
+    }
+}
+
+/*
+ * Channel base
+ */
+pub trait ChannelValueBase {
+    fn send(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn recv(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the channel stored in the variable a method was called from
+ */
+fn get_channel(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<Channel> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+
+    Some(downcast_val::<Channel>(variable.value.as_self()).clone())
+}
+
+impl Channel {
+    pub fn has_messages(&self) -> bool {
+        !self.0.borrow().is_empty()
+    }
+
+    /*
+     * Take the oldest message
+     */
+    pub fn take_message(&self) -> Option<BoxedValue> {
+        self.0.borrow_mut().pop_front()
+    }
+}
+
+impl ChannelValueBase for Channel {
+    /*
+     * function: send()
+     *
+     * Sends a message, it doesn't wait for it to be received
+     */
+    fn send(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let channel = get_channel(&args_vals, stack)?;
+
+        // The first value is the variable the method was called from
+        if args_vals.len() != 2 {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::WrongArgumentsCount,
+                vec![
+                    function.name.clone(),
+                    "1".to_string(),
+                    (args_vals.len() - 1).to_string(),
+                ],
+            );
+            return None;
+        }
+
+        channel.0.borrow_mut().push_back(args_vals[1].clone());
+
+        None
+    }
+
+    /*
+     * function: recv()
+     *
+     * Waits for a message and returns it
+     */
+    fn recv(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let channel = get_channel(&args_vals, stack)?;
+        block_on(Wait::Message(channel), stack)
+    }
+}
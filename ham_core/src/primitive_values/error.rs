@@ -0,0 +1,76 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        primitive_base::PrimitiveValueBase,
+        string::StringVal,
+    },
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::Ops,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    sync::Mutex,
+};
+
+/*
+ * Error returned as a value, ex: when `std:fs` can't read a file
+ */
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ErrorVal(pub String);
+
+impl PrimitiveValueBase for ErrorVal {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+/*
+ * Error base
+ */
+pub trait ErrorValueBase {
+    fn get_state(&self) -> String;
+
+    fn message(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+impl ErrorValueBase for ErrorVal {
+    fn get_state(&self) -> String {
+        self.0.clone()
+    }
+
+    /*
+     * function: message()
+     *
+     * Returns the text describing the error
+     */
+    fn message(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let var_name = value_to_string(args_vals[0].clone()).unwrap();
+        let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+        let error = downcast_val::<ErrorVal>(variable.value.as_self());
+
+        Some(BoxedValue {
+            interface: Ops::String,
+            value: Box::new(StringVal(error.get_state())),
+        })
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    bind_arguments,
+    primitive_values::{
+        boolean::Boolean,
+        list::List,
+        map::{
+            Map,
+            MapValueBase,
+        },
+        primitive_base::PrimitiveValueBase,
+        string::StringVal,
+    },
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+        SuspendedScopes,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+    vm::{
+        compiler,
+        Step,
+        Vm,
+    },
+};
+use serde::{
+    Serialize,
+    Serializer,
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    mem,
+    rc::Rc,
+    sync::Mutex,
+};
+
+/*
+ * Where the values of a generator come from
+ */
+enum Source {
+    // Code suspended in a `yield`, its variables are kept out of the stack until it's resumed
+    Code {
+        vm: Box<Vm>,
+        scope: u64,
+        scopes: SuspendedScopes,
+    },
+    // The items of a list, used to iterate over lists
+    Items {
+        items: Vec<BoxedValue>,
+        index: usize,
+    },
+    // Taken while the code is running
+    Running,
+    Finished,
+}
+
+/*
+ * What resuming a generator gave
+ */
+pub enum GeneratorStep {
+    Yielded(Option<BoxedValue>),
+    Finished,
+}
+
+struct GeneratorState {
+    source: Source,
+    // Next step, if it was already taken to check if there was one
+    peeked: Option<GeneratorStep>,
+}
+
+/*
+ * Generator, produces values lazily every time it's resumed
+ *
+ * Copies of it share the same state, so they resume the same code
+ */
+#[derive(Clone)]
+pub struct Generator(Rc<RefCell<GeneratorState>>);
+
+impl PrimitiveValueBase for Generator {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Generator")
+    }
+}
+
+impl Serialize for Generator {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("generator")
+    }
+}
+
+/*
+ * Generator base
+ */
+pub trait GeneratorValueBase {
+    fn next(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn done(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the generator stored in the variable a method was called from
+ */
+fn get_generator(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<Generator> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+
+    Some(downcast_val::<Generator>(variable.value.as_self()).clone())
+}
+
+impl Generator {
+    fn with_source(source: Source) -> Self {
+        Self(Rc::new(RefCell::new(GeneratorState {
+            source,
+            peeked: None,
+        })))
+    }
+
+    /*
+     * Create the generator of a call to a generator function, nothing of its code runs until it's resumed
+     */
+    pub fn start(
+        function: &FunctionDef,
+        arguments: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let scope = stack.lock().unwrap().open_scope();
+
+        if !bind_arguments(function, arguments, scope, stack) {
+            stack.lock().unwrap().close_scope(scope);
+            return None;
+        }
+
+        let scopes = stack.lock().unwrap().suspend_scopes(scope);
+        let vm = Vm::generator(compiler::compile(&function.body), scope);
+
+        Some(BoxedValue {
+            interface: Ops::Generator,
+            value: Box::new(Self::with_source(Source::Code {
+                vm: Box::new(vm),
+                scope,
+                scopes,
+            })),
+        })
+    }
+
+    /*
+     * Get a generator to iterate over a value, reporting the error if it can't be iterated
+     */
+    pub fn iterate(value: &BoxedValue, stack: &Mutex<Stack>) -> Option<Self> {
+        match value.interface {
+            Ops::Generator => Some(downcast_val::<Generator>(value.value.as_self()).clone()),
+            Ops::List => Some(Self::with_source(Source::Items {
+                items: downcast_val::<List>(value.value.as_self()).0.clone(),
+                index: 0,
+            })),
+            // Maps are iterated by their keys
+            Ops::Map => Some(Self::with_source(Source::Items {
+                items: downcast_val::<Map>(value.value.as_self())
+                    .get_state()
+                    .iter()
+                    .map(|(key, _)| BoxedValue {
+                        interface: Ops::String,
+                        value: Box::new(StringVal(key.clone())),
+                    })
+                    .collect(),
+                index: 0,
+            })),
+            _ => {
+                stack.lock().unwrap().raise_error(
+                    errors::CODES::NotIterable,
+                    vec![value.interface.type_name().to_string()],
+                );
+                None
+            }
+        }
+    }
+
+    /*
+     * Run until the next value
+     */
+    pub fn resume(&self, stack: &Mutex<Stack>) -> GeneratorStep {
+        let peeked = self.0.borrow_mut().peeked.take();
+        peeked.unwrap_or_else(|| self.advance(stack))
+    }
+
+    /*
+     * Check if the generator is over, which might need to run it until its next value
+     */
+    pub fn is_finished(&self, stack: &Mutex<Stack>) -> bool {
+        if self.0.borrow().peeked.is_none() {
+            let step = self.advance(stack);
+            self.0.borrow_mut().peeked = Some(step);
+        }
+
+        matches!(self.0.borrow().peeked, Some(GeneratorStep::Finished))
+    }
+
+    fn advance(&self, stack: &Mutex<Stack>) -> GeneratorStep {
+        let source = mem::replace(&mut self.0.borrow_mut().source, Source::Running);
+
+        let (source, step) = match source {
+            Source::Code {
+                mut vm,
+                scope,
+                scopes,
+            } => {
+                // Its variables are the innermost ones while it runs
+                stack.lock().unwrap().resume_scopes(scopes);
+
+                match vm.run(stack) {
+                    Step::Yielded(value) => {
+                        let scopes = stack.lock().unwrap().suspend_scopes(scope);
+                        (
+                            Source::Code { vm, scope, scopes },
+                            GeneratorStep::Yielded(value),
+                        )
+                    }
+                    // Returned values are ignored
+                    Step::Returned(_) => {
+                        stack.lock().unwrap().close_scope(scope);
+                        (Source::Finished, GeneratorStep::Finished)
+                    }
+                    Step::Blocked(_) => unreachable!("Generators are not blocked"),
+                }
+            }
+            Source::Items { items, index } => {
+                if let Some(item) = items.get(index).cloned() {
+                    (
+                        Source::Items {
+                            items,
+                            index: index + 1,
+                        },
+                        GeneratorStep::Yielded(Some(item)),
+                    )
+                } else {
+                    (Source::Finished, GeneratorStep::Finished)
+                }
+            }
+            Source::Running => {
+                stack
+                    .lock()
+                    .unwrap()
+                    .raise_error(errors::CODES::GeneratorRunning, vec![]);
+                (Source::Running, GeneratorStep::Finished)
+            }
+            Source::Finished => (Source::Finished, GeneratorStep::Finished),
+        };
+
+        self.0.borrow_mut().source = source;
+        step
+    }
+}
+
+impl GeneratorValueBase for Generator {
+    /*
+     * function: next()
+     *
+     * Resumes the generator and returns its next value
+     */
+    fn next(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let generator = get_generator(&args_vals, stack)?;
+
+        match generator.resume(stack) {
+            GeneratorStep::Yielded(value) => value,
+            GeneratorStep::Finished => {
+                stack
+                    .lock()
+                    .unwrap()
+                    .raise_error(errors::CODES::GeneratorFinished, vec![]);
+                None
+            }
+        }
+    }
+
+    /*
+     * function: done()
+     *
+     * Returns whether the generator has no more values
+     */
+    fn done(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let generator = get_generator(&args_vals, stack)?;
+
+        Some(BoxedValue {
+            interface: Ops::Boolean,
+            value: Box::new(Boolean(generator.is_finished(stack))),
+        })
+    }
+}
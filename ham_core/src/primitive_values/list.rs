@@ -0,0 +1,122 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        number::{
+            number_argument,
+            Number,
+        },
+        primitive_base::PrimitiveValueBase,
+    },
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    sync::Mutex,
+};
+
+/*
+ * List of values, ex: the arguments collected by `...rest`
+ */
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct List(pub Vec<BoxedValue>);
+
+impl PrimitiveValueBase for List {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+/*
+ * List base
+ */
+pub trait ListValueBase {
+    fn get_state(&self) -> &Vec<BoxedValue>;
+
+    fn len(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn get(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the list stored in the variable a method was called from
+ */
+fn get_list(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<List> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+
+    Some(downcast_val::<List>(variable.value.as_self()).clone())
+}
+
+impl ListValueBase for List {
+    fn get_state(&self) -> &Vec<BoxedValue> {
+        &self.0
+    }
+
+    /*
+     * function: len()
+     *
+     * Returns how many items the list has
+     */
+    fn len(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let list = get_list(&args_vals, stack)?;
+
+        Some(BoxedValue {
+            interface: Ops::Number,
+            value: Box::new(Number(list.get_state().len())),
+        })
+    }
+
+    /*
+     * function: get()
+     *
+     * Returns a copy of the item in the given position, starting from 0
+     */
+    fn get(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let list = get_list(&args_vals, stack)?;
+
+        let index = number_argument(function, &args_vals, stack)?;
+
+        let item = list.get_state().get(index).cloned();
+
+        if item.is_none() {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::IndexOutOfBounds,
+                vec![index.to_string(), list.get_state().len().to_string()],
+            );
+        }
+
+        item
+    }
+}
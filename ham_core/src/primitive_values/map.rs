@@ -0,0 +1,222 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        boolean::Boolean,
+        list::List,
+        none::NoneVal,
+        number::Number,
+        primitive_base::PrimitiveValueBase,
+        string::{
+            string_argument,
+            StringVal,
+        },
+    },
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::Ops,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::Mutex,
+};
+
+/*
+ * Values by their key, ex: the metadata of a file
+ *
+ * Keys keep the order they were inserted in, so iterating a map always gives the same order,
+ * the position of each key is kept aside so looking one up doesn't go through all of them
+ */
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(from = "Vec<(String, BoxedValue)>", into = "Vec<(String, BoxedValue)>")]
+pub struct Map {
+    entries: Vec<(String, BoxedValue)>,
+    indexes: HashMap<String, usize>,
+}
+
+impl PrimitiveValueBase for Map {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Map {
+    /*
+     * Set the value of a key, existing keys keep their position
+     */
+    pub fn insert(&mut self, key: String, value: BoxedValue) {
+        match self.indexes.get(&key) {
+            Some(index) => self.entries[*index].1 = value,
+            None => {
+                self.indexes.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    /*
+     * Get the value of a key
+     */
+    pub fn value(&self, key: &str) -> Option<&BoxedValue> {
+        self.indexes.get(key).map(|index| &self.entries[*index].1)
+    }
+}
+
+impl From<Vec<(String, BoxedValue)>> for Map {
+    fn from(entries: Vec<(String, BoxedValue)>) -> Self {
+        let mut map = Map::default();
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl From<Map> for Vec<(String, BoxedValue)> {
+    fn from(map: Map) -> Self {
+        map.entries
+    }
+}
+
+/*
+ * Map base
+ */
+pub trait MapValueBase {
+    fn get_state(&self) -> &Vec<(String, BoxedValue)>;
+
+    fn len(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn get(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn has(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn keys(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the map stored in the variable a method was called from
+ */
+fn get_map(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<Map> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+
+    Some(downcast_val::<Map>(variable.value.as_self()).clone())
+}
+
+impl MapValueBase for Map {
+    fn get_state(&self) -> &Vec<(String, BoxedValue)> {
+        &self.entries
+    }
+
+    /*
+     * function: len()
+     *
+     * Returns how many keys the map has
+     */
+    fn len(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let map = get_map(&args_vals, stack)?;
+
+        Some(BoxedValue {
+            interface: Ops::Number,
+            value: Box::new(Number(map.get_state().len())),
+        })
+    }
+
+    /*
+     * function: get()
+     *
+     * Returns a copy of the value of the given key, or `none` if the map doesn't have it
+     */
+    fn get(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let map = get_map(&args_vals, stack)?;
+
+        let key = string_argument(function, &args_vals, stack)?;
+
+        Some(map.value(&key).cloned().unwrap_or(BoxedValue {
+            interface: Ops::None,
+            value: Box::new(NoneVal),
+        }))
+    }
+
+    /*
+     * function: has()
+     *
+     * Returns true if the map has the given key
+     */
+    fn has(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let map = get_map(&args_vals, stack)?;
+
+        let key = string_argument(function, &args_vals, stack)?;
+
+        Some(BoxedValue {
+            interface: Ops::Boolean,
+            value: Box::new(Boolean(map.value(&key).is_some())),
+        })
+    }
+
+    /*
+     * function: keys()
+     *
+     * Returns a list with the keys, in the order they were inserted in
+     */
+    fn keys(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let map = get_map(&args_vals, stack)?;
+
+        let keys = map
+            .get_state()
+            .iter()
+            .map(|(key, _)| BoxedValue {
+                interface: Ops::String,
+                value: Box::new(StringVal(key.clone())),
+            })
+            .collect();
+
+        Some(BoxedValue {
+            interface: Ops::List,
+            value: Box::new(List(keys)),
+        })
+    }
+}
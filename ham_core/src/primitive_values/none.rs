@@ -0,0 +1,157 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        boolean::Boolean,
+        primitive_base::PrimitiveValueBase,
+    },
+    runtime::value_to_string,
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    sync::Mutex,
+};
+
+/*
+ * Absence of a value, ex: what a function returns when it doesn't `return` anything
+ */
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NoneVal;
+
+impl PrimitiveValueBase for NoneVal {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+/*
+ * Methods every value has, so the code can tell none and errors apart without `?`
+ */
+pub trait OptionValueBase {
+    fn is_none(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn is_error(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn unwrap_or(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the value of the variable a method was called from
+ */
+fn get_value(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<BoxedValue> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    stack.lock().unwrap().get_value_by_name(var_name.as_str())
+}
+
+/*
+ * Check the variable's value is of the given type, the method takes no arguments
+ */
+fn is_type(
+    function: &FunctionDef,
+    args_vals: &[BoxedValue],
+    val_type: Ops,
+    stack: &Mutex<Stack>,
+) -> Option<BoxedValue> {
+    // The first value is the variable the method was called from
+    if args_vals.len() != 1 {
+        stack.lock().unwrap().raise_error(
+            errors::CODES::WrongArgumentsCount,
+            vec![
+                function.name.clone(),
+                "0".to_string(),
+                (args_vals.len() - 1).to_string(),
+            ],
+        );
+        return None;
+    }
+
+    let value = get_value(args_vals, stack)?;
+
+    Some(BoxedValue {
+        interface: Ops::Boolean,
+        value: Box::new(Boolean(value.interface == val_type)),
+    })
+}
+
+impl OptionValueBase for NoneVal {
+    /*
+     * function: is_none()
+     *
+     * Returns true if the value is none
+     */
+    fn is_none(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        is_type(function, &args_vals, Ops::None, stack)
+    }
+
+    /*
+     * function: is_error()
+     *
+     * Returns true if the value is an error
+     */
+    fn is_error(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        is_type(function, &args_vals, Ops::Error, stack)
+    }
+
+    /*
+     * function: unwrap_or(default)
+     *
+     * Returns the value, or the default if it's none or an error
+     */
+    fn unwrap_or(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        // The first value is the variable the method was called from
+        if args_vals.len() != 2 {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::WrongArgumentsCount,
+                vec![
+                    function.name.clone(),
+                    "1".to_string(),
+                    (args_vals.len() - 1).to_string(),
+                ],
+            );
+            return None;
+        }
+
+        let value = get_value(&args_vals, stack)?;
+
+        match value.interface {
+            Ops::None | Ops::Error => args_vals.into_iter().nth(1),
+            _ => Some(value),
+        }
+    }
+}
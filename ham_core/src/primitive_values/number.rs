@@ -1,31 +1,34 @@
 use crate::{
-    ast_types::{
-        ast_base::AstBase,
-        boxed_val::BoxedValue,
-        expression::Expression,
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        pointer::HeapCell,
+        primitive_base::PrimitiveValueBase,
+        string::StringVal,
     },
-    primitive_values::primitive_base::PrimitiveValueBase,
-    runtime::{
-        downcast_val,
-        value_to_string,
+    runtime::downcast_val,
+    stack::{
+        FunctionDef,
+        Stack,
     },
-    stack::Stack,
-    utils::Ops,
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
 };
-use serde::Serialize;
 use std::{
     any::Any,
-    sync::{
-        Mutex,
-        MutexGuard,
-    },
+    sync::Mutex,
 };
 
 /*
  * Number
  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Number(pub usize);
 
 impl PrimitiveValueBase for Number {
@@ -34,6 +37,77 @@ impl PrimitiveValueBase for Number {
     }
 }
 
+/*
+ * Get the number given as the only argument of a method, reporting the error if there isn't one
+ */
+pub(crate) fn number_argument(
+    function: &FunctionDef,
+    args_vals: &[BoxedValue],
+    stack: &Mutex<Stack>,
+) -> Option<usize> {
+    // The first value is the variable the method was called from
+    match args_vals.get(1) {
+        Some(value) if args_vals.len() == 2 && value.interface == Ops::Number => {
+            Some(downcast_val::<Number>(value.value.as_self()).0)
+        }
+        Some(value) if args_vals.len() == 2 => {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::MismatchedTypes,
+                vec![
+                    format!("the argument of '{}'", function.name),
+                    Ops::Number.type_name().to_string(),
+                    value.interface.type_name().to_string(),
+                ],
+            );
+            None
+        }
+        _ => {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::WrongArgumentsCount,
+                vec![
+                    function.name.clone(),
+                    "1".to_string(),
+                    (args_vals.len() - 1).to_string(),
+                ],
+            );
+            None
+        }
+    }
+}
+
+/*
+ * Get the cell of the variable a method was called from, its name is the first value
+ */
+fn variable_cell(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<HeapCell> {
+    let var_name = &downcast_val::<StringVal>(args_vals[0].value.as_self()).0;
+
+    stack
+        .lock()
+        .unwrap()
+        .find_variable(var_name)
+        .map(|variable| variable.value.clone())
+}
+
+/*
+ * Sum a number to the one in a variable's cell, reporting the error if the result is bigger
+ * than the biggest number
+ *
+ * The VM calls this directly for the variables it keeps in slots, see `Vm::call_method`
+ */
+pub(crate) fn sum_cell(cell: &HeapCell, amount: usize, stack: &Stack) -> Option<Number> {
+    let current = downcast_val::<Number>(cell.borrow().value.as_self()).get_state();
+    let sum = current.checked_add(amount);
+
+    if sum.is_none() {
+        stack.raise_error(
+            errors::CODES::NumberOverflow,
+            vec!["sum".to_string(), current.to_string(), amount.to_string()],
+        );
+    }
+
+    sum.map(Number::new)
+}
+
 /*
  * Number base
  */
@@ -42,19 +116,15 @@ pub trait NumberValueBase {
     fn get_state(&self) -> usize;
 
     fn mut_sum(
-        args: Vec<String>,
+        function: &FunctionDef,
         args_vals: Vec<BoxedValue>,
-        body: Vec<Box<dyn AstBase>>,
         stack: &Mutex<Stack>,
-        ast: &MutexGuard<Expression>,
     ) -> Option<BoxedValue>;
 
     fn sum(
-        args: Vec<String>,
+        function: &FunctionDef,
         args_vals: Vec<BoxedValue>,
-        body: Vec<Box<dyn AstBase>>,
         stack: &Mutex<Stack>,
-        ast: &MutexGuard<Expression>,
     ) -> Option<BoxedValue>;
 }
 
@@ -73,35 +143,20 @@ impl NumberValueBase for Number {
      * Returns the variable's value plus the argument
      */
     fn sum(
-        _: Vec<String>,
+        function: &FunctionDef,
         args_vals: Vec<BoxedValue>,
-        _: Vec<Box<dyn AstBase>>,
         stack: &Mutex<Stack>,
-        _: &MutexGuard<Expression>,
     ) -> Option<BoxedValue> {
-        let var_name = value_to_string(args_vals[0].clone(), stack).unwrap();
-        let new_val = downcast_val::<Number>(args_vals[1].value.as_self()).0;
-
-        // Get the variable from the stack
-        let variable = stack
-            .lock()
-            .unwrap()
-            .get_variable_by_name(var_name.as_str());
-
-        match variable {
-            Some(current_var) => {
-                let current_var = downcast_val::<Number>(current_var.value.as_self());
-                let current_val = current_var.get_state();
-
-                let new_val = Number::new(current_val + new_val);
-
-                Some(BoxedValue {
-                    interface: Ops::Number,
-                    value: Box::new(new_val),
-                })
-            }
-            _ => None,
-        }
+        let new_val = number_argument(function, &args_vals, stack)?;
+
+        // Get the variable's value from the stack
+        let cell = variable_cell(&args_vals, stack)?;
+        let new_val = sum_cell(&cell, new_val, &stack.lock().unwrap())?;
+
+        Some(BoxedValue {
+            interface: Ops::Number,
+            value: Box::new(new_val),
+        })
     }
 
     /*
@@ -110,34 +165,21 @@ impl NumberValueBase for Number {
      * Assigns to the variable value it's value plus the argument
      */
     fn mut_sum(
-        _: Vec<String>,
+        function: &FunctionDef,
         args_vals: Vec<BoxedValue>,
-        _: Vec<Box<dyn AstBase>>,
         stack: &Mutex<Stack>,
-        _: &MutexGuard<Expression>,
     ) -> Option<BoxedValue> {
-        let var_name = value_to_string(args_vals[0].clone(), stack).unwrap();
-        let new_val = downcast_val::<Number>(args_vals[1].value.as_self()).0;
-
-        // Get the variable from the stack
-        let var_ref = stack
-            .lock()
-            .unwrap()
-            .get_variable_by_name(var_name.as_str());
-
-        if let Some(current_var) = var_ref {
-            let current_val = downcast_val::<Number>(current_var.value.as_self());
-            let current_num = current_val.get_state();
-
-            let new_val = Number::new(current_num + new_val);
-
-            stack.lock().unwrap().modify_var(
-                var_name,
-                BoxedValue {
-                    interface: Ops::Number,
-                    value: Box::new(new_val),
-                },
-            );
+        let new_val = number_argument(function, &args_vals, stack)?;
+
+        // Get the variable's value from the stack
+        if let Some(cell) = variable_cell(&args_vals, stack) {
+            let new_val = sum_cell(&cell, new_val, &stack.lock().unwrap())?;
+
+            // Pointers share the variable's cell, so they see the new value too
+            *cell.borrow_mut() = BoxedValue {
+                interface: Ops::Number,
+                value: Box::new(new_val),
+            };
         }
 
         None
@@ -1,9 +1,28 @@
-use crate::primitive_values::primitive_base::PrimitiveValueBase;
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::primitive_base::PrimitiveValueBase,
+    utils::Ops,
+};
 use serde::Serialize;
-use std::any::Any;
+use std::{
+    any::Any,
+    cell::RefCell,
+    rc::Rc,
+};
+
+/*
+ * A value stored on the heap, shared between a variable and the pointers to it
+ *
+ * It lives as long as anything still references it
+ */
+pub type HeapCell = Rc<RefCell<BoxedValue>>;
 
 #[derive(Clone, Debug, Serialize)]
-pub struct Pointer(pub u64);
+pub struct Pointer {
+    pub cell: HeapCell,
+    // Created with `&mut`, only then the value can be written through it
+    pub mutable: bool,
+}
 
 // Implement base methods for Pointer
 impl PrimitiveValueBase for Pointer {
@@ -16,11 +35,48 @@ impl PrimitiveValueBase for Pointer {
  * Pointer base
  */
 pub trait PointerBase {
-    fn get_state(&self) -> u64;
+    fn get_state(&self) -> BoxedValue;
 }
 
 impl PointerBase for Pointer {
-    fn get_state(&self) -> u64 {
-        self.0
+    /*
+     * Returns a copy of the pointed value
+     */
+    fn get_state(&self) -> BoxedValue {
+        self.cell.borrow().clone()
+    }
+}
+
+impl Pointer {
+    /*
+     * Returns a copy of the given value, if it's a pointer
+     */
+    pub fn of(value: &BoxedValue) -> Option<Pointer> {
+        if value.interface == Ops::Pointer {
+            value.value.as_self().downcast_ref::<Pointer>().cloned()
+        } else {
+            None
+        }
+    }
+
+    /*
+     * Returns the cell pointed by the given value, if it's a pointer
+     */
+    pub fn cell_of(value: &BoxedValue) -> Option<HeapCell> {
+        Self::of(value).map(|pointer| pointer.cell)
+    }
+
+    /*
+     * Check if following the pointers from the given value ever reaches the cell
+     */
+    pub fn reaches(value: &BoxedValue, cell: &HeapCell) -> bool {
+        let mut next = Self::cell_of(value);
+        while let Some(current) = next {
+            if Rc::ptr_eq(&current, cell) {
+                return true;
+            }
+            next = Self::cell_of(&current.borrow());
+        }
+        false
     }
 }
@@ -0,0 +1,313 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        error::ErrorVal,
+        map::Map,
+        none::NoneVal,
+        number::Number,
+        primitive_base::PrimitiveValueBase,
+        string::{
+            string_argument,
+            StringVal,
+        },
+    },
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::Ops,
+};
+use serde::{
+    Serialize,
+    Serializer,
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    io::{
+        BufRead,
+        BufReader,
+        Read,
+        Write,
+    },
+    process::{
+        Child,
+        ChildStdin,
+        ChildStdout,
+        ExitStatus,
+    },
+    rc::Rc,
+    sync::Mutex,
+    thread::{
+        self,
+        JoinHandle,
+    },
+};
+
+/*
+ * Subprocess started by the code, its input is written and its output read while it runs
+ *
+ * Copies of it share the same subprocess
+ */
+#[derive(Clone)]
+pub struct Process(Rc<RefCell<ProcessState>>);
+
+struct ProcessState {
+    command: String,
+    child: Child,
+    // Taken when it's closed
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+    // Read in the background, so the subprocess doesn't get stuck when it writes too much
+    stderr: Option<JoinHandle<String>>,
+}
+
+impl PrimitiveValueBase for Process {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl fmt::Debug for Process {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Process")
+    }
+}
+
+impl Serialize for Process {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("process")
+    }
+}
+
+impl Process {
+    /*
+     * Take a subprocess started with all of its standard streams piped
+     */
+    pub fn new(command: String, mut child: Child) -> Self {
+        let stdin = child.stdin.take();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let stderr = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut text = String::new();
+                stderr.read_to_string(&mut text).ok();
+                text
+            })
+        });
+
+        Self(Rc::new(RefCell::new(ProcessState {
+            command,
+            child,
+            stdin,
+            stdout,
+            stderr,
+        })))
+    }
+}
+
+/*
+ * Map with the exit code and the output of a finished subprocess, or an error if it was killed
+ */
+pub(crate) fn process_output(
+    command: &str,
+    status: ExitStatus,
+    stdout: String,
+    stderr: String,
+) -> BoxedValue {
+    let code = match status.code() {
+        Some(code) => code as u32 as usize,
+        None => {
+            return BoxedValue {
+                interface: Ops::Error,
+                value: Box::new(ErrorVal(format!("{}: was killed by a signal", command))),
+            }
+        }
+    };
+
+    let text = |text: String| BoxedValue {
+        interface: Ops::String,
+        value: Box::new(StringVal(text)),
+    };
+
+    let mut output = Map::default();
+    output.insert(
+        "code".to_string(),
+        BoxedValue {
+            interface: Ops::Number,
+            value: Box::new(Number(code)),
+        },
+    );
+    output.insert("stdout".to_string(), text(stdout));
+    output.insert("stderr".to_string(), text(stderr));
+
+    BoxedValue {
+        interface: Ops::Map,
+        value: Box::new(output),
+    }
+}
+
+/*
+ * Error value for an operation that failed on a subprocess
+ */
+fn process_error(command: &str, error: impl fmt::Display) -> Option<BoxedValue> {
+    Some(BoxedValue {
+        interface: Ops::Error,
+        value: Box::new(ErrorVal(format!("{}: {}", command, error))),
+    })
+}
+
+/*
+ * Process base
+ */
+pub trait ProcessValueBase {
+    fn write(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn close(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn read_line(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn wait(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the process stored in the variable a method was called from
+ */
+fn get_process(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<Process> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+
+    Some(downcast_val::<Process>(variable.value.as_self()).clone())
+}
+
+impl ProcessValueBase for Process {
+    /*
+     * function: write()
+     *
+     * Writes the text into the input of the subprocess
+     */
+    fn write(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let process = get_process(&args_vals, stack)?;
+        let text = string_argument(function, &args_vals, stack)?;
+
+        let mut state = process.0.borrow_mut();
+        let state = &mut *state;
+
+        let result = match &mut state.stdin {
+            Some(stdin) => stdin.write_all(text.as_bytes()).and_then(|_| stdin.flush()),
+            None => return process_error(&state.command, "its input was closed"),
+        };
+
+        result
+            .err()
+            .and_then(|error| process_error(&state.command, error))
+    }
+
+    /*
+     * function: close()
+     *
+     * Closes the input of the subprocess, so it knows nothing else will be written
+     */
+    fn close(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let process = get_process(&args_vals, stack)?;
+        process.0.borrow_mut().stdin.take();
+        None
+    }
+
+    /*
+     * function: read_line()
+     *
+     * Waits for the next line written by the subprocess and returns it, without its line break,
+     * or `none` once its output is over
+     */
+    fn read_line(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let process = get_process(&args_vals, stack)?;
+        let mut state = process.0.borrow_mut();
+
+        let mut line = String::new();
+
+        match state.stdout.read_line(&mut line) {
+            Ok(0) => Some(BoxedValue {
+                interface: Ops::None,
+                value: Box::new(NoneVal),
+            }),
+            Ok(_) => {
+                let line = line.strip_suffix('\n').unwrap_or(&line);
+                let line = line.strip_suffix('\r').unwrap_or(line);
+
+                Some(BoxedValue {
+                    interface: Ops::String,
+                    value: Box::new(StringVal(line.to_string())),
+                })
+            }
+            Err(error) => process_error(&state.command, error),
+        }
+    }
+
+    /*
+     * function: wait()
+     *
+     * Closes the input of the subprocess and waits for it to finish, returns a map with its
+     * exit `code` and the `stdout` and `stderr` it wrote which were not read yet
+     */
+    fn wait(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let process = get_process(&args_vals, stack)?;
+        let mut state = process.0.borrow_mut();
+        let state = &mut *state;
+
+        state.stdin.take();
+
+        let mut stdout = String::new();
+
+        if let Err(error) = state.stdout.read_to_string(&mut stdout) {
+            return process_error(&state.command, error);
+        }
+
+        let stderr = state
+            .stderr
+            .take()
+            .and_then(|stderr| stderr.join().ok())
+            .unwrap_or_default();
+
+        match state.child.wait() {
+            Ok(status) => Some(process_output(&state.command, status, stdout, stderr)),
+            Err(error) => process_error(&state.command, error),
+        }
+    }
+}
@@ -1,12 +1,30 @@
-use crate::primitive_values::primitive_base::PrimitiveValueBase;
-use serde::Serialize;
-use std::any::Any;
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::primitive_base::PrimitiveValueBase,
+    runtime::downcast_val,
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    any::Any,
+    sync::Mutex,
+};
 
 /*
  * String
  */
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct StringVal(pub String);
 
 // Implement base methods for String
@@ -16,6 +34,44 @@ impl PrimitiveValueBase for StringVal {
     }
 }
 
+/*
+ * Get the string given as the only argument of a method, reporting the error if there isn't one
+ */
+pub(crate) fn string_argument(
+    function: &FunctionDef,
+    args_vals: &[BoxedValue],
+    stack: &Mutex<Stack>,
+) -> Option<String> {
+    // The first value is the variable the method was called from
+    match args_vals.get(1) {
+        Some(value) if args_vals.len() == 2 && value.interface == Ops::String => {
+            Some(downcast_val::<StringVal>(value.value.as_self()).0.clone())
+        }
+        Some(value) if args_vals.len() == 2 => {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::MismatchedTypes,
+                vec![
+                    format!("the argument of '{}'", function.name),
+                    Ops::String.type_name().to_string(),
+                    value.interface.type_name().to_string(),
+                ],
+            );
+            None
+        }
+        _ => {
+            stack.lock().unwrap().raise_error(
+                errors::CODES::WrongArgumentsCount,
+                vec![
+                    function.name.clone(),
+                    "1".to_string(),
+                    (args_vals.len() - 1).to_string(),
+                ],
+            );
+            None
+        }
+    }
+}
+
 /*
  * String base
  */
@@ -0,0 +1,26 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::primitive_base::PrimitiveValueBase,
+    stack::FunctionDef,
+};
+use serde::Serialize;
+use std::any::Any;
+
+/*
+ * A function call made by a return statement, ex: `return calc(value)`
+ *
+ * It's returned instead of calling the function, so the caller can run it
+ * without nesting one more call
+ */
+#[derive(Clone, Debug, Serialize)]
+pub struct TailCall {
+    #[serde(skip)]
+    pub function: FunctionDef,
+    pub arguments: Vec<BoxedValue>,
+}
+
+impl PrimitiveValueBase for TailCall {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,317 @@
+use crate::{
+    ast_types::{
+        boxed_val::BoxedValue,
+        spawn::Spawn,
+    },
+    bind_arguments,
+    event_loop::{
+        block_on,
+        Wait,
+    },
+    get_function_from_def,
+    primitive_values::{
+        boolean::Boolean,
+        primitive_base::PrimitiveValueBase,
+    },
+    runtime::{
+        downcast_val,
+        prepare_call,
+        resolve_reference,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+        SuspendedScopes,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+    vm::{
+        compiler,
+        Step,
+        Vm,
+    },
+};
+use serde::{
+    Serialize,
+    Serializer,
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    mem,
+    rc::Rc,
+    sync::Mutex,
+};
+
+/*
+ * What a task runs
+ */
+enum Source {
+    // Code which is suspended while it waits, its variables are kept out of the stack until it's resumed
+    Code {
+        vm: Box<Vm>,
+        scope: u64,
+        scopes: SuspendedScopes,
+    },
+    // Functions which can't be suspended (ex: native functions), they are simply called
+    Call {
+        function: FunctionDef,
+        arguments: Vec<BoxedValue>,
+    },
+    // Taken while the code is running
+    Running,
+    Finished(Option<BoxedValue>),
+}
+
+struct TaskState {
+    source: Source,
+    // What it waits for before it can go on
+    waiting: Option<Wait>,
+}
+
+/*
+ * Task, runs a function along with the other tasks, see `EventLoop`
+ *
+ * Copies of it share the same state
+ */
+#[derive(Clone)]
+pub struct Task(Rc<RefCell<TaskState>>);
+
+impl PrimitiveValueBase for Task {
+    fn as_self(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl fmt::Debug for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Task")
+    }
+}
+
+impl Serialize for Task {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("task")
+    }
+}
+
+/*
+ * Task base
+ */
+pub trait TaskValueBase {
+    fn wait_result(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+
+    fn done(
+        function: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue>;
+}
+
+/*
+ * Get the task stored in the variable a method was called from
+ */
+fn get_task(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<Task> {
+    let var_name = value_to_string(args_vals[0].clone()).unwrap();
+    let variable = stack.lock().unwrap().get_value_by_name(var_name.as_str())?;
+
+    Some(downcast_val::<Task>(variable.value.as_self()).clone())
+}
+
+impl Task {
+    /*
+     * Spawn a task running the given call, whose arguments are already resolved
+     */
+    pub(crate) fn spawn(
+        spawn: &Spawn,
+        values: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let (function, arguments) = if let Some(function) = &spawn.function {
+            (get_function_from_def(function), values)
+        } else {
+            prepare_call(stack, &spawn.call.fn_name, &spawn.call.reference_to, values)?
+        };
+
+        Self::start(&function, arguments, stack)
+    }
+
+    /*
+     * Create a task calling the function, it will start running once the code that spawned it waits
+     */
+    pub fn start(
+        function: &FunctionDef,
+        arguments: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        // Functions of modules are called, so they see the module's variables
+        let source = if function.body.is_empty() || function.generator || function.module.is_some()
+        {
+            Source::Call {
+                function: function.clone(),
+                arguments,
+            }
+        } else {
+            let scope = stack.lock().unwrap().open_scope();
+
+            if !bind_arguments(function, arguments, scope, stack) {
+                stack.lock().unwrap().close_scope(scope);
+                return None;
+            }
+
+            let scopes = stack.lock().unwrap().suspend_scopes(scope);
+            let vm = Vm::task(compiler::compile(&function.body), scope);
+
+            Source::Code {
+                vm: Box::new(vm),
+                scope,
+                scopes,
+            }
+        };
+
+        let task = Self(Rc::new(RefCell::new(TaskState {
+            source,
+            waiting: None,
+        })));
+
+        stack.lock().unwrap().event_loop.push(task.clone());
+
+        Some(BoxedValue {
+            interface: Ops::Task,
+            value: Box::new(task),
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.0.borrow().source, Source::Finished(_))
+    }
+
+    /*
+     * Copy of the value returned by the task, if it's finished
+     */
+    pub fn result(&self) -> Option<BoxedValue> {
+        match &self.0.borrow().source {
+            Source::Finished(value) => value.clone(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn waiting_for(&self) -> Option<Wait> {
+        self.0.borrow().waiting.clone()
+    }
+
+    /*
+     * Run until the task waits for something or finishes, `value` is what it was waiting for
+     */
+    pub(crate) fn run(&self, value: Option<BoxedValue>, stack: &Mutex<Stack>) {
+        let source = {
+            let mut state = self.0.borrow_mut();
+            state.waiting = None;
+            mem::replace(&mut state.source, Source::Running)
+        };
+
+        let source = match source {
+            Source::Code {
+                mut vm,
+                scope,
+                scopes,
+            } => {
+                // Its variables are the innermost ones while it runs
+                stack.lock().unwrap().resume_scopes(scopes);
+
+                match vm.resume(value, stack) {
+                    Step::Blocked(wait) => {
+                        let scopes = stack.lock().unwrap().suspend_scopes(scope);
+
+                        self.0.borrow_mut().waiting = Some(wait);
+                        stack.lock().unwrap().event_loop.push(self.clone());
+
+                        Source::Code { vm, scope, scopes }
+                    }
+                    Step::Returned(value) => {
+                        let value = value.and_then(|value| {
+                            resolve_reference(stack, value.interface, value.value.as_ref())
+                        });
+                        stack.lock().unwrap().close_scope(scope);
+
+                        Source::Finished(value)
+                    }
+                    Step::Yielded(_) => unreachable!("Tasks don't yield"),
+                }
+            }
+            Source::Call {
+                function,
+                arguments,
+            } => Source::Finished((function.cb)(&function, arguments, stack)),
+            source => source,
+        };
+
+        self.0.borrow_mut().source = source;
+    }
+}
+
+impl TaskValueBase for Task {
+    /*
+     * function: await()
+     *
+     * Waits for the task to finish and returns its result
+     */
+    fn wait_result(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let task = get_task(&args_vals, stack)?;
+        block_on(Wait::Task(task), stack)
+    }
+
+    /*
+     * function: done()
+     *
+     * Returns whether the task has finished
+     */
+    fn done(
+        _: &FunctionDef,
+        args_vals: Vec<BoxedValue>,
+        stack: &Mutex<Stack>,
+    ) -> Option<BoxedValue> {
+        let task = get_task(&args_vals, stack)?;
+
+        Some(BoxedValue {
+            interface: Ops::Boolean,
+            value: Box::new(Boolean(task.is_finished())),
+        })
+    }
+}
+
+/*
+ * Get the tasks given to `join`, reporting the values which are not tasks
+ */
+pub(crate) fn task_arguments(args_vals: &[BoxedValue], stack: &Mutex<Stack>) -> Option<Vec<Task>> {
+    args_vals
+        .iter()
+        .map(|value| {
+            if value.interface == Ops::Task {
+                Some(downcast_val::<Task>(value.value.as_self()).clone())
+            } else {
+                stack.lock().unwrap().raise_error(
+                    errors::CODES::MismatchedTypes,
+                    vec![
+                        "the arguments of 'join'".to_string(),
+                        Ops::Task.type_name().to_string(),
+                        value.interface.type_name().to_string(),
+                    ],
+                );
+                None
+            }
+        })
+        .collect()
+}
@@ -3,37 +3,76 @@ use crate::{
         boxed_val::BoxedValue,
         expression::Expression,
         fn_call::FnCall,
+        named_argument::NamedArgument,
         reference::Reference,
+        spawn::Spawn,
     },
     primitive_values::{
         boolean::Boolean,
+        channel::{
+            Channel,
+            ChannelValueBase,
+        },
+        error::{
+            ErrorVal,
+            ErrorValueBase,
+        },
+        generator::{
+            Generator,
+            GeneratorValueBase,
+        },
+        list::{
+            List,
+            ListValueBase,
+        },
+        map::{
+            Map,
+            MapValueBase,
+        },
+        none::{
+            NoneVal,
+            OptionValueBase,
+        },
         number::{
             Number,
             NumberValueBase,
         },
-        pointer::Pointer,
+        pointer::{
+            Pointer,
+            PointerBase,
+        },
+        primitive_base::PrimitiveValueBase,
+        process::{
+            Process,
+            ProcessValueBase,
+        },
         string::StringVal,
+        task::{
+            Task,
+            TaskValueBase,
+        },
     },
-    run_ast,
+    run_body,
     stack::{
+        FunctionAction,
         FunctionDef,
         FunctionsContainer,
+        Scope,
         Stack,
+        VariableDef,
+        GLOBAL_SCOPE,
     },
-    types::BoxedPrimitiveValue,
+    stdlib::none_value,
     utils::{
         errors,
-        errors::raise_error,
         Ops,
     },
 };
 use std::{
     any::Any,
     collections::HashMap,
-    sync::{
-        Mutex,
-        MutexGuard,
-    },
+    rc::Rc,
+    sync::Mutex,
 };
 
 /*
@@ -46,22 +85,46 @@ pub fn downcast_val<T: 'static>(val: &dyn Any) -> &T {
 /*
  * Force the transformation from a primitive type into a String
  */
-pub fn value_to_string(value: BoxedValue, stack: &Mutex<Stack>) -> Result<String, Ops> {
+pub fn value_to_string(value: BoxedValue) -> Result<String, Ops> {
     match value.interface {
         Ops::Boolean => Ok(downcast_val::<Boolean>(value.value.as_self()).0.to_string()),
         Ops::String => Ok(downcast_val::<StringVal>(value.value.as_self()).0.clone()),
         Ops::Number => Ok(downcast_val::<Number>(value.value.as_self()).0.to_string()),
         Ops::Pointer => {
-            let pointer = downcast_val::<Pointer>(value.value.as_self()).0;
-            let variable = stack.lock().unwrap().get_variable_by_id(pointer).unwrap();
-
-            value_to_string(
-                BoxedValue {
-                    value: variable.value,
-                    interface: variable.val_type,
-                },
-                stack,
-            )
+            let pointer = downcast_val::<Pointer>(value.value.as_self());
+            value_to_string(pointer.get_state())
+        }
+        Ops::List => {
+            let list = downcast_val::<List>(value.value.as_self());
+            let items = list
+                .get_state()
+                .iter()
+                .map(|item| value_to_string(item.clone()))
+                .collect::<Result<Vec<String>, Ops>>()?;
+            Ok(format!("[{}]", items.join(", ")))
+        }
+        Ops::Map => {
+            let map = downcast_val::<Map>(value.value.as_self());
+            let entries = map
+                .get_state()
+                .iter()
+                .map(|(key, value)| Ok(format!("{}: {}", key, value_to_string(value.clone())?)))
+                .collect::<Result<Vec<String>, Ops>>()?;
+            Ok(format!("{{{}}}", entries.join(", ")))
+        }
+        Ops::Error => {
+            let error = downcast_val::<ErrorVal>(value.value.as_self());
+            Ok(format!("error: {}", error.get_state()))
+        }
+        Ops::Generator => Ok("generator".to_string()),
+        Ops::Task => Ok("task".to_string()),
+        Ops::Channel => Ok("channel".to_string()),
+        Ops::Process => Ok("process".to_string()),
+        Ops::None => Ok("none".to_string()),
+        // Native functions don't know about names, they get the value
+        Ops::NamedArgument => {
+            let argument = downcast_val::<NamedArgument>(value.value.as_self());
+            value_to_string(argument.value.clone())
         }
         _ => Err(value.interface),
     }
@@ -70,10 +133,10 @@ pub fn value_to_string(value: BoxedValue, stack: &Mutex<Stack>) -> Result<String
 /*
  * Transform a group of boxed values into strings
  */
-pub fn values_to_strings(values: Vec<BoxedValue>, stack: &Mutex<Stack>) -> Vec<String> {
+pub fn values_to_strings(values: Vec<BoxedValue>) -> Vec<String> {
     return values
         .iter()
-        .map(|arg| value_to_string(arg.clone(), stack).unwrap())
+        .map(|arg| value_to_string(arg.clone()).unwrap())
         .collect();
 }
 
@@ -81,182 +144,287 @@ pub fn values_to_strings(values: Vec<BoxedValue>, stack: &Mutex<Stack>) -> Vec<S
  * Returns the methods for the specified primitive type
  */
 pub fn get_methods_in_type(val_type: Ops) -> HashMap<String, FunctionDef> {
-    let mut res = HashMap::new();
-
     /*
      * map a value type to it's implemented functions
      */
-    match val_type {
-        Ops::Number => {
-            res.insert(
-                "sum".to_string(),
-                FunctionDef {
-                    name: String::from("sum"),
-                    body: vec![],
-                    cb: Number::sum,
-                    expr_id: "".to_string(),
-                    arguments: vec![],
-                },
-            );
-
-            res.insert(
-                "mut_sum".to_string(),
-                FunctionDef {
-                    name: String::from("mut_sum"),
-                    body: vec![],
-                    cb: Number::mut_sum,
-                    expr_id: "".to_string(),
-                    arguments: vec![],
-                },
-            );
-        }
+    let methods: &[&str] = match val_type {
+        Ops::Number => &["sum", "mut_sum"],
 
         /*
          * TODO: Methods for strings
          */
-        Ops::String => {}
+        Ops::String => &[],
 
         /*
          * TODO: Methods for booleans
          */
-        Ops::Boolean => {}
-        _ => (),
+        Ops::Boolean => &[],
+        Ops::List => &["len", "get"],
+        Ops::Map => &["len", "get", "has", "keys"],
+        Ops::Error => &["message"],
+        Ops::Process => &["write", "close", "read_line", "wait"],
+        Ops::Generator => &["next", "done"],
+        Ops::Task => &["await", "done"],
+        Ops::Channel => &["send", "recv"],
+        _ => &[],
+    };
+
+    methods
+        .iter()
+        .chain(&OPTION_METHODS)
+        .filter_map(|name| get_method_in_type(val_type, name))
+        .map(|method| (method.name.clone(), method))
+        .collect()
+}
+
+/*
+ * Methods which only read or modify their own variable, and report their errors instead of panicking
+ *
+ * The optimizer computes calls to them before running, so a method with any other
+ * side effect (ex: printing or blocking) must not be listed here
+ */
+pub(crate) const PURE_METHODS: [&str; 9] = [
+    "sum",
+    "mut_sum",
+    "len",
+    "get",
+    "has",
+    "keys",
+    "is_none",
+    "is_error",
+    "unwrap_or",
+];
+
+// Methods every value has, to check for none and errors
+const OPTION_METHODS: [&str; 3] = ["is_none", "is_error", "unwrap_or"];
+
+/*
+ * Returns a method of the specified primitive type, see `PURE_METHODS` when adding one
+ */
+pub fn get_method_in_type(val_type: Ops, fn_name: &str) -> Option<FunctionDef> {
+    let cb: FunctionAction = match (val_type, fn_name) {
+        (Ops::Number, "sum") => Number::sum,
+        (Ops::Number, "mut_sum") => Number::mut_sum,
+        (Ops::List, "len") => List::len,
+        (Ops::List, "get") => List::get,
+        (Ops::Map, "len") => Map::len,
+        (Ops::Map, "get") => Map::get,
+        (Ops::Map, "has") => Map::has,
+        (Ops::Map, "keys") => Map::keys,
+        (Ops::Error, "message") => ErrorVal::message,
+        (Ops::Process, "write") => Process::write,
+        (Ops::Process, "close") => Process::close,
+        (Ops::Process, "read_line") => Process::read_line,
+        (Ops::Process, "wait") => Process::wait,
+        (Ops::Generator, "next") => Generator::next,
+        (Ops::Generator, "done") => Generator::done,
+        (Ops::Task, "await") => Task::wait_result,
+        (Ops::Task, "done") => Task::done,
+        (Ops::Channel, "send") => Channel::send,
+        (Ops::Channel, "recv") => Channel::recv,
+        (_, "is_none") => NoneVal::is_none,
+        (_, "is_error") => NoneVal::is_error,
+        (_, "unwrap_or") => NoneVal::unwrap_or,
+        _ => return None,
+    };
+
+    Some(FunctionDef {
+        name: fn_name.to_string(),
+        body: Rc::default(),
+        cb,
+        expr_id: GLOBAL_SCOPE,
+        arguments: vec![],
+        generator: false,
+        defaults: vec![],
+        rest_argument: None,
+        module: None,
+    })
+}
+
+/*
+ * Find the function of a call, by its name or as a method of a variable, and put together its
+ * arguments, reporting the error if it's not found
+ *
+ * Methods get the name of their variable as first argument, except the functions of modules
+ */
+pub(crate) fn prepare_call(
+    stack: &Mutex<Stack>,
+    name: &str,
+    reference_to: &Option<String>,
+    values: Vec<BoxedValue>,
+) -> Option<(FunctionDef, Vec<BoxedValue>)> {
+    let function = {
+        let stack = stack.lock().unwrap();
+
+        let function = if let Some(reference_to) = reference_to {
+            if let Some(variable) = stack.find_variable(reference_to) {
+                variable.get_function_by_name(name)
+            } else {
+                stack.raise_error(errors::CODES::VariableNotFound, vec![reference_to.clone()]);
+                return None;
+            }
+        } else {
+            stack.get_function_by_name(name)
+        };
+
+        if function.is_none() {
+            stack.raise_error(errors::CODES::FunctionNotFound, vec![name.to_string()]);
+        }
+
+        function?
+    };
+
+    let mut arguments = Vec::new();
+
+    if let Some(reference_to) = reference_to.as_ref().filter(|_| function.module.is_none()) {
+        arguments.push(BoxedValue {
+            interface: Ops::String,
+            value: Box::new(StringVal(reference_to.to_string())),
+        });
     }
 
-    res
+    arguments.extend(values);
+
+    Some((function, arguments))
 }
 
 /*
- * For static values it will just return the input but for references it will resolve its value
+ * For static values it will just return a copy of the input but for references it will resolve its value
  * and then return it
  */
 pub fn resolve_reference(
     stack: &Mutex<Stack>,
     val_type: Ops,
-    ref_val: BoxedPrimitiveValue,
-    ast: &MutexGuard<Expression>,
+    ref_val: &(dyn PrimitiveValueBase + 'static),
 ) -> Option<BoxedValue> {
     match val_type {
         Ops::Expression => {
             let expr = downcast_val::<Expression>(ref_val.as_self());
-            let res = run_ast(&Mutex::new(expr.clone()), stack);
-
-            stack.lock().unwrap().drop_ops_from_id(expr.expr_id.clone());
+            let scope = Scope::new(stack);
 
-            res
-        }
+            // The rest of the group uses the result of its call, so it gives nothing if the call doesn't
+            if let Some(result) = expr.get_call_result() {
+                let assignment = &result.assignment;
+                let value =
+                    resolve_reference(stack, assignment.interface, assignment.value.as_ref())?;
 
-        Ops::Pointer => {
-            let pointer = downcast_val::<Pointer>(ref_val.as_self()).0;
-            let variable = stack.lock().unwrap().get_variable_by_id(pointer);
+                stack.lock().unwrap().push_variable(VariableDef::new(
+                    result.def_name.clone(),
+                    value,
+                    scope.id,
+                ));
 
-            if let Some(variable) = variable {
-                Some(BoxedValue {
-                    value: variable.value,
-                    interface: variable.val_type,
-                })
-            } else {
-                raise_error(errors::CODES::BrokenPointer, vec![pointer.to_string()]);
-                None
+                return run_body(&expr.body[1..], scope.id, stack);
             }
+
+            run_body(&expr.body, scope.id, stack)
         }
+
+        Ops::Pointer => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
         Ops::String => Some(BoxedValue {
             interface: val_type,
-            value: ref_val,
+            value: dyn_clone::clone_box(ref_val),
         }),
         Ops::Boolean => Some(BoxedValue {
             interface: val_type,
-            value: ref_val,
+            value: dyn_clone::clone_box(ref_val),
         }),
         Ops::Number => Some(BoxedValue {
             interface: val_type,
-            value: ref_val,
+            value: dyn_clone::clone_box(ref_val),
         }),
-        Ops::Reference => {
-            let mut referenced_variable = downcast_val::<Reference>(ref_val.as_self()).clone();
+        Ops::List => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Map => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Error => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Process => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::None => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Generator => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Task => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Channel => Some(BoxedValue {
+            interface: val_type,
+            value: dyn_clone::clone_box(ref_val),
+        }),
+        Ops::Spawn => {
+            let spawn = downcast_val::<Spawn>(ref_val.as_self());
 
-            let is_pointer = referenced_variable.0.starts_with('&');
+            let mut arguments = Vec::new();
 
-            if is_pointer {
-                // Remove & from it's name
-                referenced_variable.0.remove(0);
+            for argument in &spawn.call.arguments {
+                if let Some(arg_ref) =
+                    resolve_reference(stack, argument.interface, argument.value.as_ref())
+                {
+                    arguments.push(arg_ref);
+                }
             }
 
-            let variable = stack
+            Task::spawn(spawn, arguments, stack)
+        }
+        Ops::NamedArgument => {
+            let argument = downcast_val::<NamedArgument>(ref_val.as_self());
+            let value = resolve_reference(
+                stack,
+                argument.value.interface,
+                argument.value.value.as_ref(),
+            )?;
+
+            // The name is kept so the called function knows which argument it is
+            Some(BoxedValue {
+                interface: val_type,
+                value: Box::new(NamedArgument {
+                    name: argument.name.clone(),
+                    value,
+                }),
+            })
+        }
+        Ops::Reference => {
+            let referenced_variable = &downcast_val::<Reference>(ref_val.as_self()).0;
+
+            // Returns a copy of it's value, or a pointer to it
+            stack
                 .lock()
                 .unwrap()
-                .get_variable_by_name(referenced_variable.0.as_str());
-
-            if let Some(variable) = variable {
-                if is_pointer {
-                    // Return a pointer
-                    Some(BoxedValue {
-                        interface: Ops::Pointer,
-                        value: Box::new(Pointer(variable.var_id)),
-                    })
-                } else {
-                    // Return a copy of it's value
-                    Some(BoxedValue {
-                        interface: variable.val_type,
-                        value: variable.value,
-                    })
-                }
-            } else {
-                raise_error(errors::CODES::VariableNotFound, vec![referenced_variable.0]);
-                None
-            }
+                .get_value_by_reference(referenced_variable)
         }
         Ops::FnCall => {
             let fn_call = downcast_val::<FnCall>(ref_val.as_self());
 
-            let is_referenced = fn_call.reference_to.is_some();
-
-            let function = if is_referenced {
-                let reference_to = fn_call.reference_to.as_ref().unwrap();
-                let variable = stack
-                    .lock()
-                    .unwrap()
-                    .get_variable_by_name(reference_to.as_str());
-
-                variable
-                    .unwrap()
-                    .get_function_by_name(fn_call.fn_name.as_str())
-            } else {
-                stack
-                    .lock()
-                    .unwrap()
-                    .get_function_by_name(fn_call.fn_name.as_str())
-            };
-
-            // If the calling function is found
-            if let Some(function) = function {
-                let mut arguments = Vec::new();
-
-                // Pass the reference name as first argument
-                if is_referenced {
-                    let reference_to = fn_call.reference_to.as_ref().unwrap();
-                    arguments.push(BoxedValue {
-                        interface: Ops::String,
-                        value: Box::new(StringVal(reference_to.to_string())),
-                    });
-                }
-
-                for argument in &fn_call.arguments {
-                    let arg_ref =
-                        resolve_reference(stack, argument.interface, argument.value.clone(), &ast);
+            let values = fn_call
+                .arguments
+                .iter()
+                .filter_map(|argument| {
+                    resolve_reference(stack, argument.interface, argument.value.as_ref())
+                })
+                .collect();
 
-                    if let Some(arg_ref) = arg_ref {
-                        arguments.push(arg_ref);
-                    } else {
-                        // Broken argument
-                    }
-                }
+            let (function, arguments) =
+                prepare_call(stack, &fn_call.fn_name, &fn_call.reference_to, values)?;
 
-                // Call the function and return it's result
-                (function.cb)(function.arguments, arguments, function.body, &stack, &ast)
-            } else {
-                None
-            }
+            // Call the function and return it's result, which is `none` if it doesn't return anything
+            Some((function.cb)(&function, arguments, stack).unwrap_or_else(none_value))
         }
         _ => None,
     }
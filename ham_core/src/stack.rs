@@ -2,80 +2,135 @@ use crate::{
     ast_types::{
         ast_base::AstBase,
         boxed_val::BoxedValue,
-        expression::Expression,
+    },
+    clock::Clock,
+    event_loop::{
+        block_on,
+        EventLoop,
+        Wait,
+    },
+    limits::{
+        Abort,
+        LimitError,
+        Limits,
+        Usage,
+    },
+    modules::{
+        check_arguments,
+        ModuleGraph,
+        ModuleScope,
+        NativeModule,
+    },
+    permissions::{
+        Capability,
+        Permissions,
     },
     primitive_values::{
-        pointer::Pointer,
-        primitive_base::PrimitiveValueBase,
+        channel::Channel,
+        pointer::{
+            HeapCell,
+            Pointer,
+        },
         string::StringVal,
+        task::task_arguments,
     },
     runtime::{
-        downcast_val,
+        get_method_in_type,
         value_to_string,
         values_to_strings,
     },
+    stdlib::{
+        self,
+        to_number,
+    },
+    streams::Streams,
     utils::{
         errors,
         Ops,
     },
 };
 use std::{
+    cell::RefCell,
     collections::HashMap,
-    sync::{
-        Mutex,
-        MutexGuard,
+    rc::Rc,
+    sync::Mutex,
+    time::{
+        self,
+        Instant,
     },
-    thread,
-    time,
 };
 
+/*
+ * ID of the scope where the whole program runs
+ */
+pub const GLOBAL_SCOPE: u64 = 0;
+
 /*
  * Variable definition stored on the memory stack
  */
 #[derive(Clone)]
 pub struct VariableDef {
     pub name: String,
-    pub val_type: Ops,
-    pub value: Box<dyn PrimitiveValueBase>,
-    pub expr_id: String,
+    // Pointers to the variable share this cell, so they keep it alive
+    pub value: HeapCell,
+    pub expr_id: u64,
     pub functions: HashMap<String, FunctionDef>,
-    pub var_id: u64,
+}
+
+impl VariableDef {
+    pub fn new(name: String, value: BoxedValue, expr_id: u64) -> Self {
+        Self {
+            name,
+            value: Rc::new(RefCell::new(value)),
+            expr_id,
+            functions: HashMap::new(),
+        }
+    }
+
+    /*
+     * Type of the value currently stored in the variable
+     */
+    pub fn val_type(&self) -> Ops {
+        self.value.borrow().interface
+    }
 }
 
 impl FunctionsContainer for VariableDef {
     fn get_function_by_name(&self, fn_name: &str) -> Option<FunctionDef> {
-        let op_fn: Option<&FunctionDef> = self.functions.get(fn_name);
-
-        if let Some(op_fn) = op_fn {
-            Some(op_fn.clone())
-        } else {
-            errors::raise_error(errors::CODES::FunctionNotFound, vec![fn_name.to_string()]);
-            None
-        }
+        self.functions
+            .get(fn_name)
+            .cloned()
+            .or_else(|| get_method_in_type(self.val_type(), fn_name))
     }
     fn push_function(&mut self, function: FunctionDef) {
         self.functions.insert(function.name.clone(), function);
     }
 }
 
-type FunctionAction = fn(
-    args: Vec<String>,
+pub type FunctionAction = fn(
+    function: &FunctionDef,
     args_vals: Vec<BoxedValue>,
-    body: Vec<Box<dyn AstBase>>,
     stack: &Mutex<Stack>,
-    ast: &MutexGuard<Expression>,
 ) -> Option<BoxedValue>;
 
 /*
  * Function definition stored on the memory stack
  */
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct FunctionDef {
     pub name: String,
-    pub body: Vec<Box<dyn AstBase>>,
+    pub body: Rc<Vec<Box<dyn AstBase>>>,
     pub cb: FunctionAction,
-    pub expr_id: String,
+    pub expr_id: u64,
     pub arguments: Vec<String>,
+    // Default value of the arguments which can be left out
+    pub defaults: Vec<(String, BoxedValue)>,
+    // Argument collecting the rest of the arguments into a list, ex: `...rest`
+    pub rest_argument: Option<String>,
+    // Calling it creates a generator instead of running it
+    pub generator: bool,
+    // Module it belongs to, its variables are visible while the function runs
+    pub module: Option<ModuleScope>,
 }
 
 /*
@@ -83,7 +138,7 @@ pub struct FunctionDef {
  */
 pub trait FunctionsContainer {
     /*
-     * Return the requested function if found
+     * Return the requested function if found, reporting the error is up to the caller
      */
     fn get_function_by_name(&self, fn_name: &str) -> Option<FunctionDef>;
     /*
@@ -92,28 +147,83 @@ pub trait FunctionsContainer {
     fn push_function(&mut self, function: FunctionDef);
 }
 
+/*
+ * Where a scope starts on the stack
+ */
+#[derive(Clone, Copy)]
+struct ScopeMark {
+    id: u64,
+    variables: usize,
+    functions: usize,
+}
+
+/*
+ * Scopes taken out of the stack along with everything defined in them, so they can be put back later
+ *
+ * This is how generators keep their variables while they are not running
+ */
+#[derive(Clone, Default)]
+pub struct SuspendedScopes {
+    // Where every scope starts, relative to the first one
+    scopes: Vec<ScopeMark>,
+    variables: Vec<VariableDef>,
+    functions: Vec<FunctionDef>,
+}
+
+/*
+ * A scope opened on the stack, everything defined inside it is dropped along with it
+ */
+pub struct Scope<'a> {
+    pub id: u64,
+    stack: &'a Mutex<Stack>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(stack: &'a Mutex<Stack>) -> Self {
+        let id = stack.lock().unwrap().open_scope();
+        Self { id, stack }
+    }
+}
+
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut stack) = self.stack.lock() {
+            stack.close_scope(self.id);
+        }
+    }
+}
+
 /*
  * Implementation of the stack
  */
 #[derive(Clone)]
 pub struct Stack {
-    pub functions: HashMap<String, FunctionDef>,
+    pub functions: Vec<FunctionDef>,
     pub variables: Vec<VariableDef>,
+    scopes: Vec<ScopeMark>,
     pub item_index: u64,
+    pub streams: Streams,
+    pub limits: Limits,
+    pub usage: Usage,
+    pub aborted: Option<Abort>,
+    pub permissions: Permissions,
+    pub event_loop: EventLoop,
+    pub clock: Clock,
+    pub modules: ModuleGraph,
+    // Command-line arguments given to the script, ex: `ham run main.ham -- a b`
+    pub args: Vec<String>,
 }
 
 impl FunctionsContainer for Stack {
     fn get_function_by_name(&self, fn_name: &str) -> Option<FunctionDef> {
-        for function in self.functions.values() {
-            if function.name == *fn_name {
-                return Some(function.clone());
-            }
-        }
-        errors::raise_error(errors::CODES::FunctionNotFound, vec![fn_name.to_string()]);
-        None
+        self.functions
+            .iter()
+            .rev()
+            .find(|function| function.name == *fn_name)
+            .cloned()
     }
     fn push_function(&mut self, function: FunctionDef) {
-        self.functions.insert(function.expr_id.clone(), function);
+        self.functions.push(function);
     }
 }
 
@@ -123,22 +233,27 @@ impl Stack {
         self.item_index
     }
 
-    pub fn new(expr_id: String) -> Stack {
-        let mut functions = HashMap::new();
-
-        functions.insert(
-            "clear".to_string(),
-            FunctionDef {
-                name: "clear".to_string(),
-                body: vec![],
-                arguments: vec![],
-                cb: |_, _, _, _, _| {
-                    print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
-                    None
-                },
-                expr_id: expr_id.clone(),
+    pub fn new(expr_id: u64) -> Stack {
+        let mut functions = Vec::new();
+
+        functions.push(FunctionDef {
+            name: "clear".to_string(),
+            body: Rc::default(),
+            arguments: vec![],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |_, _, stack| {
+                stack
+                    .lock()
+                    .unwrap()
+                    .streams
+                    .write_out(&format!("{esc}[2J{esc}[1;1H", esc = 27 as char));
+                None
             },
-        );
+            expr_id,
+        });
 
         /*
          * format() function
@@ -148,93 +263,318 @@ impl Stack {
          * let msg = format("Hello {} from {}", "people", "world")
          *
          */
-        functions.insert(
-            "format".to_string(),
-            FunctionDef {
-                name: "format".to_string(),
-                body: vec![],
-                arguments: vec![],
-                cb: |_, args, _, stack, _| {
-                    let mut args = values_to_strings(args, stack);
-
-                    let mut template = args[0].clone();
+        functions.push(FunctionDef {
+            name: "format".to_string(),
+            body: Rc::default(),
+            arguments: vec![],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |_, args, stack| {
+                let mut args = values_to_strings(args);
+
+                // The template is needed
+                if args.is_empty() {
+                    stack.lock().unwrap().raise_error(
+                        errors::CODES::WrongArgumentsCount,
+                        vec![
+                            "format".to_string(),
+                            errors::arguments_count(1, 1, true),
+                            "0".to_string(),
+                        ],
+                    );
+                    return None;
+                }
 
-                    args.remove(0);
+                let mut template = args.remove(0);
 
-                    for arg in args {
-                        template = template.replacen("{}", arg.as_str(), 1);
+                for arg in args {
+                    if let Some(position) = template.find("{}") {
+                        // Sized up front, so long texts are not copied again while growing
+                        let mut formatted = String::with_capacity(template.len() + arg.len());
+                        formatted.push_str(&template[..position]);
+                        formatted.push_str(&arg);
+                        formatted.push_str(&template[position + 2..]);
+                        template = formatted;
                     }
+                }
 
-                    Some(BoxedValue {
-                        interface: Ops::String,
-                        value: Box::new(StringVal(template)),
-                    })
-                },
-                expr_id: expr_id.clone(),
+                Some(BoxedValue {
+                    interface: Ops::String,
+                    value: Box::new(StringVal(template)),
+                })
             },
-        );
+            expr_id,
+        });
 
         /*
          * print() function
          */
-        functions.insert(
-            "print".to_string(),
-            FunctionDef {
-                name: "print".to_string(),
-                body: vec![],
-                arguments: vec![],
-                cb: |_, args, _, stack, _| {
-                    print!("{}", values_to_strings(args, stack).join(" "));
-                    None
-                },
-                expr_id: expr_id.clone(),
+        functions.push(FunctionDef {
+            name: "print".to_string(),
+            body: Rc::default(),
+            arguments: vec![],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |_, args, stack| {
+                let text = values_to_strings(args).join(" ");
+                stack.lock().unwrap().streams.write_out(&text);
+                None
             },
-        );
+            expr_id,
+        });
 
         /*
          * println() function
          */
-        functions.insert(
-            "println".to_string(),
-            FunctionDef {
-                name: "println".to_string(),
-                body: vec![],
-                arguments: vec![],
-                cb: |_, args, _, stack, _| {
-                    println!("{}", values_to_strings(args, stack).join(""));
-                    None
-                },
-                expr_id: expr_id.clone(),
+        functions.push(FunctionDef {
+            name: "println".to_string(),
+            body: Rc::default(),
+            arguments: vec![],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |_, args, stack| {
+                let text = values_to_strings(args).join("");
+                stack
+                    .lock()
+                    .unwrap()
+                    .streams
+                    .write_out(&format!("{}\n", text));
+                None
             },
-        );
+            expr_id,
+        });
 
         /*
          * wait() function
          */
-        functions.insert(
-            "wait".to_string(),
-            FunctionDef {
-                name: "wait".to_string(),
-                body: vec![],
-                arguments: vec![],
-                cb: |_, args, _, stack, _| {
-                    let time = value_to_string(args[0].clone(), stack)
-                        .unwrap()
-                        .parse::<u64>()
-                        .unwrap();
-                    let time = time::Duration::from_millis(time);
-                    thread::sleep(time);
-                    None
-                },
-                expr_id,
+        functions.push(FunctionDef {
+            name: "wait".to_string(),
+            body: Rc::default(),
+            arguments: vec!["milliseconds".to_string()],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |function, args_vals, stack| {
+                if !check_arguments(function, &args_vals, &[Ops::Number], stack) {
+                    return None;
+                }
+                let time = time::Duration::from_millis(to_number(&args_vals[0]) as u64);
+
+                // Other tasks run in the meantime
+                let deadline = stack.lock().unwrap().clock.now() + time;
+                block_on(Wait::Timer(deadline), stack)
+            },
+            expr_id,
+        });
+
+        /*
+         * channel() function
+         *
+         * Example:
+         *
+         * let messages = channel()
+         * messages.send("hello")
+         * println(messages.recv())
+         *
+         */
+        functions.push(FunctionDef {
+            name: "channel".to_string(),
+            body: Rc::default(),
+            arguments: vec![],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |_, _, _| {
+                Some(BoxedValue {
+                    interface: Ops::Channel,
+                    value: Box::new(Channel::default()),
+                })
+            },
+            expr_id,
+        });
+
+        /*
+         * join() function, waits for all the given tasks and returns a list with their results
+         *
+         * Example:
+         *
+         * let first = spawn fetch(1)
+         * let second = spawn fetch(2)
+         * let results = join(first, second)
+         *
+         */
+        functions.push(FunctionDef {
+            name: "join".to_string(),
+            body: Rc::default(),
+            arguments: vec![],
+            generator: false,
+            defaults: vec![],
+            rest_argument: None,
+            module: None,
+            cb: |_, args, stack| {
+                let tasks = task_arguments(&args, stack)?;
+                block_on(Wait::All(tasks), stack)
             },
-        );
+            expr_id,
+        });
 
-        Stack {
+        let mut stack = Stack {
             variables: Vec::new(),
             functions,
+            scopes: Vec::new(),
             item_index: 0,
+            streams: Streams::default(),
+            limits: Limits::default(),
+            usage: Usage::default(),
+            aborted: None,
+            permissions: Permissions::default(),
+            event_loop: EventLoop::default(),
+            clock: Clock::default(),
+            modules: ModuleGraph::default(),
+            args: Vec::new(),
+        };
+
+        stdlib::register_modules(&mut stack);
+
+        stack
+    }
+
+    /*
+     * Make a native module available to the code, ex: `import db from "host:db"`
+     */
+    pub fn register_module(&mut self, name: &str, module: NativeModule) {
+        self.modules.natives.insert(name.to_string(), module);
+    }
+
+    /*
+     * Make every run of the same code behave the same, time only passes
+     * when the code waits, so it doesn't depend on how fast it runs
+     */
+    pub fn set_deterministic(&mut self) {
+        self.clock = Clock::virtual_time();
+    }
+
+    /*
+     * Check if the script is allowed to use a capability on the given target,
+     * reporting an error if it's not
+     */
+    pub fn check_permission(&self, capability: Capability, target: &str) -> bool {
+        let allowed = self.permissions.is_allowed(capability, target);
+
+        if !allowed {
+            self.raise_error(
+                errors::CODES::PermissionDenied,
+                vec![capability.name().to_string(), target.to_string()],
+            );
+        }
+
+        allowed
+    }
+
+    /*
+     * Stop the execution, ex: because a limit was hit, only the first reason is kept
+     */
+    pub fn abort(&mut self, reason: impl Into<Abort>) {
+        if self.aborted.is_none() {
+            self.aborted = Some(reason.into());
+        }
+    }
+
+    /*
+     * Count an evaluated AST node
+     *
+     * Returns false if the execution must stop
+     */
+    pub fn tick(&mut self) -> bool {
+        self.tick_by(1)
+    }
+
+    /*
+     * Count some evaluated AST nodes or VM instructions at once
+     *
+     * Returns false if the execution must stop
+     */
+    pub fn tick_by(&mut self, count: u64) -> bool {
+        if self.aborted.is_some() {
+            return false;
+        }
+
+        self.usage.instructions += count;
+
+        if let Some(max) = self.limits.max_instructions {
+            if self.usage.instructions > max {
+                self.abort(LimitError::Instructions(max));
+                return false;
+            }
+        }
+
+        if let Some(timeout) = self.limits.timeout {
+            let started = *self.usage.started.get_or_insert_with(Instant::now);
+            if started.elapsed() > timeout {
+                self.abort(LimitError::Timeout(timeout));
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /*
+     * When the execution hits the timeout, if there is one
+     */
+    pub(crate) fn timeout_deadline(&mut self) -> Option<Instant> {
+        let timeout = self.limits.timeout?;
+        let started = *self.usage.started.get_or_insert_with(Instant::now);
+
+        Some(started + timeout)
+    }
+
+    /*
+     * Enter into a function call
+     *
+     * Returns false if the execution must stop, in which case the call must not be made
+     */
+    pub fn enter_call(&mut self) -> bool {
+        if self.aborted.is_some() {
+            return false;
         }
+        let max = self.limits.call_depth();
+        if self.usage.call_depth >= max {
+            self.abort(LimitError::CallDepth(max));
+            return false;
+        }
+        self.usage.call_depth += 1;
+        true
+    }
+
+    /*
+     * Leave a function call previously entered with `enter_call`
+     */
+    pub fn exit_call(&mut self) {
+        self.usage.call_depth -= 1;
+    }
+
+    /*
+     * Report an error into the error stream
+     */
+    pub fn raise_error(&self, kind: errors::CODES, args: Vec<String>) {
+        self.streams.write_err(&errors::format_error(kind, args));
+    }
+
+    /*
+     * Report an error found in a line of the code before running it
+     */
+    pub fn raise_error_in_line(&self, kind: errors::CODES, args: Vec<String>, line: usize) {
+        self.streams
+            .write_err(&errors::format_error_in_line(kind, args, line));
     }
 
     /*
@@ -248,9 +588,9 @@ impl Stack {
             .map(|func| {
                 format!(
                     "fn {}({}); is {} \n",
-                    func.0,
-                    func.1.arguments.join(", "),
-                    func.1.expr_id
+                    func.name,
+                    func.arguments.join(", "),
+                    func.expr_id
                 )
             })
             .collect();
@@ -261,49 +601,107 @@ impl Stack {
                 format!(
                     "let {} = {};  in {} \n",
                     var.name,
-                    value_to_string(
-                        BoxedValue {
-                            value: var.value.clone(),
-                            interface: var.val_type
-                        },
-                        &Mutex::new(self.clone())
-                    )
-                    .unwrap(),
+                    value_to_string(var.value.borrow().clone()).unwrap(),
                     var.expr_id
                 )
             })
             .collect();
-        println!(
-            "DEBUG:: \n | functions | \n{} \n | variables | \n{}",
+        self.streams.write_out(&format!(
+            "DEBUG:: \n | functions | \n{} \n | variables | \n{}\n",
             functions, variables
-        );
+        ));
     }
 
     /*
-     * Drop from the stack all variables and functions which are port
-     * of an specified expression ID
+     * Open a new scope, returns its ID
      *
-     * This is mainly used in block expression (functions, if...)
+     * This is mainly used in block expressions (functions, if...), see `Scope`
      */
-    pub fn drop_ops_from_id(&mut self, id: String) {
-        self.variables.retain(|var| var.expr_id != id);
-        self.functions.retain(|_, func| func.expr_id != id);
+    pub fn open_scope(&mut self) -> u64 {
+        let id = self.reseve_index();
+        self.scopes.push(ScopeMark {
+            id,
+            variables: self.variables.len(),
+            functions: self.functions.len(),
+        });
+        id
     }
 
-    pub fn push_variable(&mut self, var: VariableDef) {
-        self.variables.push(var);
+    /*
+     * Close a scope, dropping all the variables and functions defined since it was opened,
+     * including the ones of any inner scope still open
+     */
+    pub fn close_scope(&mut self, id: u64) {
+        if let Some(index) = self.scopes.iter().rposition(|scope| scope.id == id) {
+            let mark = self.scopes[index];
+            self.scopes.truncate(index);
+            self.variables.truncate(mark.variables);
+            self.functions.truncate(mark.functions);
+        }
     }
 
     /*
-     * Get a variable from the stack by its ID
+     * Take a scope out of the stack, along with any inner scope still open
      */
-    pub fn get_variable_by_id(&self, var_id: u64) -> Option<VariableDef> {
-        for variable in &self.variables {
-            if variable.var_id == var_id {
-                return Some(variable.clone());
+    pub fn suspend_scopes(&mut self, id: u64) -> SuspendedScopes {
+        let index = if let Some(index) = self.scopes.iter().rposition(|scope| scope.id == id) {
+            index
+        } else {
+            return SuspendedScopes::default();
+        };
+
+        let first = self.scopes[index];
+
+        SuspendedScopes {
+            scopes: self
+                .scopes
+                .split_off(index)
+                .into_iter()
+                .map(|scope| ScopeMark {
+                    id: scope.id,
+                    variables: scope.variables - first.variables,
+                    functions: scope.functions - first.functions,
+                })
+                .collect(),
+            variables: self.variables.split_off(first.variables),
+            functions: self.functions.split_off(first.functions),
+        }
+    }
+
+    /*
+     * Put back some suspended scopes, as the innermost ones
+     */
+    pub fn resume_scopes(&mut self, suspended: SuspendedScopes) {
+        for scope in suspended.scopes {
+            self.scopes.push(ScopeMark {
+                id: scope.id,
+                variables: self.variables.len() + scope.variables,
+                functions: self.functions.len() + scope.functions,
+            });
+        }
+
+        self.variables.extend(suspended.variables);
+        self.functions.extend(suspended.functions);
+    }
+
+    pub fn push_variable(&mut self, var: VariableDef) {
+        if let Some(max) = self.limits.max_variables {
+            if self.variables.len() >= max {
+                self.abort(LimitError::Variables(max));
+                return;
             }
         }
-        None
+        self.variables.push(var);
+    }
+
+    /*
+     * Get a reference to a variable from the stack by its name, without copying it
+     */
+    pub fn find_variable(&self, var_name: &str) -> Option<&VariableDef> {
+        self.variables
+            .iter()
+            .rev()
+            .find(|variable| variable.name == *var_name)
     }
 
     /*
@@ -319,15 +717,89 @@ impl Stack {
     }
 
     /*
-     * Get a mutable variable from the stack by its ID
+     * Get a copy of a variable's value by its name
      */
-    pub fn get_mut_variable_by_id(&mut self, var_id: u64) -> Option<&mut VariableDef> {
-        for variable in &mut self.variables {
-            if variable.var_id == var_id {
-                return Some(variable);
+    pub fn get_value_by_name(&self, var_name: &str) -> Option<BoxedValue> {
+        self.find_variable(var_name)
+            .map(|variable| variable.value.borrow().clone())
+    }
+
+    /*
+     * Get the value of a reference, which can be a variable (`a`), a pointer to it (`&a`)
+     * or the value a pointer points to (`*a`, `**a`...)
+     *
+     * Errors are reported through the stack
+     */
+    pub fn get_value_by_reference(&self, reference: &str) -> Option<BoxedValue> {
+        let mutable_pointed = reference.strip_prefix("&mut ");
+        let pointed = mutable_pointed.or_else(|| reference.strip_prefix('&'));
+
+        if let Some(var_name) = pointed {
+            return match self.find_variable(var_name) {
+                Some(variable) => Some(BoxedValue {
+                    interface: Ops::Pointer,
+                    value: Box::new(Pointer {
+                        cell: variable.value.clone(),
+                        mutable: mutable_pointed.is_some(),
+                    }),
+                }),
+                None => {
+                    self.raise_error(errors::CODES::VariableNotFound, vec![var_name.to_string()]);
+                    None
+                }
+            };
+        }
+
+        let var_name = reference.trim_start_matches('*');
+
+        match self.find_variable(var_name) {
+            Some(variable) => {
+                let derefs = reference.len() - var_name.len();
+                self.deref_cell(variable.value.clone(), derefs, reference, false)
+                    .map(|cell| cell.borrow().clone())
+            }
+            None => {
+                self.raise_error(errors::CODES::VariableNotFound, vec![var_name.to_string()]);
+                None
             }
         }
-        None
+    }
+
+    /*
+     * Dereference a cell the given times, reporting an error if any of the values is not a pointer
+     *
+     * Writing through a pointer needs every pointer followed to be created with `&mut`
+     */
+    pub(crate) fn deref_cell(
+        &self,
+        cell: HeapCell,
+        times: usize,
+        reference: &str,
+        writing: bool,
+    ) -> Option<HeapCell> {
+        let mut cell = cell;
+        for _ in 0..times {
+            let next = Pointer::of(&cell.borrow());
+            match next {
+                Some(pointer) if writing && !pointer.mutable => {
+                    self.raise_error(
+                        errors::CODES::ImmutablePointer,
+                        vec![
+                            reference.to_string(),
+                            "&mut ...".to_string(),
+                            "Point to it with &mut.".to_string(),
+                        ],
+                    );
+                    return None;
+                }
+                Some(pointer) => cell = pointer.cell,
+                None => {
+                    self.raise_error(errors::CODES::NotAPointer, vec![reference.to_string()]);
+                    return None;
+                }
+            }
+        }
+        Some(cell)
     }
 
     /*
@@ -343,32 +815,31 @@ impl Stack {
     }
 
     /*
-     * Modify a variable value
+     * Modify a variable value, `*a` modifies the value `a` points to instead
      */
     pub fn modify_var(&mut self, var_name: String, value: BoxedValue) {
-        let variable = self.get_mut_variable_by_name(var_name.as_str());
-
-        // If variable exists
-        if let Some(variable) = variable {
-            // Is pointer
-            if variable.val_type == Ops::Pointer {
-                let variable = variable.clone();
-
-                let pointer = downcast_val::<Pointer>(variable.value.as_self());
+        let name = var_name.trim_start_matches('*');
+        let derefs = var_name.len() - name.len();
 
-                let variable_origin = self.get_mut_variable_by_id(pointer.0);
+        let cell = if let Some(variable) = self.find_variable(name) {
+            variable.value.clone()
+        } else {
+            self.raise_error(errors::CODES::VariableNotFound, vec![name.to_string()]);
+            return;
+        };
 
-                if let Some(variable_origin) = variable_origin {
-                    variable_origin.value = value.value;
-                    variable_origin.val_type = value.interface;
-                } else {
-                    // Broken pointer
-                }
-            } else {
-                variable.value = value.value;
-            }
+        let cell = if let Some(cell) = self.deref_cell(cell, derefs, &var_name, true) {
+            cell
         } else {
-            errors::raise_error(errors::CODES::VariableNotFound, vec![var_name.clone()]);
+            return;
+        };
+
+        // A pointer can't end up pointing to itself
+        if Pointer::reaches(&value, &cell) {
+            self.raise_error(errors::CODES::CircularPointer, vec![var_name]);
+            return;
         }
+
+        *cell.borrow_mut() = value;
     }
 }
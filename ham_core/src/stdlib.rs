@@ -0,0 +1,91 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    primitive_values::{
+        boolean::Boolean,
+        error::ErrorVal,
+        list::List,
+        none::NoneVal,
+        number::Number,
+        string::StringVal,
+    },
+    runtime::downcast_val,
+    stack::Stack,
+    utils::Ops,
+};
+
+pub mod env;
+pub mod fs;
+pub mod json;
+pub mod math;
+pub mod path;
+pub mod process;
+pub mod time;
+
+/*
+ * Register the standard library, its modules are imported with the `std:` prefix, ex: `import math from "std:math"`
+ */
+pub fn register_modules(stack: &mut Stack) {
+    stack.register_module("std:env", env::module());
+    stack.register_module("std:fs", fs::module());
+    stack.register_module("std:json", json::module());
+    stack.register_module("std:math", math::module());
+    stack.register_module("std:path", path::module());
+    stack.register_module("std:process", process::module());
+    stack.register_module("std:time", time::module());
+}
+
+/*
+ * Get the number of a value, its type must be checked before
+ */
+pub(crate) fn to_number(value: &BoxedValue) -> usize {
+    downcast_val::<Number>(value.value.as_self()).0
+}
+
+/*
+ * Get the text of a value, its type must be checked before
+ */
+pub(crate) fn to_string(value: &BoxedValue) -> String {
+    downcast_val::<StringVal>(value.value.as_self()).0.clone()
+}
+
+pub(crate) fn number_value(value: usize) -> BoxedValue {
+    BoxedValue {
+        interface: Ops::Number,
+        value: Box::new(Number(value)),
+    }
+}
+
+pub(crate) fn string_value(value: String) -> BoxedValue {
+    BoxedValue {
+        interface: Ops::String,
+        value: Box::new(StringVal(value)),
+    }
+}
+
+pub(crate) fn boolean_value(value: bool) -> BoxedValue {
+    BoxedValue {
+        interface: Ops::Boolean,
+        value: Box::new(Boolean(value)),
+    }
+}
+
+pub(crate) fn list_value(items: Vec<BoxedValue>) -> BoxedValue {
+    BoxedValue {
+        interface: Ops::List,
+        value: Box::new(List(items)),
+    }
+}
+
+pub(crate) fn error_value(message: String) -> BoxedValue {
+    BoxedValue {
+        interface: Ops::Error,
+        value: Box::new(ErrorVal(message)),
+    }
+}
+
+pub(crate) fn none_value() -> BoxedValue {
+    BoxedValue {
+        interface: Ops::None,
+        value: Box::new(NoneVal),
+    }
+}
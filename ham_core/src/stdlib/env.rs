@@ -0,0 +1,174 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    modules::{
+        check_arguments,
+        project_root,
+        NativeModule,
+    },
+    permissions::{
+        Capability,
+        Permissions,
+    },
+    primitive_values::map::Map,
+    stdlib::{
+        error_value,
+        none_value,
+        string_value,
+        to_string,
+    },
+    utils::Ops,
+};
+use std::{
+    env::{
+        self,
+        VarError,
+    },
+    path::Path,
+};
+
+/*
+ * Load the variables of the `.env` file next to the project's `ham.yml`, if there is one,
+ * the variables which are already set keep their value
+ *
+ * Only the variables the `env` permission is granted for are loaded
+ */
+pub fn load_env_file(filedir: &Path, permissions: &Permissions) -> Result<(), dotenvy::Error> {
+    let root = project_root(filedir);
+    let path = root.join(".env");
+
+    if !root.join("ham.yml").is_file() || !path.is_file() {
+        return Ok(());
+    }
+
+    for variable in dotenvy::from_path_iter(path)? {
+        let (name, value) = variable?;
+
+        if permissions.is_allowed(Capability::Env, &name) && env::var_os(&name).is_none() {
+            env::set_var(name, value);
+        }
+    }
+
+    Ok(())
+}
+
+/*
+ * std:env module, reading or changing a variable needs the `env` permission for it
+ *
+ * Example:
+ *
+ * import env from "std:env"
+ *
+ * let home = env.get("HOME")
+ * env.set("MODE", "release")
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function("get", &["name"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let name = to_string(&args_vals[0]);
+
+            if !stack
+                .lock()
+                .unwrap()
+                .check_permission(Capability::Env, &name)
+            {
+                return None;
+            }
+
+            // Variables which are not set have no value
+            Some(match env::var(&name) {
+                Ok(value) => string_value(value),
+                Err(VarError::NotPresent) => none_value(),
+                Err(error) => error_value(format!("{}: {}", name, error)),
+            })
+        })
+        .function("set", &["name", "value"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String, Ops::String], stack) {
+                return None;
+            }
+            let name = to_string(&args_vals[0]);
+
+            if !stack
+                .lock()
+                .unwrap()
+                .check_permission(Capability::Env, &name)
+            {
+                return None;
+            }
+
+            // Names can't be empty or have `=`, and values can't have null characters
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return Some(error_value(format!("'{}' is not a valid name", name)));
+            }
+
+            let value = to_string(&args_vals[1]);
+
+            if value.contains('\0') {
+                return Some(error_value(format!(
+                    "{}: the value has a null character",
+                    name
+                )));
+            }
+
+            // Subprocesses get it too
+            env::set_var(name, value);
+            None
+        })
+        .function("vars", &[], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[], stack) {
+                return None;
+            }
+
+            // Only the variables the script can read, sorted by their name
+            let stack = stack.lock().unwrap();
+            let mut vars = env::vars()
+                .filter(|(name, _)| stack.permissions.is_allowed(Capability::Env, name))
+                .collect::<Vec<(String, String)>>();
+            vars.sort();
+
+            let mut map = Map::default();
+
+            for (name, value) in vars {
+                map.insert(name, string_value(value));
+            }
+
+            Some(BoxedValue {
+                interface: Ops::Map,
+                value: Box::new(map),
+            })
+        })
+        .function("cwd", &[], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[], stack) {
+                return None;
+            }
+
+            Some(match env::current_dir() {
+                Ok(cwd) => string_value(cwd.display().to_string()),
+                Err(error) => error_value(format!("cwd: {}", error)),
+            })
+        })
+        .function("home_dir", &[], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[], stack) {
+                return None;
+            }
+
+            // It's read from the environment
+            let name = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+
+            if !stack
+                .lock()
+                .unwrap()
+                .check_permission(Capability::Env, name)
+            {
+                return None;
+            }
+
+            Some(match env::var(name) {
+                Ok(home) => string_value(home),
+                Err(error) => error_value(format!("{}: {}", name, error)),
+            })
+        })
+}
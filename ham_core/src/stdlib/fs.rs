@@ -0,0 +1,230 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    modules::{
+        check_arguments,
+        NativeModule,
+    },
+    permissions::Capability,
+    primitive_values::map::Map,
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    stdlib::{
+        boolean_value,
+        error_value,
+        list_value,
+        number_value,
+        string_value,
+        to_string,
+    },
+    utils::Ops,
+};
+use std::{
+    fs::{
+        self,
+        OpenOptions,
+    },
+    io::{
+        self,
+        Write,
+    },
+    sync::Mutex,
+};
+
+/*
+ * Get the paths given as the arguments of a function, checking the script can use them
+ * once their links are followed, so a link can't lead out of the granted folders
+ *
+ * Returns None if it can't, the error is reported through the stack
+ */
+fn paths_arguments(
+    function: &FunctionDef,
+    args_vals: &[BoxedValue],
+    capabilities: &[Capability],
+    stack: &Mutex<Stack>,
+) -> Option<Vec<String>> {
+    let types = vec![Ops::String; function.arguments.len()];
+
+    if !check_arguments(function, args_vals, &types, stack) {
+        return None;
+    }
+
+    let paths = args_vals
+        .iter()
+        .take(capabilities.len())
+        .map(to_string)
+        .collect::<Vec<String>>();
+
+    let stack = stack.lock().unwrap();
+
+    paths
+        .iter()
+        .zip(capabilities)
+        .all(|(path, capability)| stack.check_permission(*capability, path))
+        .then_some(paths)
+}
+
+/*
+ * Error value for an operation that failed on a path
+ */
+fn io_error(path: &str, error: io::Error) -> BoxedValue {
+    error_value(format!("{}: {}", path, error))
+}
+
+/*
+ * Nothing if the operation went well, or its error
+ */
+fn done(path: &str, result: io::Result<()>) -> Option<BoxedValue> {
+    result.err().map(|error| io_error(path, error))
+}
+
+/*
+ * std:fs module, the functions that fail return an error value
+ *
+ * Example:
+ *
+ * import fs from "std:fs"
+ *
+ * fs.write("notes.txt", "Hello")
+ * let notes = fs.read_to_string("notes.txt")
+ * let texts = fs.glob("*.txt")
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function("read_to_string", &["path"], |function, args_vals, stack| {
+            let paths = paths_arguments(function, &args_vals, &[Capability::Read], stack)?;
+            let path = &paths[0];
+
+            Some(match fs::read_to_string(path) {
+                Ok(content) => string_value(content),
+                Err(error) => io_error(path, error),
+            })
+        })
+        .function(
+            "write",
+            &["path", "content"],
+            |function, args_vals, stack| {
+                let paths = paths_arguments(function, &args_vals, &[Capability::Write], stack)?;
+                let path = &paths[0];
+
+                done(path, fs::write(path, to_string(&args_vals[1])))
+            },
+        )
+        .function(
+            "append",
+            &["path", "content"],
+            |function, args_vals, stack| {
+                let paths = paths_arguments(function, &args_vals, &[Capability::Write], stack)?;
+                let path = &paths[0];
+
+                let result = OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(to_string(&args_vals[1]).as_bytes()));
+
+                done(path, result)
+            },
+        )
+        .function("exists", &["path"], |function, args_vals, stack| {
+            let paths = paths_arguments(function, &args_vals, &[Capability::Read], stack)?;
+
+            Some(boolean_value(fs::metadata(&paths[0]).is_ok()))
+        })
+        .function("remove", &["path"], |function, args_vals, stack| {
+            let paths = paths_arguments(function, &args_vals, &[Capability::Write], stack)?;
+            let path = &paths[0];
+
+            // Folders are removed with everything inside them
+            let result = match fs::metadata(path) {
+                Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+                _ => fs::remove_file(path),
+            };
+
+            done(path, result)
+        })
+        .function("rename", &["from", "to"], |function, args_vals, stack| {
+            let capabilities = [Capability::Write, Capability::Write];
+            let paths = paths_arguments(function, &args_vals, &capabilities, stack)?;
+
+            done(&paths[0], fs::rename(&paths[0], &paths[1]))
+        })
+        .function("copy", &["from", "to"], |function, args_vals, stack| {
+            let capabilities = [Capability::Read, Capability::Write];
+            let paths = paths_arguments(function, &args_vals, &capabilities, stack)?;
+
+            done(&paths[0], fs::copy(&paths[0], &paths[1]).map(|_| ()))
+        })
+        .function("read_dir", &["path"], |function, args_vals, stack| {
+            let paths = paths_arguments(function, &args_vals, &[Capability::Read], stack)?;
+            let path = &paths[0];
+
+            let entries = fs::read_dir(path).and_then(|entries| {
+                entries
+                    .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+                    .collect::<io::Result<Vec<String>>>()
+            });
+
+            // Sorted, so they don't depend on the file system
+            Some(match entries {
+                Ok(mut entries) => {
+                    entries.sort();
+                    list_value(entries.into_iter().map(string_value).collect())
+                }
+                Err(error) => io_error(path, error),
+            })
+        })
+        .function("create_dir_all", &["path"], |function, args_vals, stack| {
+            let paths = paths_arguments(function, &args_vals, &[Capability::Write], stack)?;
+            let path = &paths[0];
+
+            done(path, fs::create_dir_all(path))
+        })
+        .function("metadata", &["path"], |function, args_vals, stack| {
+            let paths = paths_arguments(function, &args_vals, &[Capability::Read], stack)?;
+            let path = &paths[0];
+
+            Some(match fs::metadata(path) {
+                Ok(metadata) => {
+                    let mut map = Map::default();
+                    map.insert("size".to_string(), number_value(metadata.len() as usize));
+                    map.insert("is_file".to_string(), boolean_value(metadata.is_file()));
+                    map.insert("is_dir".to_string(), boolean_value(metadata.is_dir()));
+                    map.insert(
+                        "readonly".to_string(),
+                        boolean_value(metadata.permissions().readonly()),
+                    );
+
+                    BoxedValue {
+                        interface: Ops::Map,
+                        value: Box::new(map),
+                    }
+                }
+                Err(error) => io_error(path, error),
+            })
+        })
+        .function("glob", &["pattern"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let pattern = to_string(&args_vals[0]);
+
+            let matches = match glob::glob(&pattern) {
+                Ok(matches) => matches,
+                Err(error) => return Some(error_value(format!("{}: {}", pattern, error))),
+            };
+
+            // Only the files the script can read are listed, the ones found through links too
+            let stack = stack.lock().unwrap();
+            let files = matches
+                .filter_map(Result::ok)
+                .map(|path| path.to_string_lossy().to_string())
+                .filter(|path| stack.permissions.is_allowed(Capability::Read, path))
+                .map(string_value)
+                .collect();
+
+            Some(list_value(files))
+        })
+}
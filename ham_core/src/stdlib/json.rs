@@ -0,0 +1,247 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    modules::{
+        argument,
+        check_arguments,
+        NativeModule,
+    },
+    primitive_values::{
+        boolean::Boolean,
+        list::List,
+        map::{
+            Map,
+            MapValueBase,
+        },
+        number::Number,
+        pointer::{
+            Pointer,
+            PointerBase,
+        },
+        string::StringVal,
+    },
+    runtime::downcast_val,
+    stdlib::{
+        boolean_value,
+        error_value,
+        list_value,
+        none_value,
+        number_value,
+        string_value,
+        to_string,
+    },
+    utils::Ops,
+};
+use serde::{
+    de::{
+        self,
+        Deserializer,
+        MapAccess,
+        SeqAccess,
+        Visitor,
+    },
+    ser::{
+        self,
+        SerializeMap,
+        SerializeSeq,
+    },
+    Deserialize,
+    Serialize,
+    Serializer,
+};
+use std::{
+    convert::TryFrom,
+    fmt,
+};
+
+/*
+ * Ham value read from JSON
+ *
+ * - Objects are maps, their keys keep the order they were written in
+ * - Arrays are lists
+ * - Numbers which are not whole and positive are strings with the number, ex: `"-1.5"`
+ * - Strings and booleans are the same
+ * - `null` is `none`
+ */
+struct FromJson(BoxedValue);
+
+impl<'de> Deserialize<'de> for FromJson {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = FromJson;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<FromJson, E> {
+        Ok(FromJson(boolean_value(value)))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<FromJson, E> {
+        Ok(FromJson(match usize::try_from(value) {
+            Ok(value) => number_value(value),
+            Err(_) => string_value(value.to_string()),
+        }))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<FromJson, E> {
+        Ok(FromJson(match usize::try_from(value) {
+            Ok(value) => number_value(value),
+            Err(_) => string_value(value.to_string()),
+        }))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<FromJson, E> {
+        // Ex: `2.0` is read as `2`
+        Ok(FromJson(
+            if value.fract() == 0.0 && value >= 0.0 && value <= usize::MAX as f64 {
+                number_value(value as usize)
+            } else {
+                string_value(value.to_string())
+            },
+        ))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<FromJson, E> {
+        Ok(FromJson(string_value(value.to_string())))
+    }
+
+    fn visit_string<E: de::Error>(self, value: String) -> Result<FromJson, E> {
+        Ok(FromJson(string_value(value)))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<FromJson, E> {
+        Ok(FromJson(none_value()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<FromJson, A::Error> {
+        let mut items = Vec::new();
+
+        while let Some(FromJson(item)) = seq.next_element()? {
+            items.push(item);
+        }
+
+        Ok(FromJson(list_value(items)))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut entries: A) -> Result<FromJson, A::Error> {
+        let mut map = Map::default();
+
+        while let Some((key, FromJson(value))) = entries.next_entry::<String, FromJson>()? {
+            map.insert(key, value);
+        }
+
+        Ok(FromJson(BoxedValue {
+            interface: Ops::Map,
+            value: Box::new(map),
+        }))
+    }
+}
+
+/*
+ * Ham value written as JSON, the opposite of `FromJson`
+ *
+ * Values of other types can't be written, ex: tasks
+ */
+struct ToJson<'a>(&'a BoxedValue);
+
+impl Serialize for ToJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = self.0.value.as_self();
+
+        match self.0.interface {
+            Ops::Boolean => serializer.serialize_bool(downcast_val::<Boolean>(value).0),
+            Ops::Number => serializer.serialize_u64(downcast_val::<Number>(value).0 as u64),
+            Ops::String => serializer.serialize_str(&downcast_val::<StringVal>(value).0),
+            Ops::None => serializer.serialize_unit(),
+            Ops::Pointer => {
+                ToJson(&downcast_val::<Pointer>(value).get_state()).serialize(serializer)
+            }
+            Ops::List => {
+                let list = &downcast_val::<List>(value).0;
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for item in list {
+                    seq.serialize_element(&ToJson(item))?;
+                }
+                seq.end()
+            }
+            Ops::Map => {
+                let map = downcast_val::<Map>(value).get_state();
+                let mut entries = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    entries.serialize_entry(key, &ToJson(value))?;
+                }
+                entries.end()
+            }
+            interface => Err(ser::Error::custom(format!(
+                "values of type '{}' can't be written as JSON",
+                interface.type_name()
+            ))),
+        }
+    }
+}
+
+/*
+ * std:json module
+ *
+ * Example:
+ *
+ * import json from "std:json"
+ *
+ * let config = json.parse(text)
+ * let port = config.get("port")
+ * let pretty = json.stringify(config, true)
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function("parse", &["text"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+
+            // The errors say where the text is wrong, ex: `expected value at line 1 column 5`
+            Some(
+                match serde_json::from_str::<FromJson>(&to_string(&args_vals[0])) {
+                    Ok(FromJson(value)) => value,
+                    Err(error) => error_value(format!("invalid JSON: {}", error)),
+                },
+            )
+        })
+        .function(
+            "stringify",
+            &["value", "pretty"],
+            |function, args_vals, stack| {
+                // The value can be of any type
+                let types = match args_vals.first() {
+                    Some(value) => vec![value.interface, Ops::Boolean],
+                    None => vec![],
+                };
+
+                if !check_arguments(function, &args_vals, &types, stack) {
+                    return None;
+                }
+
+                let value = ToJson(&args_vals[0]);
+                let pretty = argument(function, &args_vals, 1);
+
+                let text = if downcast_val::<Boolean>(pretty.value.as_self()).0 {
+                    serde_json::to_string_pretty(&value)
+                } else {
+                    serde_json::to_string(&value)
+                };
+
+                Some(match text {
+                    Ok(text) => string_value(text),
+                    Err(error) => error_value(error.to_string()),
+                })
+            },
+        )
+        .default_value("pretty", boolean_value(false))
+}
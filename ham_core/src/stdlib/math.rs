@@ -0,0 +1,75 @@
+use crate::{
+    modules::{
+        check_arguments,
+        NativeModule,
+    },
+    stdlib::{
+        number_value,
+        to_number,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::convert::TryFrom;
+
+/*
+ * std:math module, for whole numbers
+ *
+ * Example:
+ *
+ * import math from "std:math"
+ *
+ * let side = math.sqrt(16)
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function("min", &["a", "b"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::Number, Ops::Number], stack) {
+                return None;
+            }
+            let min = to_number(&args_vals[0]).min(to_number(&args_vals[1]));
+            Some(number_value(min))
+        })
+        .function("max", &["a", "b"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::Number, Ops::Number], stack) {
+                return None;
+            }
+            let max = to_number(&args_vals[0]).max(to_number(&args_vals[1]));
+            Some(number_value(max))
+        })
+        .function(
+            "pow",
+            &["base", "exponent"],
+            |function, args_vals, stack| {
+                if !check_arguments(function, &args_vals, &[Ops::Number, Ops::Number], stack) {
+                    return None;
+                }
+                let base = to_number(&args_vals[0]);
+                let exponent = to_number(&args_vals[1]);
+
+                let power = u32::try_from(exponent)
+                    .ok()
+                    .and_then(|exponent| base.checked_pow(exponent));
+
+                if power.is_none() {
+                    stack.lock().unwrap().raise_error(
+                        errors::CODES::NumberOverflow,
+                        vec!["pow".to_string(), base.to_string(), exponent.to_string()],
+                    );
+                }
+
+                power.map(number_value)
+            },
+        )
+        .function("sqrt", &["value"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::Number], stack) {
+                return None;
+            }
+            // Rounded down, ex: `sqrt(10)` is 3
+            Some(number_value(to_number(&args_vals[0]).isqrt()))
+        })
+        .value("max_number", number_value(usize::MAX))
+}
@@ -0,0 +1,66 @@
+use crate::{
+    modules::{
+        check_arguments,
+        NativeModule,
+    },
+    stdlib::{
+        boolean_value,
+        string_value,
+        to_string,
+    },
+    utils::Ops,
+};
+use std::path::Path;
+
+/*
+ * std:path module, paths are only read as text, the files don't need to exist
+ *
+ * Example:
+ *
+ * import path from "std:path"
+ *
+ * let file = path.join("src", "main.ham")
+ * let name = path.basename(file)
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function("join", &["base", "path"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String, Ops::String], stack) {
+                return None;
+            }
+            let path = Path::new(&to_string(&args_vals[0])).join(to_string(&args_vals[1]));
+            Some(string_value(path.to_string_lossy().to_string()))
+        })
+        .function("dirname", &["path"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let path = to_string(&args_vals[0]);
+            let parent = Path::new(&path).parent().unwrap_or_else(|| Path::new(""));
+            Some(string_value(parent.to_string_lossy().to_string()))
+        })
+        .function("basename", &["path"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let path = to_string(&args_vals[0]);
+            let name = Path::new(&path).file_name().unwrap_or_default();
+            Some(string_value(name.to_string_lossy().to_string()))
+        })
+        .function("extension", &["path"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let path = to_string(&args_vals[0]);
+            let extension = Path::new(&path).extension().unwrap_or_default();
+            Some(string_value(extension.to_string_lossy().to_string()))
+        })
+        .function("is_absolute", &["path"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let path = to_string(&args_vals[0]);
+            Some(boolean_value(Path::new(&path).is_absolute()))
+        })
+}
@@ -0,0 +1,182 @@
+use crate::{
+    ast_types::boxed_val::BoxedValue,
+    limits::Abort,
+    modules::{
+        check_arguments,
+        NativeModule,
+    },
+    permissions::Capability,
+    primitive_values::{
+        list::List,
+        process::{
+            process_output,
+            Process,
+        },
+    },
+    runtime::{
+        downcast_val,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    stdlib::{
+        error_value,
+        list_value,
+        string_value,
+        to_number,
+        to_string,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::{
+    convert::TryFrom,
+    process::{
+        Command,
+        Stdio,
+    },
+    sync::Mutex,
+};
+
+/*
+ * Get the command and its arguments, lists are taken as several arguments, ex: `run("git", "status")`
+ * or `run("git", arguments)`
+ *
+ * Returns None if the command can't be run, the error is reported through the stack
+ */
+fn get_command(
+    function: &FunctionDef,
+    args_vals: &[BoxedValue],
+    stack: &Mutex<Stack>,
+) -> Option<Command> {
+    if !check_arguments(function, args_vals, &[Ops::String], stack) {
+        return None;
+    }
+
+    let program = to_string(&args_vals[0]);
+
+    let mut arguments = Vec::new();
+
+    for value in &args_vals[1..] {
+        let items = match value.interface {
+            Ops::List => downcast_val::<List>(value.value.as_self()).0.clone(),
+            _ => vec![value.clone()],
+        };
+
+        for item in items {
+            match value_to_string(item) {
+                Ok(argument) => arguments.push(argument),
+                Err(interface) => {
+                    stack.lock().unwrap().raise_error(
+                        errors::CODES::MismatchedTypes,
+                        vec![
+                            format!("the arguments of '{}'", program),
+                            Ops::String.type_name().to_string(),
+                            interface.type_name().to_string(),
+                        ],
+                    );
+                    return None;
+                }
+            }
+        }
+    }
+
+    if !stack
+        .lock()
+        .unwrap()
+        .check_permission(Capability::Run, &program)
+    {
+        return None;
+    }
+
+    let mut command = Command::new(program);
+    command.args(arguments);
+    Some(command)
+}
+
+/*
+ * std:process module
+ *
+ * Example:
+ *
+ * import process from "std:process"
+ *
+ * let output = process.run("git", "status", "--short")
+ * let code = output.get("code")
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function(
+            "run",
+            &["command", "...arguments"],
+            |function, args_vals, stack| {
+                let mut command = get_command(function, &args_vals, stack)?;
+                let program = to_string(&args_vals[0]);
+
+                // It runs until it finishes, with nothing as its input
+                let output = command.stdin(Stdio::null()).output();
+
+                Some(match output {
+                    Ok(output) => process_output(
+                        &program,
+                        output.status,
+                        String::from_utf8_lossy(&output.stdout).to_string(),
+                        String::from_utf8_lossy(&output.stderr).to_string(),
+                    ),
+                    Err(error) => error_value(format!("{}: {}", program, error)),
+                })
+            },
+        )
+        .function(
+            "start",
+            &["command", "...arguments"],
+            |function, args_vals, stack| {
+                let mut command = get_command(function, &args_vals, stack)?;
+                let program = to_string(&args_vals[0]);
+
+                let child = command
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn();
+
+                Some(match child {
+                    Ok(child) => BoxedValue {
+                        interface: Ops::Process,
+                        value: Box::new(Process::new(program, child)),
+                    },
+                    Err(error) => error_value(format!("{}: {}", program, error)),
+                })
+            },
+        )
+        .function("exit", &["code"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::Number], stack) {
+                return None;
+            }
+
+            let code = to_number(&args_vals[0]);
+            let mut stack = stack.lock().unwrap();
+
+            match i32::try_from(code) {
+                Ok(code) => stack.abort(Abort::Exit(code)),
+                Err(_) => stack.raise_error(
+                    errors::CODES::InvalidExitCode,
+                    vec![code.to_string(), i32::MAX.to_string()],
+                ),
+            }
+            None
+        })
+        .function("args", &[], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[], stack) {
+                return None;
+            }
+
+            let args = stack.lock().unwrap().args.clone();
+            Some(list_value(args.into_iter().map(string_value).collect()))
+        })
+}
@@ -0,0 +1,49 @@
+use crate::{
+    event_loop::{
+        block_on,
+        Wait,
+    },
+    modules::{
+        check_arguments,
+        NativeModule,
+    },
+    stdlib::{
+        number_value,
+        to_number,
+    },
+    utils::Ops,
+};
+use std::time::Duration;
+
+/*
+ * std:time module, it follows the clock of the stack, so it's virtual in the deterministic mode
+ *
+ * Example:
+ *
+ * import time from "std:time"
+ *
+ * let start = time.now()
+ * time.sleep(100)
+ *
+ */
+pub fn module() -> NativeModule {
+    NativeModule::default()
+        .function("now", &[], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[], stack) {
+                return None;
+            }
+            // Milliseconds since the program started
+            let now = stack.lock().unwrap().clock.now().as_millis();
+            Some(number_value(now as usize))
+        })
+        .function("sleep", &["milliseconds"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::Number], stack) {
+                return None;
+            }
+            let time = Duration::from_millis(to_number(&args_vals[0]) as u64);
+
+            // Other tasks run in the meantime
+            let deadline = stack.lock().unwrap().clock.now() + time;
+            block_on(Wait::Timer(deadline), stack)
+        })
+}
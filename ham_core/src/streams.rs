@@ -0,0 +1,131 @@
+use std::{
+    io::{
+        self,
+        BufRead,
+        BufReader,
+        Write,
+    },
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+/*
+ * Shared handle to something the interpreter can write into
+ */
+pub type OutputStream = Arc<Mutex<dyn Write + Send>>;
+
+/*
+ * Shared handle to something the interpreter can read lines from
+ */
+pub type InputStream = Arc<Mutex<dyn BufRead + Send>>;
+
+/*
+ * Input and output handles used by the interpreter
+ *
+ * The host decides where the script's output, error output and input go,
+ * by default these are the process's stdout, stderr and stdin.
+ */
+#[derive(Clone)]
+pub struct Streams {
+    pub stdout: OutputStream,
+    pub stderr: OutputStream,
+    pub stdin: InputStream,
+}
+
+impl Default for Streams {
+    fn default() -> Self {
+        Self {
+            stdout: Arc::new(Mutex::new(io::stdout())),
+            stderr: Arc::new(Mutex::new(io::stderr())),
+            stdin: Arc::new(Mutex::new(BufReader::new(io::stdin()))),
+        }
+    }
+}
+
+impl Streams {
+    pub fn new(stdout: OutputStream, stderr: OutputStream, stdin: InputStream) -> Self {
+        Self {
+            stdout,
+            stderr,
+            stdin,
+        }
+    }
+
+    /*
+     * Write some text into the output stream
+     */
+    pub fn write_out(&self, text: &str) {
+        write_to(&self.stdout, text);
+    }
+
+    /*
+     * Write some text into the error stream
+     */
+    pub fn write_err(&self, text: &str) {
+        write_to(&self.stderr, text);
+    }
+
+    /*
+     * Read a line from the input stream, without the line break
+     *
+     * Returns None once the input is over
+     */
+    pub fn read_line(&self) -> Option<String> {
+        let mut line = String::new();
+
+        match self.stdin.lock().unwrap().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end_matches(&['\n', '\r'][..]).to_string()),
+        }
+    }
+}
+
+fn write_to(stream: &OutputStream, text: &str) {
+    let mut stream = stream.lock().unwrap();
+
+    // There is nowhere to report a failing output stream, so just ignore it
+    stream.write_all(text.as_bytes()).ok();
+    stream.flush().ok();
+}
+
+/*
+ * In-memory output stream, useful to capture what a script writes
+ *
+ * Example:
+ *
+ * let output = MemoryStream::default();
+ * let input = Arc::new(Mutex::new(Cursor::new("some input")));
+ * let streams = Streams::new(output.handle(), output.handle(), input);
+ *
+ */
+#[derive(Clone, Default)]
+pub struct MemoryStream(Arc<Mutex<Vec<u8>>>);
+
+impl MemoryStream {
+    /*
+     * Everything written into the stream so far
+     */
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).to_string()
+    }
+
+    /*
+     * Wrap the stream into a shared handle
+     */
+    pub fn handle(&self) -> OutputStream {
+        Arc::new(Mutex::new(self.clone()))
+    }
+}
+
+impl Write for MemoryStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
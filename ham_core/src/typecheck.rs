@@ -0,0 +1,871 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+        expression::Expression,
+        fn_call::FnCall,
+        fn_def::FnDefinition,
+        for_block::For,
+        if_ast::IfConditional,
+        module::{
+            Import,
+            Module,
+        },
+        named_argument::NamedArgument,
+        reference::Reference,
+        result::ResultExpression,
+        return_ast::ReturnStatement,
+        spawn::Spawn,
+        try_ast::TryStatement,
+        var_assign::VarAssignment,
+        var_def::VarDefinition,
+        while_block::While,
+        yield_ast::YieldStatement,
+    },
+    runtime::{
+        downcast_val,
+        get_method_in_type,
+        value_to_string,
+    },
+    stack::{
+        FunctionDef,
+        Stack,
+    },
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::{
+    collections::HashMap,
+    mem,
+    sync::Mutex,
+};
+
+/*
+ * What is known about a function before running
+ */
+#[derive(Clone, PartialEq)]
+struct Signature {
+    // Name and type of every argument, None if it takes any number of them
+    arguments: Option<Vec<(String, Option<Ops>)>>,
+    // Arguments which have a default value
+    optional: Vec<String>,
+    // Extra arguments are collected into a list
+    variadic: bool,
+    returns: Option<Ops>,
+}
+
+impl Signature {
+    fn of(function: &FnDefinition) -> Self {
+        Self {
+            arguments: Some(
+                function
+                    .arguments
+                    .iter()
+                    .cloned()
+                    .zip(function.argument_types.iter().cloned())
+                    .collect(),
+            ),
+            optional: function
+                .defaults
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+            variadic: function.rest_argument.is_some(),
+            returns: if function.generator {
+                Some(Ops::Generator)
+            } else {
+                function.return_type
+            },
+        }
+    }
+
+    /*
+     * Signature of the native functions
+     */
+    fn of_native(fn_name: &str) -> Option<Self> {
+        let (arguments, returns) = match fn_name {
+            "print" | "println" => (None, None),
+            "format" => (None, Some(Ops::String)),
+            "wait" => (
+                Some(vec![("milliseconds".to_string(), Some(Ops::Number))]),
+                None,
+            ),
+            "channel" => (Some(vec![]), Some(Ops::Channel)),
+            "join" => (None, Some(Ops::List)),
+            "clear" => (Some(vec![]), None),
+            _ => return None,
+        };
+
+        Some(Self {
+            arguments,
+            optional: Vec::new(),
+            variadic: false,
+            returns,
+        })
+    }
+
+    /*
+     * Signature of the functions of native modules, the types of their arguments are checked while running
+     */
+    fn of_native_module(function: &FunctionDef) -> Self {
+        Self {
+            arguments: Some(
+                function
+                    .arguments
+                    .iter()
+                    .map(|argument| (argument.clone(), None))
+                    .collect(),
+            ),
+            optional: function
+                .defaults
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+            variadic: function.rest_argument.is_some(),
+            returns: None,
+        }
+    }
+
+    /*
+     * Signature of the methods of the primitive types, the variable they are called from is not an argument
+     */
+    fn of_method(val_type: Ops, fn_name: &str) -> Option<Self> {
+        get_method_in_type(val_type, fn_name)?;
+
+        let (arguments, returns) = match (val_type, fn_name) {
+            (Ops::Number, "sum") => (
+                vec![("value".to_string(), Some(Ops::Number))],
+                Some(Ops::Number),
+            ),
+            (Ops::Number, "mut_sum") => (vec![("value".to_string(), Some(Ops::Number))], None),
+            (Ops::List, "len") => (vec![], Some(Ops::Number)),
+            (Ops::List, "get") => (vec![("index".to_string(), Some(Ops::Number))], None),
+            (Ops::Map, "len") => (vec![], Some(Ops::Number)),
+            (Ops::Map, "get") => (vec![("key".to_string(), Some(Ops::String))], None),
+            (Ops::Map, "has") => (
+                vec![("key".to_string(), Some(Ops::String))],
+                Some(Ops::Boolean),
+            ),
+            (Ops::Map, "keys") => (vec![], Some(Ops::List)),
+            (Ops::Error, "message") => (vec![], Some(Ops::String)),
+            (Ops::Process, "write") => (vec![("text".to_string(), Some(Ops::String))], None),
+            (Ops::Process, "close") => (vec![], None),
+            (Ops::Process, "read_line") => (vec![], None),
+            (Ops::Process, "wait") => (vec![], None),
+            (Ops::Generator, "next") => (vec![], None),
+            (Ops::Generator, "done") => (vec![], Some(Ops::Boolean)),
+            (Ops::Task, "await") => (vec![], None),
+            (Ops::Task, "done") => (vec![], Some(Ops::Boolean)),
+            (Ops::Channel, "send") => (vec![("message".to_string(), None)], None),
+            (Ops::Channel, "recv") => (vec![], None),
+            (_, "is_none") | (_, "is_error") => (vec![], Some(Ops::Boolean)),
+            (_, "unwrap_or") => (vec![("default".to_string(), None)], None),
+            _ => return None,
+        };
+
+        Some(Self {
+            arguments: Some(arguments),
+            optional: Vec::new(),
+            variadic: false,
+            returns,
+        })
+    }
+}
+
+/*
+ * A variable known while checking the code
+ */
+#[derive(Clone)]
+struct Binding {
+    name: String,
+    // Its type, if it's known
+    value_type: Option<Ops>,
+    // Values assigned to it must have its type
+    annotated: bool,
+}
+
+/*
+ * Function whose body is being checked
+ */
+struct CurrentFunction {
+    name: String,
+    returns: Option<Ops>,
+}
+
+struct Checker<'a> {
+    stack: &'a Mutex<Stack>,
+    // Bindings visible at the current position, the last scope is the innermost one
+    scopes: Vec<Vec<Binding>>,
+    // Functions by their name, None if they are defined more than once with different signatures
+    functions: HashMap<String, Option<Signature>>,
+    // Functions of every imported module by the module's name
+    modules: HashMap<String, HashMap<String, Signature>>,
+    function: Option<CurrentFunction>,
+    valid: bool,
+}
+
+/*
+ * Check the types of the code before running it, only what is annotated or can be known is checked:
+ *
+ * - Functions must be called with the arguments they take, by position or by name, and of the annotated types
+ * - Methods must exist for the type of the variable they are called from
+ * - Functions must return values of their annotated type, and so must be the values assigned to annotated variables
+ * - Functions with a return type other than `none` can't end without returning, a `return` inside a block
+ *   (ex: `if`) is not enough because the block might not run
+ *
+ * Errors are reported through the stack, returns false if any was found
+ */
+pub fn check_types(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> bool {
+    let ast = ast.lock().unwrap();
+
+    let mut checker = Checker {
+        stack,
+        scopes: vec![Vec::new()],
+        functions: HashMap::new(),
+        modules: HashMap::new(),
+        function: None,
+        valid: true,
+    };
+
+    checker.collect_definitions(&ast.body);
+    checker.check_body(&ast.body);
+
+    checker.valid
+}
+
+impl Checker<'_> {
+    fn raise_error(&mut self, kind: errors::CODES, args: Vec<String>, line: usize) {
+        self.stack
+            .lock()
+            .unwrap()
+            .raise_error_in_line(kind, args, line);
+        self.valid = false;
+    }
+
+    /*
+     * Keep the signature of a function, the ones defined more than once with
+     * different signatures are not checked
+     */
+    fn define_function(&mut self, name: &str, signature: Signature) {
+        self.functions
+            .entry(name.to_string())
+            .and_modify(|known| {
+                if known.as_ref() != Some(&signature) {
+                    *known = None;
+                }
+            })
+            .or_insert_with(|| Some(signature.clone()));
+    }
+
+    /*
+     * Signatures of the functions exported by a module
+     */
+    fn module_signatures(&self, module: &Module) -> HashMap<String, Signature> {
+        if module.native {
+            let stack = self.stack.lock().unwrap();
+            let native = stack.modules.natives.get(&module.path);
+
+            native
+                .iter()
+                .flat_map(|native| &native.functions)
+                .map(|function| (function.name.clone(), Signature::of_native_module(function)))
+                .collect()
+        } else {
+            module
+                .functions
+                .iter()
+                .map(|function| (function.def_name.clone(), Signature::of(function)))
+                .collect()
+        }
+    }
+
+    /*
+     * Find the signature of every function and module in the tree
+     */
+    fn collect_definitions(&mut self, body: &[Box<dyn AstBase>]) {
+        for operation in body {
+            match operation.get_type() {
+                Ops::FnDef => {
+                    let function = downcast_val::<FnDefinition>(operation.as_self());
+                    self.define_function(&function.def_name, Signature::of(function));
+                    self.collect_definitions(&function.body);
+                }
+                Ops::Module => {
+                    let module = downcast_val::<Module>(operation.as_self());
+                    let functions = self.module_signatures(module);
+
+                    match &module.import {
+                        Import::Namespace(name) => {
+                            self.modules.insert(name.clone(), functions);
+                        }
+                        Import::Items(items) => {
+                            for (item, alias) in items {
+                                if let Some(signature) = functions.get(item) {
+                                    self.define_function(alias, signature.clone());
+                                }
+                            }
+                        }
+                    }
+                }
+                Ops::IfConditional => {
+                    let if_statement = downcast_val::<IfConditional>(operation.as_self());
+                    self.collect_definitions(&if_statement.body);
+                }
+                Ops::WhileDef => {
+                    let while_block = downcast_val::<While>(operation.as_self());
+                    self.collect_definitions(&while_block.body);
+                }
+                Ops::ForDef => {
+                    let for_block = downcast_val::<For>(operation.as_self());
+                    self.collect_definitions(&for_block.body);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn find_binding(&self, name: &str) -> Option<&Binding> {
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|binding| binding.name == name)
+    }
+
+    fn check_body(&mut self, body: &[Box<dyn AstBase>]) {
+        for operation in body {
+            self.check_operation(operation.as_ref());
+        }
+    }
+
+    fn check_scope(&mut self, body: &[Box<dyn AstBase>]) {
+        self.scopes.push(Vec::new());
+        self.check_body(body);
+        self.scopes.pop();
+    }
+
+    fn check_operation(&mut self, operation: &dyn AstBase) {
+        match operation.get_type() {
+            Ops::VarDef => {
+                let variable = downcast_val::<VarDefinition>(operation.as_self());
+                let value_type = self.check_value(&variable.assignment);
+
+                if let Some(var_type) = variable.var_type {
+                    self.check_type(
+                        format!("variable '{}'", variable.def_name),
+                        var_type,
+                        value_type,
+                        variable.line,
+                    );
+                }
+
+                let binding = Binding {
+                    name: variable.def_name.clone(),
+                    // The type of mutable variables without annotations can change
+                    value_type: variable.var_type.or(if variable.mutable {
+                        None
+                    } else {
+                        value_type
+                    }),
+                    annotated: variable.var_type.is_some(),
+                };
+                self.scopes.last_mut().unwrap().push(binding);
+            }
+
+            Ops::VarAssign => {
+                let variable = downcast_val::<VarAssignment>(operation.as_self());
+                let value_type = self.check_value(&variable.assignment);
+
+                let annotated_type = self
+                    .find_binding(&variable.var_name)
+                    .filter(|binding| binding.annotated)
+                    .and_then(|binding| binding.value_type);
+
+                if let Some(var_type) = annotated_type {
+                    self.check_type(
+                        format!("variable '{}'", variable.var_name),
+                        var_type,
+                        value_type,
+                        variable.line,
+                    );
+                }
+            }
+
+            Ops::FnCall => {
+                let fn_call = downcast_val::<FnCall>(operation.as_self());
+                self.check_call(fn_call);
+            }
+
+            Ops::Spawn => {
+                let spawn = downcast_val::<Spawn>(operation.as_self());
+                self.check_spawn(spawn);
+            }
+
+            Ops::Return => {
+                let statement = downcast_val::<ReturnStatement>(operation.as_self());
+                let value_type = self.check_value(&statement.value);
+
+                let returns = self.function.as_ref().and_then(|function| {
+                    function
+                        .returns
+                        .map(|returns| (function.name.clone(), returns))
+                });
+
+                if let Some((fn_name, returns)) = returns {
+                    self.check_type(
+                        format!("the value returned by '{}'", fn_name),
+                        returns,
+                        value_type,
+                        statement.line,
+                    );
+                }
+            }
+
+            Ops::IfConditional => {
+                let if_statement = downcast_val::<IfConditional>(operation.as_self());
+                self.check_conditions(&if_statement.conditions);
+                self.check_scope(&if_statement.body);
+            }
+
+            Ops::WhileDef => {
+                let while_block = downcast_val::<While>(operation.as_self());
+                self.check_conditions(&while_block.conditions);
+                self.check_scope(&while_block.body);
+            }
+
+            Ops::ForDef => {
+                let for_block = downcast_val::<For>(operation.as_self());
+                let iterable_type = self.check_value(&for_block.iterable);
+
+                if let Some(iterable_type) = iterable_type
+                    .filter(|found| ![Ops::List, Ops::Map, Ops::Generator].contains(found))
+                {
+                    self.raise_error(
+                        errors::CODES::NotIterable,
+                        vec![iterable_type.type_name().to_string()],
+                        for_block.line,
+                    );
+                }
+
+                // The type of the items is not known
+                self.scopes.push(vec![Binding {
+                    name: for_block.var_name.clone(),
+                    value_type: None,
+                    annotated: false,
+                }]);
+                self.check_body(&for_block.body);
+                self.scopes.pop();
+            }
+
+            Ops::Yield => {
+                let statement = downcast_val::<YieldStatement>(operation.as_self());
+                self.check_value(&statement.value);
+            }
+
+            // The value returned by `?` is not checked against the function's type, it's `none` or an error
+            Ops::Try => {
+                let statement = downcast_val::<TryStatement>(operation.as_self());
+                self.check_value(&statement.value);
+            }
+
+            Ops::FnDef => {
+                let function = downcast_val::<FnDefinition>(operation.as_self());
+                self.check_function(function);
+            }
+
+            Ops::Module => {
+                let module = downcast_val::<Module>(operation.as_self());
+
+                // Modules don't see the code importing them
+                let outer_scopes = mem::replace(&mut self.scopes, vec![Vec::new()]);
+                self.check_body(&module.body);
+                self.scopes = outer_scopes;
+            }
+
+            _ => {}
+        }
+    }
+
+    /*
+     * Functions only see the top level bindings and their own arguments
+     */
+    fn check_function(&mut self, function: &FnDefinition) {
+        let mut arguments: Vec<Binding> = function
+            .arguments
+            .iter()
+            .zip(&function.argument_types)
+            .map(|(name, argument_type)| Binding {
+                name: name.clone(),
+                value_type: *argument_type,
+                annotated: argument_type.is_some(),
+            })
+            .collect();
+
+        if let Some(rest_argument) = &function.rest_argument {
+            let mutable = function.mutable_arguments.contains(rest_argument);
+            arguments.push(Binding {
+                name: rest_argument.clone(),
+                value_type: if mutable { None } else { Some(Ops::List) },
+                annotated: false,
+            });
+        }
+
+        let top_level = self.scopes[0].clone();
+        let outer_scopes = mem::replace(&mut self.scopes, vec![top_level, arguments]);
+        // Generators return a generator, the values their code returns are ignored
+        let returns = if function.generator {
+            if let Some(return_type) = function.return_type {
+                self.check_type(
+                    format!("the value returned by '{}'", function.def_name),
+                    return_type,
+                    Some(Ops::Generator),
+                    function.line,
+                );
+            }
+            None
+        } else {
+            function.return_type
+        };
+
+        let outer_function = self.function.replace(CurrentFunction {
+            name: function.def_name.clone(),
+            returns,
+        });
+
+        self.check_body(&function.body);
+
+        // Ending without a `return` gives none
+        if let Some(returns) = returns.filter(|returns| *returns != Ops::None) {
+            if !always_returns(&function.body) {
+                self.raise_error(
+                    errors::CODES::MissingReturn,
+                    vec![function.def_name.clone(), returns.type_name().to_string()],
+                    function.line,
+                );
+            }
+        }
+
+        self.scopes = outer_scopes;
+        self.function = outer_function;
+    }
+
+    fn check_conditions(&mut self, conditions: &[ResultExpression]) {
+        for condition in conditions {
+            self.check_value(&condition.left);
+            self.check_value(&condition.right);
+        }
+    }
+
+    /*
+     * Report an error if a known type is not the expected one
+     */
+    fn check_type(&mut self, what: String, expected: Ops, found: Option<Ops>, line: usize) {
+        if let Some(found) = found.filter(|found| *found != expected) {
+            self.raise_error(
+                errors::CODES::MismatchedTypes,
+                vec![
+                    what,
+                    expected.type_name().to_string(),
+                    found.type_name().to_string(),
+                ],
+                line,
+            );
+        }
+    }
+
+    /*
+     * Check a value, returns its type if it's known
+     */
+    fn check_value(&mut self, value: &BoxedValue) -> Option<Ops> {
+        match value.interface {
+            Ops::Number | Ops::String | Ops::Boolean | Ops::Pointer | Ops::None => {
+                Some(value.interface)
+            }
+            Ops::Reference => {
+                let reference = &downcast_val::<Reference>(value.value.as_self()).0;
+
+                if reference.starts_with('&') {
+                    Some(Ops::Pointer)
+                } else {
+                    self.find_binding(reference)
+                        .and_then(|binding| binding.value_type)
+                }
+            }
+            Ops::FnCall => {
+                let fn_call = downcast_val::<FnCall>(value.value.as_self());
+                self.check_call(fn_call)
+            }
+            Ops::NamedArgument => {
+                let argument = downcast_val::<NamedArgument>(value.value.as_self());
+                self.check_value(&argument.value)
+            }
+            Ops::Spawn => {
+                let spawn = downcast_val::<Spawn>(value.value.as_self());
+                self.check_spawn(spawn);
+                Some(Ops::Task)
+            }
+            Ops::Expression => {
+                // Groups return the value of their last operation
+                let expression = downcast_val::<Expression>(value.value.as_self());
+                let mut value_type = None;
+
+                self.scopes.push(Vec::new());
+                for operation in &expression.body {
+                    if operation.get_type() == Ops::Return {
+                        let statement = downcast_val::<ReturnStatement>(operation.as_self());
+                        value_type = self.check_value(&statement.value);
+                    } else {
+                        self.check_operation(operation.as_ref());
+                    }
+                }
+                self.scopes.pop();
+
+                value_type
+            }
+            _ => None,
+        }
+    }
+
+    /*
+     * Check the call a task runs, or the function it's declared with
+     */
+    fn check_spawn(&mut self, spawn: &Spawn) {
+        if let Some(function) = &spawn.function {
+            self.check_function(function);
+        } else {
+            self.check_call(&spawn.call);
+        }
+    }
+
+    /*
+     * Check a function call, returns the type of its result if it's known
+     */
+    fn check_call(&mut self, fn_call: &FnCall) -> Option<Ops> {
+        // Name, if it's given by name, and type of every argument
+        let arguments: Vec<(Option<String>, Option<Ops>)> = fn_call
+            .arguments
+            .iter()
+            .map(|argument| {
+                let name = if argument.interface == Ops::NamedArgument {
+                    Some(
+                        downcast_val::<NamedArgument>(argument.value.as_self())
+                            .name
+                            .clone(),
+                    )
+                } else {
+                    None
+                };
+                (name, self.check_value(argument))
+            })
+            .collect();
+
+        let signature = if let Some(reference_to) = &fn_call.reference_to {
+            let binding_type = self
+                .find_binding(reference_to)
+                .map(|binding| binding.value_type);
+
+            match binding_type {
+                Some(Some(val_type)) => {
+                    let signature = Signature::of_method(val_type, &fn_call.fn_name);
+
+                    if signature.is_none() {
+                        self.raise_error(
+                            errors::CODES::MethodNotFound,
+                            vec![fn_call.fn_name.clone(), val_type.type_name().to_string()],
+                            fn_call.line,
+                        );
+                    }
+
+                    signature
+                }
+                // Variables whose type is not known
+                Some(None) => None,
+                None => match self.modules.get(reference_to) {
+                    Some(functions) => {
+                        let signature = functions.get(&fn_call.fn_name).cloned();
+
+                        // Only the functions declared with `pub` are exported
+                        if signature.is_none() {
+                            self.raise_error(
+                                errors::CODES::FunctionNotFound,
+                                vec![fn_call.fn_name.clone()],
+                                fn_call.line,
+                            );
+                        }
+
+                        signature
+                    }
+                    None => None,
+                },
+            }
+        } else {
+            match self.functions.get(&fn_call.fn_name) {
+                Some(signature) => signature.clone(),
+                None => Signature::of_native(&fn_call.fn_name),
+            }
+        };
+
+        let signature = signature?;
+
+        self.check_arguments(fn_call, &signature, arguments);
+
+        signature.returns
+    }
+
+    /*
+     * Check the arguments of a call match the ones the function takes
+     */
+    fn check_arguments(
+        &mut self,
+        fn_call: &FnCall,
+        signature: &Signature,
+        arguments: Vec<(Option<String>, Option<Ops>)>,
+    ) {
+        let fn_name = fn_call.fn_name.as_str();
+        let line = fn_call.line;
+        let given = arguments.len();
+        let (named, positional): (Vec<_>, Vec<_>) =
+            arguments.into_iter().partition(|(name, _)| name.is_some());
+
+        let expected = if let Some(expected) = &signature.arguments {
+            expected
+        } else {
+            // Functions taking any number of arguments don't have names for them
+            if let Some((Some(name), _)) = named.first() {
+                self.raise_error(
+                    errors::CODES::UnknownArgument,
+                    vec![fn_name.to_string(), name.clone()],
+                    line,
+                );
+            }
+            return;
+        };
+
+        let mut given_names = Vec::new();
+        let mut valid_names = true;
+
+        for (name, found) in named {
+            let name = name.unwrap();
+            let position = expected
+                .iter()
+                .position(|(expected_name, _)| *expected_name == name);
+
+            match position {
+                None => {
+                    valid_names = false;
+                    self.raise_error(
+                        errors::CODES::UnknownArgument,
+                        vec![fn_name.to_string(), name],
+                        line,
+                    )
+                }
+                Some(position) if position < positional.len() || given_names.contains(&name) => {
+                    valid_names = false;
+                    self.raise_error(
+                        errors::CODES::DuplicatedArgument,
+                        vec![fn_name.to_string(), name],
+                        line,
+                    )
+                }
+                Some(position) => {
+                    if let Some(expected_type) = expected[position].1 {
+                        self.check_type(
+                            format!("argument '{}' of '{}'", name, fn_name),
+                            expected_type,
+                            found,
+                            line,
+                        );
+                    }
+                    given_names.push(name);
+                }
+            }
+        }
+
+        if !valid_names {
+            return;
+        }
+
+        let too_many = positional.len() > expected.len() && !signature.variadic;
+        let missing = expected
+            .iter()
+            .skip(positional.len())
+            .any(|(name, _)| !signature.optional.contains(name) && !given_names.contains(name));
+
+        if too_many || missing {
+            self.raise_error(
+                errors::CODES::WrongArgumentsCount,
+                vec![
+                    fn_name.to_string(),
+                    errors::arguments_count(
+                        expected.len() - signature.optional.len(),
+                        expected.len(),
+                        signature.variadic,
+                    ),
+                    given.to_string(),
+                ],
+                line,
+            );
+            return;
+        }
+
+        for ((name, expected_type), (_, found)) in expected.iter().zip(positional) {
+            if let Some(expected_type) = expected_type {
+                self.check_type(
+                    format!("argument '{}' of '{}'", name, fn_name),
+                    *expected_type,
+                    found,
+                    line,
+                );
+            }
+        }
+    }
+}
+
+/*
+ * Check if a block of code can't reach its end, because it returns or loops forever
+ *
+ * The blocks inside it are not followed, ex: an `if` that returns might not run
+ */
+fn always_returns(body: &[Box<dyn AstBase>]) -> bool {
+    body.iter().any(|operation| match operation.get_type() {
+        Ops::Return => true,
+        Ops::WhileDef => {
+            let while_block = downcast_val::<While>(operation.as_self());
+            while_block.conditions.iter().all(is_always_true) && !breaks(&while_block.body)
+        }
+        _ => false,
+    })
+}
+
+/*
+ * Check if a condition only compares literals and is true
+ */
+fn is_always_true(condition: &ResultExpression) -> bool {
+    let literal = |value: &BoxedValue| {
+        matches!(value.interface, Ops::Number | Ops::String | Ops::Boolean)
+            .then(|| value_to_string(value.clone()).ok())
+            .flatten()
+    };
+
+    match (literal(&condition.left), literal(&condition.right)) {
+        (Some(left), Some(right)) => match condition.relation {
+            Ops::EqualCondition => left == right,
+            Ops::NotEqualCondition => left != right,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/*
+ * Check if a loop's body can break out of it, the loops inside it have their own breaks
+ */
+fn breaks(body: &[Box<dyn AstBase>]) -> bool {
+    body.iter().any(|operation| match operation.get_type() {
+        Ops::Break => true,
+        Ops::IfConditional => breaks(&downcast_val::<IfConditional>(operation.as_self()).body),
+        _ => false,
+    })
+}
@@ -1,4 +1,7 @@
-use serde::Serialize;
+use serde::{
+    Deserialize,
+    Serialize,
+};
 
 #[derive(Clone)]
 pub enum Directions {
@@ -6,7 +9,7 @@ pub enum Directions {
     RightToLeft,
 }
 
-#[derive(Clone, Debug, Serialize, PartialEq, Copy)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Copy)]
 pub enum Ops {
     Invalid,
     Reference,
@@ -36,6 +39,77 @@ pub enum Ops {
     Module,
     FromModule,
     Break,
+    TailCall,
+    Mut,
+    ConstDef,
+    TypeAnnotation,
+    ReturnType,
+    NamedArgument,
+    List,
+    Yield,
+    Generator,
+    ForDef,
+    In,
+    Spawn,
+    Task,
+    Channel,
+    Pub,
+    Map,
+    Error,
+    Process,
+    None,
+    Try,
+}
+
+impl Ops {
+    /*
+     * Types of the values which can be used in annotations
+     */
+    pub const TYPES: [Ops; 12] = [
+        Ops::Number,
+        Ops::String,
+        Ops::Boolean,
+        Ops::Pointer,
+        Ops::List,
+        Ops::Generator,
+        Ops::Task,
+        Ops::Channel,
+        Ops::Map,
+        Ops::Error,
+        Ops::Process,
+        Ops::None,
+    ];
+
+    /*
+     * Get the type of a value by the name used in annotations, ex: `let a: number = 1`
+     */
+    pub fn from_type_name(name: &str) -> Option<Ops> {
+        Ops::TYPES
+            .iter()
+            .copied()
+            .find(|val_type| val_type.type_name() == name)
+    }
+
+    /*
+     * Name used in annotations for the type of a value
+     */
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Ops::Number => "number",
+            Ops::String => "string",
+            Ops::Boolean => "boolean",
+            Ops::Pointer => "pointer",
+            Ops::List => "list",
+            Ops::Generator => "generator",
+            Ops::Task => "task",
+            Ops::Channel => "channel",
+            Ops::Map => "map",
+            Ops::Error => "error",
+            Ops::Process => "process",
+            Ops::None => "none",
+            _ => "unknown",
+        }
+    }
 }
 
 pub mod errors {
@@ -52,18 +126,140 @@ pub mod errors {
         // Not used returned value
         ReturnedValueNotUsed,
 
-        // Pointer points to an invalid reference
-        BrokenPointer,
+        // Dereferenced a value which is not a pointer
+        NotAPointer,
+
+        // An assignment would make a pointer point to itself
+        CircularPointer,
 
         // Module is not found (ex, file's path is not correct)
         ModuleNotFound,
 
+        // Native module is not registered (ex, `std:nope`)
+        NativeModuleNotFound,
+
+        // Modules import each other, directly or through others
+        ImportCycle,
+
+        // Imported an item which the module doesn't export
+        NotExported,
+
         // Got a wrong keyword
         UnexpectedKeyword,
+
+        // The execution hit one of the limits
+        ExecutionAborted,
+
+        // The script is not allowed to do something
+        PermissionDenied,
+
+        // Mutated a variable which is not mutable
+        ImmutableVariable,
+
+        // Wrote through a pointer to a value which is not mutable
+        ImmutablePointer,
+
+        // Declared a constant inside a block
+        NestedConstant,
+
+        // Annotated a value with a type that doesn't exist
+        UnknownType,
+
+        // Called a function with a different number of arguments than it has
+        WrongArgumentsCount,
+
+        // Called a method that the value's type doesn't have
+        MethodNotFound,
+
+        // Got a value of a type different than the expected one
+        MismatchedTypes,
+
+        // A function with a return type can end without returning a value
+        MissingReturn,
+
+        // Two arguments of a function are not separated by a comma
+        ExpectedComma,
+
+        // Called a function with a named argument it doesn't have
+        UnknownArgument,
+
+        // Called a function giving the same argument more than once
+        DuplicatedArgument,
+
+        // Accessed an item of a list which doesn't exist
+        IndexOutOfBounds,
+
+        // A sum or power is bigger than the biggest number
+        NumberOverflow,
+
+        // Exited with a code bigger than the biggest exit code
+        InvalidExitCode,
+
+        // Used `yield` outside of a function
+        YieldOutsideGenerator,
+
+        // Resumed a generator from its own code
+        GeneratorRunning,
+
+        // Asked a finished generator for another value
+        GeneratorFinished,
+
+        // Iterated over a value which is not a list or a generator
+        NotIterable,
+
+        // Waited for something that will never happen, as every task is waiting too
+        Deadlock,
+
+        // Used `?` on an error outside of a function, so there is nothing to return it from
+        UnhandledError,
+    }
+
+    /*
+     * Describe how many arguments a function takes, ex: `2`, `1 to 3` or `at least 1`
+     */
+    pub fn arguments_count(required: usize, total: usize, variadic: bool) -> String {
+        if variadic {
+            format!("at least {}", required)
+        } else if required == total {
+            total.to_string()
+        } else {
+            format!("{} to {}", required, total)
+        }
+    }
+
+    /*
+     * List the names of the types which can be used in annotations, ex: `number, string or boolean`
+     */
+    fn type_names() -> String {
+        let names: Vec<&str> = super::Ops::TYPES.iter().map(super::Ops::type_name).collect();
+        let (last, rest) = names.split_last().unwrap();
+
+        format!("{} or {}", rest.join(", "), last)
+    }
+
+    /*
+     * Build the message of an error
+     *
+     * Errors are reported through `Stack::raise_error`, which writes them into the host's error stream
+     */
+    pub fn format_error(kind: CODES, args: Vec<String>) -> String {
+        format!("{}: {}\n", "Error".red(), message(kind, args))
     }
 
-    pub fn raise_error(kind: CODES, args: Vec<String>) {
-        let msg = match kind {
+    /*
+     * Build the message of an error found in a line of the code, ex: by the type checker
+     */
+    pub fn format_error_in_line(kind: CODES, args: Vec<String>, line: usize) -> String {
+        format!(
+            "{}: {}, in line {}\n",
+            "Error".red(),
+            message(kind, args),
+            line
+        )
+    }
+
+    fn message(kind: CODES, args: Vec<String>) -> String {
+        match kind {
             CODES::FunctionNotFound => format!("Function '{}' was not found", args[0]),
             CODES::VariableNotFound => format!("Variable '{}' was not found", args[0].blue()),
             CODES::ReturnedValueNotUsed => {
@@ -84,20 +280,187 @@ pub mod errors {
                     lines = lines
                 )
             }
-            CODES::BrokenPointer => {
+            CODES::NotAPointer => {
+                format!("'{}' does not point to any value", args[0].blue())
+            }
+            CODES::CircularPointer => {
                 format!(
-                    "Pointer points to variable by id '{}' which does no longer exist.",
+                    "Assigning to '{}' would make a pointer point to itself",
                     args[0].blue()
                 )
             }
             CODES::ModuleNotFound => {
-                format!("There is no module in path '{}'", args[0].blue())
+                format!(
+                    "There is no module '{}', tried: {}",
+                    args[0].blue(),
+                    args[1].blue()
+                )
+            }
+            CODES::NativeModuleNotFound => {
+                format!(
+                    "There is no native module '{}', the available ones are: {}",
+                    args[0].blue(),
+                    args[1].blue()
+                )
+            }
+            CODES::ImportCycle => {
+                format!("Modules import each other: {}", args[0].blue())
+            }
+            CODES::NotExported => {
+                format!(
+                    "'{}' is not exported by the module '{}', declare it with pub",
+                    args[0].blue(),
+                    args[1].blue()
+                )
             }
             CODES::UnexpectedKeyword => {
                 format!("Unexpected keyword '{}'", args[0].blue())
             }
-        };
-
-        println!("{}: {}", "Error".red(), msg);
+            CODES::ExecutionAborted => args[0].clone(),
+            CODES::PermissionDenied => {
+                format!(
+                    "Permission denied to {} '{}', run again with --allow-{}",
+                    args[0],
+                    args[1].blue(),
+                    args[0]
+                )
+            }
+            CODES::ImmutableVariable => {
+                format!(
+                    "Cannot mutate '{name}' because it is not mutable, it was declared in line {line}\n
+    {declaration}
+    ↑ Help: {help}",
+                    name = args[0].blue(),
+                    line = args[1],
+                    declaration = args[2],
+                    help = args[3]
+                )
+            }
+            CODES::ImmutablePointer => {
+                format!(
+                    "Cannot write through '{name}' because it does not point to a mutable value\n
+    {suggestion}
+    ↑ Help: {help}",
+                    name = args[0].blue(),
+                    suggestion = args[1],
+                    help = args[2]
+                )
+            }
+            CODES::NestedConstant => {
+                format!(
+                    "Constant '{}' in line {} can only be declared at the top level",
+                    args[0].blue(),
+                    args[1]
+                )
+            }
+            CODES::UnknownType => {
+                format!(
+                    "Unknown type '{}' in line {}, expected {}",
+                    args[0].blue(),
+                    args[1],
+                    type_names()
+                )
+            }
+            CODES::WrongArgumentsCount => {
+                format!(
+                    "Function '{}' takes {} argument(s) but {} were given",
+                    args[0].blue(),
+                    args[1],
+                    args[2]
+                )
+            }
+            CODES::MethodNotFound => {
+                format!(
+                    "Method '{}' was not found for type '{}'",
+                    args[0].blue(),
+                    args[1]
+                )
+            }
+            CODES::MismatchedTypes => {
+                format!(
+                    "Expected {} to be '{}' but found '{}'",
+                    args[0],
+                    args[1].blue(),
+                    args[2].blue()
+                )
+            }
+            CODES::MissingReturn => {
+                format!(
+                    "Function '{}' returns '{}' but it can end without returning a value",
+                    args[0].blue(),
+                    args[1].blue()
+                )
+            }
+            CODES::ExpectedComma => {
+                format!(
+                    "Expected a comma before argument '{}' in line {}",
+                    args[0].blue(),
+                    args[1]
+                )
+            }
+            CODES::UnknownArgument => {
+                format!(
+                    "Function '{}' has no argument named '{}'",
+                    args[0].blue(),
+                    args[1].blue()
+                )
+            }
+            CODES::DuplicatedArgument => {
+                format!(
+                    "Argument '{}' of function '{}' was given more than once",
+                    args[1].blue(),
+                    args[0].blue()
+                )
+            }
+            CODES::YieldOutsideGenerator => {
+                format!("Cannot yield '{}' outside of a function", args[0].blue())
+            }
+            CODES::GeneratorRunning => "A generator cannot resume itself".to_string(),
+            CODES::GeneratorFinished => {
+                "The generator has no more values, check it with done()".to_string()
+            }
+            CODES::NotIterable => {
+                format!(
+                    "Cannot iterate over a value of type '{}', expected a list or a generator",
+                    args[0].blue()
+                )
+            }
+            CODES::Deadlock => {
+                "Waiting forever, every task is waiting for something too".to_string()
+            }
+            CODES::IndexOutOfBounds => {
+                format!(
+                    "Index {} is out of bounds for a list of length {}",
+                    args[0].blue(),
+                    args[1]
+                )
+            }
+            CODES::NumberOverflow => {
+                // The first argument is the operation
+                if args[0] == "pow" {
+                    format!(
+                        "{} to the power of {} is bigger than the biggest number",
+                        args[1].blue(),
+                        args[2].blue()
+                    )
+                } else {
+                    format!(
+                        "The sum of {} and {} is bigger than the biggest number",
+                        args[1].blue(),
+                        args[2].blue()
+                    )
+                }
+            }
+            CODES::InvalidExitCode => {
+                format!(
+                    "Exit code {} is bigger than the biggest exit code, {}",
+                    args[0].blue(),
+                    args[1]
+                )
+            }
+            CODES::UnhandledError => {
+                format!("Unhandled error: {}", args[0].blue())
+            }
+        }
     }
 }
@@ -0,0 +1,1079 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+        expression::Expression,
+        named_argument::NamedArgument,
+        try_ast::TryStatement,
+    },
+    event_loop::{
+        run_tasks,
+        Wait,
+    },
+    get_function_from_def,
+    limits::Abort,
+    modules::define_module,
+    primitive_values::{
+        boolean::Boolean,
+        generator::{
+            Generator,
+            GeneratorStep,
+        },
+        number::{
+            sum_cell,
+            Number,
+        },
+        pointer::{
+            HeapCell,
+            Pointer,
+        },
+        string::StringVal,
+        task::Task,
+    },
+    report_unhandled,
+    run_in_thread,
+    runtime::{
+        downcast_val,
+        get_method_in_type,
+        prepare_call,
+        value_to_string,
+        values_to_strings,
+    },
+    stack::{
+        FunctionDef,
+        FunctionsContainer,
+        Stack,
+        VariableDef,
+    },
+    stdlib::none_value,
+    utils::{
+        errors,
+        Ops,
+    },
+};
+use std::{
+    collections::HashMap,
+    mem,
+    rc::Rc,
+    sync::{
+        Mutex,
+        MutexGuard,
+    },
+};
+
+use self::bytecode::{
+    Chunk,
+    CompiledFunction,
+    Instruction,
+};
+
+pub mod bytecode;
+pub mod compiler;
+
+/*
+ * What to do with the value returned by a frame
+ */
+enum ReturnTo {
+    // End of the execution
+    Host,
+    // Push it for the caller
+    Caller,
+    // Nobody uses it, which is an error if there is one
+    Nowhere {
+        fn_name: String,
+        arguments: Vec<BoxedValue>,
+    },
+}
+
+impl ReturnTo {
+    fn call(fn_name: &str, arguments: &[BoxedValue], keep_result: bool) -> Self {
+        if keep_result {
+            ReturnTo::Caller
+        } else {
+            ReturnTo::Nowhere {
+                fn_name: fn_name.to_string(),
+                arguments: arguments.to_vec(),
+            }
+        }
+    }
+}
+
+/*
+ * A compiled function along with its definition on the stack, so it's called without looking it up
+ */
+struct Callee {
+    function: FunctionDef,
+    compiled: Rc<CompiledFunction>,
+}
+
+/*
+ * What a call runs
+ */
+enum Callable {
+    // Compiled functions run in a new frame
+    Compiled(Rc<Callee>),
+    // Anything else is called directly, ex: native functions or generators, which are created instead of run
+    Native(FunctionDef),
+}
+
+impl Callable {
+    fn of(callee: Rc<Callee>) -> Self {
+        if callee.function.generator {
+            Callable::Native(callee.function.clone())
+        } else {
+            Callable::Compiled(callee)
+        }
+    }
+}
+
+/*
+ * Execution state of a function call
+ */
+struct Frame {
+    chunk: Rc<Chunk>,
+    pc: usize,
+    // Scopes opened by this frame, the first one is the frame's own scope
+    scopes: Vec<u64>,
+    // Cells of the variables defined by the chunk, shared with the stack, by their slot
+    locals: Vec<Option<HeapCell>>,
+    // Functions defined by the chunk, also defined in the stack, by their slot
+    functions: Vec<Option<Rc<Callee>>>,
+    return_to: ReturnTo,
+}
+
+impl Frame {
+    fn new(chunk: Rc<Chunk>, return_to: ReturnTo) -> Self {
+        Self {
+            locals: vec![None; chunk.locals.len()],
+            functions: vec![None; chunk.functions.len()],
+            chunk,
+            pc: 0,
+            scopes: Vec::new(),
+            return_to,
+        }
+    }
+
+    fn scope(&self) -> u64 {
+        *self.scopes.last().unwrap()
+    }
+}
+
+/*
+ * The stack, which is kept locked while the VM runs its instructions and only unlocked
+ * to run code which locks it by itself (ex: native functions)
+ */
+struct StackLock<'a> {
+    stack: &'a Mutex<Stack>,
+    guard: Option<MutexGuard<'a, Stack>>,
+}
+
+impl<'a> StackLock<'a> {
+    fn new(stack: &'a Mutex<Stack>) -> Self {
+        Self { stack, guard: None }
+    }
+
+    /*
+     * Get the locked stack, it's locked again if it was unlocked
+     */
+    fn get(&mut self) -> &mut Stack {
+        let stack = self.stack;
+        self.guard.get_or_insert_with(|| stack.lock().unwrap())
+    }
+
+    /*
+     * Unlock the stack to give it to code which locks it by itself
+     */
+    fn unlocked(&mut self) -> &'a Mutex<Stack> {
+        self.guard = None;
+        self.stack
+    }
+}
+
+/*
+ * Whether two values are equal, numbers are compared without formatting them
+ */
+fn values_equal(left: BoxedValue, right: BoxedValue) -> bool {
+    if left.interface == Ops::Number && right.interface == Ops::Number {
+        return downcast_val::<Number>(left.value.as_self()).0
+            == downcast_val::<Number>(right.value.as_self()).0;
+    }
+
+    value_to_string(left).unwrap() == value_to_string(right).unwrap()
+}
+
+/*
+ * How the execution stopped
+ */
+pub(crate) enum Step {
+    Returned(Option<BoxedValue>),
+    // A generator gave a value, the execution can go on from there
+    Yielded(Option<BoxedValue>),
+    // A task waits for something, the execution goes on once it's given, see `Vm::resume`
+    Blocked(Wait),
+}
+
+/*
+ * Stack-based virtual machine which runs compiled code
+ *
+ * Variables and functions still live in the memory stack, so native functions
+ * work the same way as with the tree-walking interpreter, but the ones defined
+ * by the running code are also kept in the frame's slots
+ */
+pub struct Vm {
+    // Running the code of a generator, which can yield
+    generator: bool,
+    // Running the code of a task, which can be suspended by the native functions it calls
+    task: bool,
+    // Call which suspended the task, it gets the value given when resuming it
+    blocked_call: Option<(Wait, ReturnTo)>,
+    frames: Vec<Frame>,
+    operands: Vec<Option<BoxedValue>>,
+    // Instructions run since the limits were last checked
+    ticks: u64,
+    // Compiled functions by the address of their body, which they keep alive
+    functions: HashMap<*const Vec<Box<dyn AstBase>>, Rc<CompiledFunction>>,
+}
+
+/*
+ * Open the scope of a call to a compiled function and define its arguments in it, returns
+ * the frame which runs it or gives back where to return if the arguments don't match
+ *
+ * A task's functions don't call themselves by their slot, other code can define
+ * functions of the same name while the task is suspended
+ */
+fn enter_function(
+    lock: &mut StackLock,
+    callee: Rc<Callee>,
+    arguments: Vec<BoxedValue>,
+    return_to: ReturnTo,
+    task: bool,
+) -> Result<Frame, ReturnTo> {
+    let mut frame = Frame::new(callee.compiled.chunk.clone(), return_to);
+    let function = &callee.function;
+
+    let scope = lock.get().open_scope();
+    frame.scopes.push(scope);
+
+    // Arguments given in the same order as the function's are defined right away
+    let in_order = arguments.len() == function.arguments.len()
+        && function.rest_argument.is_none()
+        && arguments
+            .iter()
+            .all(|argument| argument.interface != Ops::NamedArgument);
+
+    if in_order {
+        let stack = lock.get();
+
+        for (slot, (name, value)) in function.arguments.iter().zip(arguments).enumerate() {
+            let variable = VariableDef::new(name.clone(), value, scope);
+            frame.locals[slot] = Some(variable.value.clone());
+            stack.push_variable(variable);
+        }
+    } else if crate::bind_arguments(function, arguments, scope, lock.unlocked()) {
+        let stack = lock.get();
+
+        for (slot, name) in frame.chunk.locals[..frame.chunk.arguments].iter().enumerate() {
+            frame.locals[slot] = stack
+                .find_variable(name)
+                .map(|variable| variable.value.clone());
+        }
+    } else {
+        lock.get().close_scope(scope);
+        return Err(frame.return_to);
+    }
+
+    if frame.chunk.recursive && !task {
+        frame.functions[0] = Some(callee);
+    }
+
+    Ok(frame)
+}
+
+/*
+ * Compile and execute a AST tree, this is the VM version of `run_ast`
+ */
+pub fn run_vm(ast: &Mutex<Expression>, stack: &Mutex<Stack>) -> Option<BoxedValue> {
+    let ast = ast.lock().unwrap();
+
+    let chunk = compiler::compile(&ast.body);
+    let mut vm = Vm::new(chunk, ast.expr_id);
+
+    let res = vm.execute(stack);
+
+    // Spawned tasks keep the program running until they finish
+    run_tasks(stack);
+
+    res
+}
+
+/*
+ * Compile and execute a AST tree, failing if the execution was aborted, ex: by any of the stack's limits
+ */
+pub fn try_run_vm(
+    ast: &Mutex<Expression>,
+    stack: &Mutex<Stack>,
+) -> Result<Option<BoxedValue>, Abort> {
+    run_in_thread(ast, stack, run_vm)
+}
+
+impl Vm {
+    /*
+     * Create a VM which will run the chunk in the given scope
+     */
+    pub fn new(chunk: Chunk, scope: u64) -> Self {
+        let mut frame = Frame::new(Rc::new(chunk), ReturnTo::Host);
+        frame.scopes.push(scope);
+
+        Self {
+            frames: vec![frame],
+            operands: Vec::new(),
+            ticks: 0,
+            functions: HashMap::new(),
+            generator: false,
+            task: false,
+            blocked_call: None,
+        }
+    }
+
+    /*
+     * Create a VM which will run the code of a generator in the given scope, see `Vm::run`
+     */
+    pub(crate) fn generator(chunk: Chunk, scope: u64) -> Self {
+        Self {
+            generator: true,
+            ..Self::new(chunk, scope)
+        }
+    }
+
+    /*
+     * Create a VM which will run the code of a task in the given scope, see `Vm::resume`
+     */
+    pub(crate) fn task(chunk: Chunk, scope: u64) -> Self {
+        Self {
+            task: true,
+            ..Self::new(chunk, scope)
+        }
+    }
+
+    fn pop(&mut self) -> Option<BoxedValue> {
+        self.operands.pop().flatten()
+    }
+
+    /*
+     * Pop the values given as arguments to a call, the empty ones are left out
+     */
+    fn pop_arguments(&mut self, count: usize) -> Vec<BoxedValue> {
+        let start = self.operands.len() - count;
+        self.operands.drain(start..).flatten().collect()
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().unwrap()
+    }
+
+    /*
+     * Count the instructions run since the last check, returns false if any limit was hit
+     */
+    fn check_limits(&mut self, lock: &mut StackLock) -> bool {
+        lock.get().tick_by(mem::take(&mut self.ticks))
+    }
+
+    /*
+     * Run until the code returns
+     */
+    pub fn execute(&mut self, stack: &Mutex<Stack>) -> Option<BoxedValue> {
+        match self.run(stack) {
+            Step::Returned(value) => value,
+            // Only generators yield and only tasks are blocked
+            Step::Yielded(_) | Step::Blocked(_) => None,
+        }
+    }
+
+    /*
+     * Go on running a task that was blocked, the call that blocked it returns the given value
+     */
+    pub(crate) fn resume(&mut self, value: Option<BoxedValue>, stack: &Mutex<Stack>) -> Step {
+        let mut lock = StackLock::new(stack);
+
+        if let Some((_, return_to)) = self.blocked_call.take() {
+            self.deliver(&mut lock, value, return_to);
+        }
+
+        self.run_locked(&mut lock)
+    }
+
+    /*
+     * Run until the code returns or, for generators, until it yields a value, in which
+     * case calling it again resumes the execution
+     */
+    pub(crate) fn run(&mut self, stack: &Mutex<Stack>) -> Step {
+        self.run_locked(&mut StackLock::new(stack))
+    }
+
+    fn run_locked(&mut self, lock: &mut StackLock) -> Step {
+        // The code calling this can't be suspended
+        lock.get().event_loop.suspendable = false;
+
+        let mut chunk = self.frame().chunk.clone();
+
+        // Other code could have run since the last time, ex: while a task was blocked
+        let mut ran_code = true;
+
+        loop {
+            let frame = self.frames.last_mut().unwrap();
+
+            // Calls and returns change the code being run
+            if !Rc::ptr_eq(&chunk, &frame.chunk) {
+                chunk = frame.chunk.clone();
+            }
+
+            let instruction = &chunk.instructions[frame.pc];
+            let position = frame.pc;
+            frame.pc += 1;
+            self.ticks += 1;
+
+            // Stop if any limit was hit
+            if (ran_code || instruction.is_checkpoint(position)) && !self.check_limits(lock) {
+                self.unwind(lock);
+                return Step::Returned(None);
+            }
+
+            ran_code = instruction.runs_code();
+
+            match instruction {
+                Instruction::Constant(value) => self.operands.push(Some(value.clone())),
+
+                Instruction::Nothing => self.operands.push(None),
+
+                Instruction::Load(reference) => {
+                    let value = lock.get().get_value_by_reference(reference);
+                    self.operands.push(value);
+                }
+
+                Instruction::Local(slot) => {
+                    let value = match &self.frame().locals[*slot] {
+                        Some(cell) => Some(cell.borrow().clone()),
+                        // It wasn't defined, so it's whatever has its name
+                        None => lock.get().get_value_by_reference(&chunk.locals[*slot]),
+                    };
+                    self.operands.push(value);
+                }
+
+                Instruction::LocalPointer { slot, mutable } => {
+                    let value = match &self.frame().locals[*slot] {
+                        Some(cell) => Some(BoxedValue {
+                            interface: Ops::Pointer,
+                            value: Box::new(Pointer {
+                                cell: cell.clone(),
+                                mutable: *mutable,
+                            }),
+                        }),
+                        None => {
+                            let pointer = if *mutable { "&mut " } else { "&" };
+                            let reference = format!("{}{}", pointer, chunk.locals[*slot]);
+                            lock.get().get_value_by_reference(&reference)
+                        }
+                    };
+                    self.operands.push(value);
+                }
+
+                Instruction::LocalDeref { slot, reference } => {
+                    let value = match self.frame().locals[*slot].clone() {
+                        Some(cell) => {
+                            let derefs = reference.len() - chunk.locals[*slot].len();
+                            lock.get()
+                                .deref_cell(cell, derefs, reference, false)
+                                .map(|cell| cell.borrow().clone())
+                        }
+                        None => lock.get().get_value_by_reference(reference),
+                    };
+                    self.operands.push(value);
+                }
+
+                Instruction::DefineLocal(slot) => {
+                    if let Some(value) = self.pop() {
+                        let expr_id = self.frame().scope();
+                        let variable =
+                            VariableDef::new(chunk.locals[*slot].clone(), value, expr_id);
+
+                        self.frame().locals[*slot] = Some(variable.value.clone());
+                        lock.get().push_variable(variable);
+                    }
+                }
+
+                Instruction::Define(name) => {
+                    if let Some(value) = self.pop() {
+                        let expr_id = self.frame().scope();
+
+                        lock.get()
+                            .push_variable(VariableDef::new(name.clone(), value, expr_id));
+                    }
+                }
+
+                Instruction::Name(name) => {
+                    let value = self.operands.pop().flatten().map(|value| BoxedValue {
+                        interface: Ops::NamedArgument,
+                        value: Box::new(NamedArgument {
+                            name: name.clone(),
+                            value,
+                        }),
+                    });
+                    self.operands.push(value);
+                }
+
+                Instruction::Assign(name) => {
+                    if let Some(value) = self.pop() {
+                        lock.get().modify_var(name.clone(), value);
+                    }
+                }
+
+                Instruction::AssignLocal(slot) => {
+                    if let Some(value) = self.pop() {
+                        match self.frame().locals[*slot].clone() {
+                            // A pointer can't end up pointing to itself
+                            Some(cell) if Pointer::reaches(&value, &cell) => {
+                                lock.get().raise_error(
+                                    errors::CODES::CircularPointer,
+                                    vec![chunk.locals[*slot].clone()],
+                                );
+                            }
+                            Some(cell) => *cell.borrow_mut() = value,
+                            None => lock.get().modify_var(chunk.locals[*slot].clone(), value),
+                        }
+                    }
+                }
+
+                Instruction::Compare(relation) => {
+                    let right = self.pop();
+                    let left = self.pop();
+
+                    let res = if let (Some(left), Some(right)) = (left, right) {
+                        match relation {
+                            Ops::EqualCondition => values_equal(left, right),
+                            Ops::NotEqualCondition => !values_equal(left, right),
+                            _ => false,
+                        }
+                    } else {
+                        false
+                    };
+
+                    self.operands.push(Some(BoxedValue {
+                        interface: Ops::Boolean,
+                        value: Box::new(Boolean(res)),
+                    }));
+                }
+
+                Instruction::JumpIfNotAll { conditions, target } => {
+                    let mut all_true = true;
+
+                    for _ in 0..*conditions {
+                        if let Some(condition) = self.pop() {
+                            all_true &= downcast_val::<Boolean>(condition.value.as_self()).0;
+                        }
+                    }
+
+                    if !all_true {
+                        self.frame().pc = *target;
+                    }
+                }
+
+                Instruction::Jump(target) => self.frame().pc = *target,
+
+                Instruction::JumpIfNothing(target) => {
+                    if let Some(None) = self.operands.last() {
+                        self.frame().pc = *target;
+                    }
+                }
+
+                Instruction::EnterScope => {
+                    let scope = lock.get().open_scope();
+                    self.frame().scopes.push(scope);
+                }
+
+                Instruction::ExitScope => {
+                    let scope = self.frame().scopes.pop().unwrap();
+                    lock.get().close_scope(scope);
+                }
+
+                Instruction::DefineFunction {
+                    function: compiled,
+                    slot,
+                } => {
+                    let mut function = get_function_from_def(&compiled.definition);
+                    function.expr_id = self.frame().scope();
+                    self.functions
+                        .insert(Rc::as_ptr(&function.body), compiled.clone());
+                    lock.get().push_function(function.clone());
+
+                    self.frame().functions[*slot] = Some(Rc::new(Callee {
+                        function,
+                        compiled: compiled.clone(),
+                    }));
+                }
+
+                Instruction::Call {
+                    name,
+                    reference_to,
+                    arguments,
+                    keep_result,
+                } => {
+                    let values = self.pop_arguments(*arguments);
+                    self.call(lock, name, reference_to, values, *keep_result);
+                }
+
+                Instruction::CallLocal {
+                    slot,
+                    arguments,
+                    keep_result,
+                } => {
+                    let values = self.pop_arguments(*arguments);
+                    let name = &chunk.functions[*slot];
+
+                    match self.frame().functions[*slot].clone() {
+                        Some(callee) => {
+                            let return_to = ReturnTo::call(name, &values, *keep_result);
+                            self.call_callable(lock, Callable::of(callee), values, return_to);
+                        }
+                        // It wasn't defined yet, so it's whatever has its name
+                        None => self.call(lock, name, &None, values, *keep_result),
+                    }
+                }
+
+                Instruction::CallMethod {
+                    slot,
+                    name,
+                    arguments,
+                    keep_result,
+                } => {
+                    let values = self.pop_arguments(*arguments);
+                    let var_name = &chunk.locals[*slot];
+
+                    match self.frame().locals[*slot].clone() {
+                        Some(cell) => {
+                            self.call_method(lock, cell, var_name, name, values, *keep_result)
+                        }
+                        // It wasn't defined, so it's whatever has its name
+                        None => {
+                            let reference_to = Some(var_name.clone());
+                            self.call(lock, name, &reference_to, values, *keep_result);
+                        }
+                    }
+                }
+
+                Instruction::TailCall { name, arguments } => {
+                    let values = self.pop_arguments(*arguments);
+                    let callable = lock
+                        .get()
+                        .get_function_by_name(name)
+                        .map(|function| self.callable(function));
+
+                    // Skip the return, the frame starts again with the called function
+                    if self.tail_call(lock, callable, name, values) {
+                        continue;
+                    }
+                }
+
+                Instruction::TailCallLocal { slot, arguments } => {
+                    let values = self.pop_arguments(*arguments);
+                    let callable = self.frame().functions[*slot].clone().map(Callable::of);
+
+                    if self.tail_call(lock, callable, &chunk.functions[*slot], values) {
+                        continue;
+                    }
+                }
+
+                Instruction::Spawn { spawn, keep_result } => {
+                    let values = self.pop_arguments(spawn.call.arguments.len());
+                    let task = Task::spawn(spawn, values, lock.unlocked());
+
+                    if *keep_result {
+                        self.operands.push(task);
+                    }
+                }
+
+                Instruction::Return => {
+                    let value = self.pop();
+
+                    if let Some(value) = self.return_from_frame(lock, value) {
+                        return Step::Returned(value);
+                    }
+                }
+
+                Instruction::Try => {
+                    let value = self.pop().filter(TryStatement::returns);
+
+                    if let Some(value) = value {
+                        // Only the code of the program itself has nowhere to return errors to
+                        if !self.task && !self.generator {
+                            report_unhandled(&value, lock.unlocked());
+                        }
+
+                        if let Some(value) = self.return_from_frame(lock, Some(value)) {
+                            return Step::Returned(value);
+                        }
+                    }
+                }
+
+                Instruction::Yield => {
+                    let value = self.pop();
+
+                    // Generators only yield from their own frame, functions with `yield` are generators too
+                    if self.generator {
+                        return Step::Yielded(value);
+                    }
+
+                    let value = value.and_then(|value| value_to_string(value).ok());
+                    lock.get().raise_error(
+                        errors::CODES::YieldOutsideGenerator,
+                        vec![value.unwrap_or_default()],
+                    );
+                }
+
+                Instruction::Iterate => {
+                    let iterator = self.pop().and_then(|value| {
+                        Generator::iterate(&value, lock.unlocked()).map(|generator| BoxedValue {
+                            interface: Ops::Generator,
+                            value: Box::new(generator),
+                        })
+                    });
+                    self.operands.push(iterator);
+                }
+
+                Instruction::Next { iterator, target } => {
+                    let iterator = lock.get().get_value_by_name(iterator);
+
+                    let step = iterator.map(|iterator| {
+                        downcast_val::<Generator>(iterator.value.as_self()).resume(lock.unlocked())
+                    });
+
+                    match step {
+                        Some(GeneratorStep::Yielded(value)) => self.operands.push(value),
+                        _ => self.frame().pc = *target,
+                    }
+                }
+
+                Instruction::Import(module) => {
+                    let scope = self.frame().scope();
+                    define_module(module, scope, lock.unlocked());
+                }
+            }
+
+            // Native functions called by a task can suspend it
+            if let Some((wait, _)) = &self.blocked_call {
+                return Step::Blocked(wait.clone());
+            }
+        }
+    }
+
+    /*
+     * What calling a function found by its name runs
+     */
+    fn callable(&self, function: FunctionDef) -> Callable {
+        // Native functions have no body, so they are not looked up
+        let compiled = if function.generator || function.body.is_empty() {
+            None
+        } else {
+            self.functions.get(&Rc::as_ptr(&function.body)).cloned()
+        };
+
+        match compiled {
+            Some(compiled) => Callable::Compiled(Rc::new(Callee { function, compiled })),
+            None => Callable::Native(function),
+        }
+    }
+
+    /*
+     * Call a function by its name, or a method of a variable found by its name
+     */
+    fn call(
+        &mut self,
+        lock: &mut StackLock,
+        name: &str,
+        reference_to: &Option<String>,
+        values: Vec<BoxedValue>,
+        keep_result: bool,
+    ) {
+        let (function, arguments) =
+            if let Some(call) = prepare_call(lock.unlocked(), name, reference_to, values) {
+                call
+            } else {
+                if keep_result {
+                    self.operands.push(None);
+                }
+                return;
+            };
+
+        let return_to = ReturnTo::call(name, &arguments, keep_result);
+        let callable = self.callable(function);
+
+        self.call_callable(lock, callable, arguments, return_to);
+    }
+
+    /*
+     * Call a method of a variable defined by the chunk
+     *
+     * Summing numbers is how the code counts, so it's done right away when the arguments
+     * are right, otherwise the method reports what's wrong with them
+     */
+    fn call_method(
+        &mut self,
+        lock: &mut StackLock,
+        cell: HeapCell,
+        var_name: &str,
+        name: &str,
+        values: Vec<BoxedValue>,
+        keep_result: bool,
+    ) {
+        let val_type = cell.borrow().interface;
+
+        let amount = match values.as_slice() {
+            [amount] if val_type == Ops::Number && amount.interface == Ops::Number => {
+                Some(downcast_val::<Number>(amount.value.as_self()).0)
+            }
+            _ => None,
+        };
+
+        match (name, amount) {
+            ("mut_sum", Some(amount)) => {
+                if let Some(sum) = sum_cell(&cell, amount, lock.get()) {
+                    // Pointers share the variable's cell, so they see the new value too
+                    *cell.borrow_mut() = BoxedValue {
+                        interface: Ops::Number,
+                        value: Box::new(sum),
+                    };
+                }
+
+                if keep_result {
+                    self.deliver(lock, None, ReturnTo::Caller);
+                }
+                return;
+            }
+            // Unused sums are reported by the method
+            ("sum", Some(amount)) if keep_result => {
+                let sum = sum_cell(&cell, amount, lock.get()).map(|sum| BoxedValue {
+                    interface: Ops::Number,
+                    value: Box::new(sum),
+                });
+
+                self.deliver(lock, sum, ReturnTo::Caller);
+                return;
+            }
+            _ => {}
+        }
+
+        let function = if let Some(function) = get_method_in_type(val_type, name) {
+            function
+        } else {
+            lock.get()
+                .raise_error(errors::CODES::FunctionNotFound, vec![name.to_string()]);
+            if keep_result {
+                self.operands.push(None);
+            }
+            return;
+        };
+
+        // Methods get the name of their variable as first argument, see `prepare_call`
+        let mut arguments = Vec::with_capacity(values.len() + 1);
+        arguments.push(BoxedValue {
+            interface: Ops::String,
+            value: Box::new(StringVal(var_name.to_string())),
+        });
+        arguments.extend(values);
+
+        let return_to = ReturnTo::call(name, &arguments, keep_result);
+        self.call_native(lock, &function, arguments, return_to);
+    }
+
+    /*
+     * Call a function, compiled functions are executed in a new frame and
+     * anything else is called directly
+     */
+    fn call_callable(
+        &mut self,
+        lock: &mut StackLock,
+        callable: Callable,
+        arguments: Vec<BoxedValue>,
+        return_to: ReturnTo,
+    ) {
+        let callee = match callable {
+            Callable::Compiled(callee) => callee,
+            Callable::Native(function) => {
+                return self.call_native(lock, &function, arguments, return_to)
+            }
+        };
+
+        if !lock.get().enter_call() {
+            if let ReturnTo::Caller = return_to {
+                self.operands.push(None);
+            }
+            return;
+        }
+
+        match enter_function(lock, callee, arguments, return_to, self.task) {
+            Ok(frame) => self.frames.push(frame),
+            Err(return_to) => {
+                lock.get().exit_call();
+                self.deliver(lock, None, return_to);
+            }
+        }
+    }
+
+    /*
+     * Call a function which is not compiled, ex: a native function
+     */
+    fn call_native(
+        &mut self,
+        lock: &mut StackLock,
+        function: &FunctionDef,
+        arguments: Vec<BoxedValue>,
+        return_to: ReturnTo,
+    ) {
+        // Native functions called by a task can suspend it, other code is never suspended
+        if self.task {
+            lock.get().event_loop.suspendable = true;
+        }
+
+        let res = (function.cb)(function, arguments, lock.unlocked());
+
+        let blocked = if self.task {
+            let stack = lock.get();
+            stack.event_loop.suspendable = false;
+            stack.event_loop.blocked.take()
+        } else {
+            None
+        };
+
+        if let Some(wait) = blocked {
+            self.blocked_call = Some((wait, return_to));
+        } else {
+            self.deliver(lock, res, return_to);
+        }
+    }
+
+    /*
+     * Call a function whose result is returned, if it's compiled the current frame
+     * is reused instead of pushing a new one
+     *
+     * Returns false if the function was called the usual way, in which case its result
+     * must be returned
+     */
+    fn tail_call(
+        &mut self,
+        lock: &mut StackLock,
+        callable: Option<Callable>,
+        name: &str,
+        values: Vec<BoxedValue>,
+    ) -> bool {
+        let task = self.task;
+        let frame = self.frames.last_mut().unwrap();
+
+        match callable {
+            // The frame running the whole code can't be reused
+            Some(Callable::Compiled(callee)) if !matches!(frame.return_to, ReturnTo::Host) => {
+                let stack = lock.get();
+                for scope in frame.scopes.drain(..).rev() {
+                    stack.close_scope(scope);
+                }
+
+                let return_to = mem::replace(&mut frame.return_to, ReturnTo::Host);
+
+                match enter_function(lock, callee, values, return_to, task) {
+                    Ok(next) => {
+                        *frame = next;
+                        true
+                    }
+                    Err(return_to) => {
+                        // The frame returns nothing
+                        frame.return_to = return_to;
+                        self.operands.push(None);
+                        false
+                    }
+                }
+            }
+            Some(callable) => {
+                self.call_callable(lock, callable, values, ReturnTo::Caller);
+                false
+            }
+            // It's reported as not found
+            None => {
+                self.call(lock, name, &None, values, true);
+                false
+            }
+        }
+    }
+
+    /*
+     * Give the value returned by a function to whoever called it
+     */
+    fn deliver(&mut self, lock: &mut StackLock, value: Option<BoxedValue>, return_to: ReturnTo) {
+        match return_to {
+            // Calls used as values always give one, `none` if they don't return anything
+            ReturnTo::Caller => self.operands.push(Some(value.unwrap_or_else(none_value))),
+            ReturnTo::Nowhere { fn_name, arguments } => {
+                // `none` is the same as returning nothing
+                if let Some(value) = value.filter(|value| value.interface != Ops::None) {
+                    if let Ok(value) = value_to_string(value) {
+                        // The function returned something that ends up not being used, throw error
+                        let arguments = values_to_strings(arguments).join(" ");
+                        lock.get().raise_error(
+                            errors::CODES::ReturnedValueNotUsed,
+                            vec![value, fn_name, arguments],
+                        );
+                    }
+                }
+            }
+            ReturnTo::Host => {}
+        }
+    }
+
+    /*
+     * Leave the current frame, returns the final value when the execution is over
+     *
+     * Values are kept in cells, so the ones returned don't depend on the frame's variables
+     */
+    fn return_from_frame(
+        &mut self,
+        lock: &mut StackLock,
+        value: Option<BoxedValue>,
+    ) -> Option<Option<BoxedValue>> {
+        let frame = self.frames.pop().unwrap();
+        let stack = lock.get();
+
+        if let ReturnTo::Host = frame.return_to {
+            // The scope of the whole execution is kept, as with `run_ast`
+            for scope in frame.scopes.into_iter().skip(1).rev() {
+                stack.close_scope(scope);
+            }
+            return Some(value);
+        }
+
+        for scope in frame.scopes.into_iter().rev() {
+            stack.close_scope(scope);
+        }
+        stack.exit_call();
+
+        self.deliver(lock, value, frame.return_to);
+        None
+    }
+
+    /*
+     * Leave all the frames after the execution was aborted
+     */
+    fn unwind(&mut self, lock: &mut StackLock) {
+        let stack = lock.get();
+
+        while let Some(frame) = self.frames.pop() {
+            let is_call = !matches!(frame.return_to, ReturnTo::Host);
+
+            let scopes = frame.scopes.into_iter().skip(if is_call { 0 } else { 1 });
+            for scope in scopes.rev() {
+                stack.close_scope(scope);
+            }
+
+            if is_call {
+                stack.exit_call();
+            }
+        }
+        self.operands.clear();
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    ast_types::{
+        boxed_val::BoxedValue,
+        fn_def::FnDefinition,
+        module::Module,
+        spawn::Spawn,
+    },
+    utils::Ops,
+};
+use std::rc::Rc;
+
+/*
+ * A single VM instruction
+ *
+ * Instructions work on a stack of operands, an operand might be empty (ex: the result of a
+ * function which didn't return anything)
+ */
+#[derive(Clone, Debug)]
+pub enum Instruction {
+    // Push a static value
+    Constant(BoxedValue),
+
+    // Push an empty operand
+    Nothing,
+
+    // Push the value of a reference, a variable (`a`), a pointer to it (`&a`) or a dereferenced pointer (`*a`)
+    //
+    // Only used for the variables which are not defined by the chunk itself, ex: global variables
+    // read from a function
+    Load(String),
+
+    // Push the value of a variable defined by the chunk itself, by its slot
+    Local(usize),
+
+    // Push a pointer to a variable defined by the chunk itself (`&a` or `&mut a`)
+    LocalPointer {
+        slot: usize,
+        mutable: bool,
+    },
+
+    // Push the value pointed by a variable defined by the chunk itself (`*a`, `**a`...)
+    LocalDeref {
+        slot: usize,
+        reference: String,
+    },
+
+    // Pop a value and define a variable with it in the current scope
+    Define(String),
+
+    // Like `Define`, the variable is also kept in the given slot
+    DefineLocal(usize),
+
+    // Pop a value and push it as the argument of the given name, ex: `b = 2` in `calc(1, b = 2)`
+    Name(String),
+
+    // Pop a value and assign it to an existing variable
+    Assign(String),
+
+    // Pop a value and assign it to a variable defined by the chunk itself, by its slot
+    AssignLocal(usize),
+
+    // Pop the arguments and call a function, the result is pushed if it's kept
+    Call {
+        name: String,
+        reference_to: Option<String>,
+        arguments: usize,
+        keep_result: bool,
+    },
+
+    // Like `Call`, the function is the one defined by the chunk in the given slot
+    CallLocal {
+        slot: usize,
+        arguments: usize,
+        keep_result: bool,
+    },
+
+    // Like `Call`, the function is a method of the variable defined by the chunk in the given slot
+    CallMethod {
+        slot: usize,
+        name: String,
+        arguments: usize,
+        keep_result: bool,
+    },
+
+    // Pop the arguments and call a function whose result is returned right after, it might replace
+    // the current function's frame
+    TailCall {
+        name: String,
+        arguments: usize,
+    },
+
+    // Like `TailCall`, the function is the one defined by the chunk in the given slot
+    TailCallLocal {
+        slot: usize,
+        arguments: usize,
+    },
+
+    // Pop two values and push whether they satisfy the relation (== or !=)
+    Compare(Ops),
+
+    // Pop a number of conditions and jump if any of them is false
+    JumpIfNotAll {
+        conditions: usize,
+        target: usize,
+    },
+
+    Jump(usize),
+
+    // Jump if the value on top is empty, which is kept
+    JumpIfNothing(usize),
+
+    // Open and close a block scope
+    EnterScope,
+    ExitScope,
+
+    // Define a function in the current scope, it's also kept in the given slot
+    DefineFunction {
+        function: Rc<CompiledFunction>,
+        slot: usize,
+    },
+
+    // Pop the arguments and spawn a task calling the function, the task is pushed if it's kept
+    Spawn {
+        spawn: Box<Spawn>,
+        keep_result: bool,
+    },
+
+    // Pop a value and return it from the current function
+    Return,
+
+    // Pop a value and return it from the current function if it's `none` or an error, see `TryStatement`
+    Try,
+
+    // Pop a value and give it to whoever resumed the generator, which stops running until it's resumed again
+    Yield,
+
+    // Pop a value and push a generator to iterate over it
+    Iterate,
+
+    // Push the next value of the generator stored in the given variable, or jump if it's over
+    Next {
+        iterator: String,
+        target: usize,
+    },
+
+    // Import a module into the current scope
+    Import(Box<Module>),
+}
+
+impl Instruction {
+    /*
+     * Whether the limits are checked before running it, which is needed for the instructions
+     * that run code again or leave the frame, ex: backward jumps and returns
+     */
+    pub fn is_checkpoint(&self, position: usize) -> bool {
+        match self {
+            Instruction::Jump(target) => *target <= position,
+            Instruction::Return | Instruction::Try | Instruction::Yield => true,
+            _ => false,
+        }
+    }
+
+    /*
+     * Whether it runs other code, which might stop the execution (ex: `process.exit()`), so
+     * the limits are checked again before going on
+     */
+    pub fn runs_code(&self) -> bool {
+        matches!(
+            self,
+            Instruction::Call { .. }
+                | Instruction::CallLocal { .. }
+                | Instruction::CallMethod { .. }
+                | Instruction::TailCall { .. }
+                | Instruction::TailCallLocal { .. }
+                | Instruction::Spawn { .. }
+                | Instruction::Next { .. }
+                | Instruction::Import(_)
+        )
+    }
+}
+
+/*
+ * A list of instructions
+ */
+#[derive(Clone, Debug, Default)]
+pub struct Chunk {
+    pub instructions: Vec<Instruction>,
+    // Names of the variables defined by the chunk, by their slot
+    pub locals: Vec<String>,
+    // How many of the first slots are the arguments of the function
+    pub arguments: usize,
+    // Names of the functions defined by the chunk, by their slot
+    pub functions: Vec<String>,
+    // Whether the first function slot is the function the chunk is the body of, so it can call itself
+    pub recursive: bool,
+}
+
+/*
+ * A function definition along with its compiled body
+ */
+#[derive(Clone, Debug)]
+pub struct CompiledFunction {
+    pub definition: FnDefinition,
+    pub chunk: Rc<Chunk>,
+}
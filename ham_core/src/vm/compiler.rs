@@ -0,0 +1,628 @@
+use crate::{
+    ast_types::{
+        ast_base::AstBase,
+        boxed_val::BoxedValue,
+        expression::Expression,
+        fn_call::FnCall,
+        fn_def::FnDefinition,
+        for_block::For,
+        if_ast::IfConditional,
+        module::Module,
+        named_argument::NamedArgument,
+        reference::Reference,
+        result::ResultExpression,
+        return_ast::ReturnStatement,
+        spawn::Spawn,
+        try_ast::TryStatement,
+        var_assign::VarAssignment,
+        var_def::VarDefinition,
+        while_block::While,
+        yield_ast::YieldStatement,
+    },
+    runtime::downcast_val,
+    utils::Ops,
+    vm::bytecode::{
+        Chunk,
+        CompiledFunction,
+        Instruction,
+    },
+};
+use std::rc::Rc;
+
+/*
+ * Variable holding the generator of a for loop, it can't be used from the code
+ */
+const ITERATOR: &str = "@iterator";
+
+/*
+ * Slots of the variables and functions defined in a scope, by their name
+ */
+#[derive(Default)]
+struct Slots {
+    variables: Vec<(String, usize)>,
+    functions: Vec<(String, usize)>,
+}
+
+/*
+ * Jumps that must be pointed to the end of a block once it's known
+ */
+struct Block {
+    // Scope depth inside the block
+    depth: usize,
+    exits: Vec<usize>,
+}
+
+/*
+ * Compiles AST trees into bytecode
+ */
+#[derive(Default)]
+pub struct Compiler {
+    instructions: Vec<Instruction>,
+    // How many scopes are open
+    depth: usize,
+    // While loops being compiled, `break` jumps to the end of the innermost one
+    loops: Vec<Block>,
+    // Expression values being compiled, `return` jumps to the end of the innermost one
+    groups: Vec<Block>,
+    // Slots of what is defined in each open scope
+    scopes: Vec<Slots>,
+    // Names of the variables defined by the chunk, by their slot
+    locals: Vec<String>,
+    // Names of the functions defined by the chunk, by their slot
+    functions: Vec<String>,
+}
+
+/*
+ * Compile a body of code, it will return when reaching the end
+ */
+pub fn compile(body: &[Box<dyn AstBase>]) -> Chunk {
+    Compiler::new().finish(body, 0, false)
+}
+
+/*
+ * Compile the body of a function, its arguments take the first slots and
+ * the function itself takes the first function slot
+ */
+pub fn compile_function(function: &FnDefinition) -> Chunk {
+    let mut compiler = Compiler::new();
+
+    let arguments: Vec<&String> = function
+        .arguments
+        .iter()
+        .chain(&function.rest_argument)
+        .collect();
+
+    for argument in &arguments {
+        compiler.define_local(argument);
+    }
+
+    compiler.define_function(&function.def_name);
+
+    compiler.finish(&function.body, arguments.len(), true)
+}
+
+/*
+ * Find the innermost slot of the given name
+ */
+fn find_slot<'a>(
+    scopes: impl DoubleEndedIterator<Item = &'a Vec<(String, usize)>>,
+    name: &str,
+) -> Option<usize> {
+    scopes
+        .rev()
+        .flat_map(|scope| scope.iter().rev())
+        .find(|(defined, _)| defined == name)
+        .map(|(_, slot)| *slot)
+}
+
+/*
+ * Whether a block defines something in its own scope, blocks which don't are run without opening one
+ */
+fn defines_in_scope(body: &[Box<dyn AstBase>]) -> bool {
+    body.iter().any(|operation| {
+        !matches!(
+            operation.get_type(),
+            Ops::Break
+                | Ops::WhileDef
+                | Ops::ForDef
+                | Ops::Yield
+                | Ops::Return
+                | Ops::Try
+                | Ops::IfConditional
+                | Ops::VarAssign
+                | Ops::FnCall
+                | Ops::Spawn
+        )
+    })
+}
+
+impl Compiler {
+    fn new() -> Self {
+        Self {
+            scopes: vec![Slots::default()],
+            ..Self::default()
+        }
+    }
+
+    /*
+     * Compile the code and put it together with the slots it uses
+     */
+    fn finish(mut self, body: &[Box<dyn AstBase>], arguments: usize, recursive: bool) -> Chunk {
+        self.compile_body(body);
+        self.emit(Instruction::Nothing);
+        self.emit(Instruction::Return);
+
+        Chunk {
+            instructions: self.instructions,
+            locals: self.locals,
+            arguments,
+            functions: self.functions,
+            recursive,
+        }
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+
+    fn position(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /*
+     * Point a previously emitted jump to the given position
+     */
+    fn patch(&mut self, jump: usize, target: usize) {
+        match &mut self.instructions[jump] {
+            Instruction::Jump(to) => *to = target,
+            Instruction::JumpIfNothing(to) => *to = target,
+            Instruction::JumpIfNotAll { target: to, .. } => *to = target,
+            Instruction::Next { target: to, .. } => *to = target,
+            _ => unreachable!("Only jumps can be patched"),
+        }
+    }
+
+    fn exit_scopes(&mut self, until_depth: usize) {
+        for _ in until_depth..self.depth {
+            self.emit(Instruction::ExitScope);
+        }
+    }
+
+    /*
+     * Give a slot to a variable defined in the current scope
+     */
+    fn define_local(&mut self, name: &str) -> usize {
+        let slot = self.locals.len();
+        self.locals.push(name.to_string());
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .variables
+            .push((name.to_string(), slot));
+        slot
+    }
+
+    /*
+     * Give a slot to a function defined in the current scope
+     */
+    fn define_function(&mut self, name: &str) -> usize {
+        let slot = self.functions.len();
+        self.functions.push(name.to_string());
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .functions
+            .push((name.to_string(), slot));
+        slot
+    }
+
+    /*
+     * Find the slot of a variable defined by the chunk, others are found by their name while running
+     */
+    fn find_local(&self, name: &str) -> Option<usize> {
+        find_slot(self.scopes.iter().map(|scope| &scope.variables), name)
+    }
+
+    /*
+     * Find the slot of a function defined by the chunk, others are found by their name while running
+     */
+    fn find_function(&self, name: &str) -> Option<usize> {
+        find_slot(self.scopes.iter().map(|scope| &scope.functions), name)
+    }
+
+    /*
+     * Imported modules can define variables and functions of any name, which would hide
+     * the ones defined before, so these are found by their name from then on
+     */
+    fn forget_slots(&mut self) {
+        for scope in &mut self.scopes {
+            scope.variables.clear();
+            scope.functions.clear();
+        }
+    }
+
+    fn enter_scope(&mut self) {
+        self.emit(Instruction::EnterScope);
+        self.depth += 1;
+        self.scopes.push(Slots::default());
+    }
+
+    fn exit_scope(&mut self) {
+        self.scopes.pop();
+        self.depth -= 1;
+        self.emit(Instruction::ExitScope);
+    }
+
+    fn compile_body(&mut self, body: &[Box<dyn AstBase>]) {
+        for operation in body {
+            self.compile_operation(operation.as_ref());
+        }
+    }
+
+    /*
+     * Compile the code of a block, inside its own scope
+     */
+    fn compile_block(&mut self, body: &[Box<dyn AstBase>]) {
+        if defines_in_scope(body) {
+            self.enter_scope();
+            self.compile_body(body);
+            self.exit_scope();
+        } else {
+            self.compile_body(body);
+        }
+    }
+
+    /*
+     * Compile some conditions, returns the jump to patch with the position
+     * where the execution continues when they are not all true
+     */
+    fn compile_conditions(&mut self, conditions: &[ResultExpression]) -> usize {
+        for condition in conditions {
+            self.compile_value(&condition.left);
+            self.compile_value(&condition.right);
+            self.emit(Instruction::Compare(condition.relation));
+        }
+
+        self.emit(Instruction::JumpIfNotAll {
+            conditions: conditions.len(),
+            target: 0,
+        })
+    }
+
+    fn compile_operation(&mut self, operation: &(dyn AstBase + 'static)) {
+        match operation.get_type() {
+            Ops::Break => {
+                if let Some(depth) = self.loops.last().map(|block| block.depth) {
+                    self.exit_scopes(depth - 1);
+                    let jump = self.emit(Instruction::Jump(0));
+                    self.loops.last_mut().unwrap().exits.push(jump);
+                } else {
+                    // Breaking outside a loop just ends the current function
+                    self.emit(Instruction::Nothing);
+                    self.emit(Instruction::Return);
+                }
+            }
+
+            Ops::WhileDef => {
+                let while_block = downcast_val::<While>(operation.as_self());
+
+                let start = self.position();
+                let exit = self.compile_conditions(&while_block.conditions);
+
+                self.loops.push(Block {
+                    depth: self.depth + 1,
+                    exits: vec![exit],
+                });
+                self.compile_block(&while_block.body);
+                self.emit(Instruction::Jump(start));
+
+                let end = self.position();
+                for jump in self.loops.pop().unwrap().exits {
+                    self.patch(jump, end);
+                }
+            }
+
+            Ops::ForDef => {
+                let for_block = downcast_val::<For>(operation.as_self());
+
+                // The generator being iterated is kept in the loop's own scope
+                self.enter_scope();
+                self.compile_value(&for_block.iterable);
+                self.emit(Instruction::Iterate);
+                self.emit(Instruction::Define(ITERATOR.to_string()));
+
+                let start = self.position();
+                let exit = self.emit(Instruction::Next {
+                    iterator: ITERATOR.to_string(),
+                    target: 0,
+                });
+
+                self.loops.push(Block {
+                    depth: self.depth + 1,
+                    exits: vec![exit],
+                });
+
+                self.enter_scope();
+                let slot = self.define_local(&for_block.var_name);
+                self.emit(Instruction::DefineLocal(slot));
+                self.compile_body(&for_block.body);
+                self.exit_scope();
+                self.emit(Instruction::Jump(start));
+
+                let end = self.position();
+                for jump in self.loops.pop().unwrap().exits {
+                    self.patch(jump, end);
+                }
+
+                self.exit_scope();
+            }
+
+            Ops::Yield => {
+                let statement = downcast_val::<YieldStatement>(operation.as_self());
+
+                self.compile_value(&statement.value);
+                self.emit(Instruction::Yield);
+            }
+
+            Ops::Return => {
+                let statement = downcast_val::<ReturnStatement>(operation.as_self());
+
+                if let (Some(fn_call), None) = (statement.get_tail_call(), self.groups.last()) {
+                    for argument in &fn_call.arguments {
+                        self.compile_value(argument);
+                    }
+
+                    if let Some(slot) = self.find_function(&fn_call.fn_name) {
+                        self.emit(Instruction::TailCallLocal {
+                            slot,
+                            arguments: fn_call.arguments.len(),
+                        });
+                    } else {
+                        self.emit(Instruction::TailCall {
+                            name: fn_call.fn_name.clone(),
+                            arguments: fn_call.arguments.len(),
+                        });
+                    }
+                    self.emit(Instruction::Return);
+                    return;
+                }
+
+                self.compile_value(&statement.value);
+
+                if let Some(depth) = self.groups.last().map(|block| block.depth) {
+                    // Return from the expression value
+                    self.exit_scopes(depth);
+                    let jump = self.emit(Instruction::Jump(0));
+                    self.groups.last_mut().unwrap().exits.push(jump);
+                } else {
+                    self.emit(Instruction::Return);
+                }
+            }
+
+            Ops::Try => {
+                let statement = downcast_val::<TryStatement>(operation.as_self());
+
+                self.compile_value(&statement.value);
+                self.emit(Instruction::Try);
+            }
+
+            Ops::IfConditional => {
+                let if_statement = downcast_val::<IfConditional>(operation.as_self());
+
+                let skip = self.compile_conditions(&if_statement.conditions);
+                self.compile_block(&if_statement.body);
+
+                let end = self.position();
+                self.patch(skip, end);
+            }
+
+            Ops::FnDef => {
+                let function = downcast_val::<FnDefinition>(operation.as_self());
+
+                let compiled = Rc::new(CompiledFunction {
+                    definition: function.clone(),
+                    chunk: Rc::new(compile_function(function)),
+                });
+                let slot = self.define_function(&function.def_name);
+
+                self.emit(Instruction::DefineFunction {
+                    function: compiled,
+                    slot,
+                });
+            }
+
+            Ops::VarDef => {
+                let variable = downcast_val::<VarDefinition>(operation.as_self());
+
+                self.compile_value(&variable.assignment);
+                let slot = self.define_local(&variable.def_name);
+                self.emit(Instruction::DefineLocal(slot));
+            }
+
+            Ops::VarAssign => {
+                let variable = downcast_val::<VarAssignment>(operation.as_self());
+
+                self.compile_value(&variable.assignment);
+
+                if let Some(slot) = self.find_local(&variable.var_name) {
+                    self.emit(Instruction::AssignLocal(slot));
+                } else {
+                    self.emit(Instruction::Assign(variable.var_name.clone()));
+                }
+            }
+
+            Ops::FnCall => {
+                let fn_call = downcast_val::<FnCall>(operation.as_self());
+                self.compile_call(fn_call, false);
+            }
+
+            Ops::Spawn => {
+                let spawn = downcast_val::<Spawn>(operation.as_self());
+                self.compile_spawn(spawn, false);
+            }
+
+            Ops::Module => {
+                let module = downcast_val::<Module>(operation.as_self());
+
+                self.emit(Instruction::Import(Box::new(module.clone())));
+                self.forget_slots();
+            }
+
+            // Same as the tree-walking interpreter, every operation of the code is handled above
+            _ => {
+                panic!("Unhandled code operation")
+            }
+        }
+    }
+
+    /*
+     * Compile a call, the functions and variables defined by the chunk are used by their slot
+     */
+    fn compile_call(&mut self, fn_call: &FnCall, keep_result: bool) {
+        for argument in &fn_call.arguments {
+            self.compile_value(argument);
+        }
+
+        let arguments = fn_call.arguments.len();
+
+        let local = match &fn_call.reference_to {
+            Some(reference_to) => self.find_local(reference_to),
+            None => self.find_function(&fn_call.fn_name),
+        };
+
+        let instruction = match (local, &fn_call.reference_to) {
+            (Some(slot), Some(_)) => Instruction::CallMethod {
+                slot,
+                name: fn_call.fn_name.clone(),
+                arguments,
+                keep_result,
+            },
+            (Some(slot), None) => Instruction::CallLocal {
+                slot,
+                arguments,
+                keep_result,
+            },
+            (None, reference_to) => Instruction::Call {
+                name: fn_call.fn_name.clone(),
+                reference_to: reference_to.clone(),
+                arguments,
+                keep_result,
+            },
+        };
+
+        self.emit(instruction);
+    }
+
+    fn compile_spawn(&mut self, spawn: &Spawn, keep_result: bool) {
+        for argument in &spawn.call.arguments {
+            self.compile_value(argument);
+        }
+
+        self.emit(Instruction::Spawn {
+            spawn: Box::new(spawn.clone()),
+            keep_result,
+        });
+    }
+
+    /*
+     * Compile a value, which will end up as one operand in the stack
+     */
+    fn compile_value(&mut self, value: &BoxedValue) {
+        match value.interface {
+            Ops::Boolean | Ops::Number | Ops::String | Ops::Pointer | Ops::None => {
+                self.emit(Instruction::Constant(value.clone()));
+            }
+            Ops::Reference => {
+                let reference = &downcast_val::<Reference>(value.value.as_self()).0;
+                self.compile_reference(reference);
+            }
+            Ops::FnCall => {
+                let fn_call = downcast_val::<FnCall>(value.value.as_self());
+                self.compile_call(fn_call, true);
+            }
+            Ops::NamedArgument => {
+                let argument = downcast_val::<NamedArgument>(value.value.as_self());
+                self.compile_value(&argument.value);
+                self.emit(Instruction::Name(argument.name.clone()));
+            }
+            Ops::Spawn => {
+                let spawn = downcast_val::<Spawn>(value.value.as_self());
+                self.compile_spawn(spawn, true);
+            }
+            Ops::Expression => {
+                let expression = downcast_val::<Expression>(value.value.as_self());
+
+                // The result of a call is given right away instead of storing it in the group's scope
+                if let Some(fn_call) = expression.get_call() {
+                    self.compile_call(fn_call, true);
+                    return;
+                }
+
+                self.enter_scope();
+                self.groups.push(Block {
+                    depth: self.depth,
+                    exits: vec![],
+                });
+
+                let body = match expression.get_call_result() {
+                    // The rest of the group uses the call's result, so it gives nothing if the call doesn't
+                    Some(result) => {
+                        self.compile_value(&result.assignment);
+                        let jump = self.emit(Instruction::JumpIfNothing(0));
+                        self.groups.last_mut().unwrap().exits.push(jump);
+
+                        let slot = self.define_local(&result.def_name);
+                        self.emit(Instruction::DefineLocal(slot));
+
+                        &expression.body[1..]
+                    }
+                    None => &expression.body[..],
+                };
+
+                self.compile_body(body);
+                self.emit(Instruction::Nothing);
+
+                let end = self.position();
+                for jump in self.groups.pop().unwrap().exits {
+                    self.patch(jump, end);
+                }
+
+                self.exit_scope();
+            }
+            _ => {
+                self.emit(Instruction::Nothing);
+            }
+        }
+    }
+
+    /*
+     * Compile a reference, a variable (`a`), a pointer to it (`&a`) or a dereferenced pointer (`*a`)
+     */
+    fn compile_reference(&mut self, reference: &str) {
+        let mutable_pointed = reference.strip_prefix("&mut ");
+        let pointed = mutable_pointed.or_else(|| reference.strip_prefix('&'));
+        let var_name = pointed.unwrap_or_else(|| reference.trim_start_matches('*'));
+
+        let slot = if let Some(slot) = self.find_local(var_name) {
+            slot
+        } else {
+            self.emit(Instruction::Load(reference.to_string()));
+            return;
+        };
+
+        if pointed.is_some() {
+            self.emit(Instruction::LocalPointer {
+                slot,
+                mutable: mutable_pointed.is_some(),
+            });
+        } else if var_name.len() < reference.len() {
+            self.emit(Instruction::LocalDeref {
+                slot,
+                reference: reference.to_string(),
+            });
+        } else {
+            self.emit(Instruction::Local(slot));
+        }
+    }
+}
@@ -8,14 +8,27 @@ use ham_core::{
         Expression,
         ExpressionBase,
     },
+    limits::{
+        Abort,
+        Limits,
+    },
+    permissions::{
+        Capability,
+        Grant,
+        Permissions,
+    },
     stack::Stack,
+    streams::MemoryStream,
+    utils::errors,
 };
 use ham_manager::Manifest;
 use question::Question;
 use std::{
     fs,
     path::Path,
+    process,
     sync::Mutex,
+    time::Duration,
 };
 
 fn commands() -> ArgMatches {
@@ -23,20 +36,176 @@ fn commands() -> ArgMatches {
         .version("0.0.2")
         .author("Marc E. <mespinsanz@gmail.com>")
         .subcommand(App::new("repl"))
+        .subcommand(
+            App::new("check")
+                .about("Checks the code for errors without running it.")
+                .arg(Arg::new("file").about("File to check.")),
+        )
         .subcommand(
             App::new("run")
                 .arg(Arg::new("file").about("Live code interpreter."))
+                .arg(
+                    Arg::new("args")
+                        .about("Arguments given to the script, after --.")
+                        .multiple(true)
+                        .last(true),
+                )
                 .arg(
                     Arg::new("show_ast_tree")
                         .about("Displays the AST Tree of the code.")
                         .takes_value(false)
                         .short('t')
                         .long("show-ast-tree"),
-                ),
+                )
+                .arg(
+                    Arg::new("from_ast")
+                        .about("Runs the AST Tree read from a JSON file, as displayed by --show-ast-tree.")
+                        .takes_value(true)
+                        .long("from-ast"),
+                )
+                .arg(
+                    Arg::new("show_optimized_ast_tree")
+                        .about("Displays the AST Tree of the code after optimizing it.")
+                        .takes_value(false)
+                        .long("show-optimized-ast-tree"),
+                )
+                .arg(
+                    Arg::new("vm")
+                        .about("Runs the code with the bytecode VM instead of the tree-walking interpreter.")
+                        .takes_value(false)
+                        .long("vm"),
+                )
+                .arg(
+                    Arg::new("deterministic")
+                        .about("Runs the code with a virtual clock, so every run gives the same output.")
+                        .takes_value(false)
+                        .long("deterministic"),
+                )
+                .arg(
+                    Arg::new("max_instructions")
+                        .about("Maximum number of instructions to evaluate.")
+                        .takes_value(true)
+                        .long("max-instructions"),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .about("Maximum execution time, in milliseconds.")
+                        .takes_value(true)
+                        .long("timeout"),
+                )
+                .arg(
+                    Arg::new("max_call_depth")
+                        .about("Maximum number of nested function calls, 1000 by default.")
+                        .takes_value(true)
+                        .long("max-call-depth"),
+                )
+                .arg(
+                    Arg::new("max_variables")
+                        .about("Maximum number of variables held at the same time.")
+                        .takes_value(true)
+                        .long("max-variables"),
+                )
+                .arg(
+                    Arg::new("sandbox")
+                        .about("Deny all permissions except the ones explicitly allowed.")
+                        .takes_value(false)
+                        .long("sandbox"),
+                )
+                .arg(permission_arg(
+                    "allow-read",
+                    "Allow reading files (and importing modules), optionally only from the given paths.",
+                ))
+                .arg(permission_arg(
+                    "allow-write",
+                    "Allow writing files, optionally only into the given paths.",
+                ))
+                .arg(permission_arg(
+                    "allow-env",
+                    "Allow accessing environment variables, optionally only the given ones.",
+                ))
+                .arg(permission_arg(
+                    "allow-run",
+                    "Allow running subprocesses, optionally only the given commands.",
+                )),
         )
         .get_matches()
 }
 
+/*
+ * Argument to grant a permission, ex: --allow-read or --allow-read=./data,./config
+ */
+fn permission_arg<'a>(name: &'a str, about: &'a str) -> Arg<'a> {
+    Arg::new(name)
+        .about(about)
+        .long(name)
+        .takes_value(true)
+        .min_values(0)
+        .require_equals(true)
+        .use_delimiter(true)
+}
+
+/*
+ * Read the permissions from the arguments
+ *
+ * Everything is allowed unless the sandbox is enabled, which happens with --sandbox
+ * or when granting any permission explicitly
+ */
+fn permissions_from(matches: &ArgMatches) -> Permissions {
+    let capabilities = [
+        ("allow-read", Capability::Read),
+        ("allow-write", Capability::Write),
+        ("allow-env", Capability::Env),
+        ("allow-run", Capability::Run),
+    ];
+
+    let sandboxed = matches.is_present("sandbox")
+        || capabilities
+            .iter()
+            .any(|(name, _)| matches.is_present(name));
+
+    if !sandboxed {
+        return Permissions::allow_all();
+    }
+
+    let mut permissions = Permissions::deny_all();
+
+    for (name, capability) in capabilities.iter() {
+        if matches.is_present(name) {
+            let targets = matches
+                .values_of(name)
+                .map(|targets| targets.map(|target| target.to_string()).collect())
+                .unwrap_or_default();
+
+            permissions.grant(*capability, Grant::from_targets(targets));
+        }
+    }
+
+    permissions
+}
+
+/*
+ * Read the execution limits from the arguments
+ */
+fn limits_from(matches: &ArgMatches) -> Limits {
+    let number_of = |name: &str| -> Option<u64> {
+        matches.value_of(name).map(|value| {
+            value.parse::<u64>().unwrap_or_else(|_| {
+                eprintln!("'{}' is not a valid number for --{}", value, name);
+                process::exit(1)
+            })
+        })
+    };
+
+    Limits {
+        max_instructions: number_of("max_instructions"),
+        timeout: number_of("timeout").map(Duration::from_millis),
+        max_call_depth: number_of("max_call_depth")
+            .map(|max| max as usize)
+            .or(Limits::default().max_call_depth),
+        max_variables: number_of("max_variables").map(|max| max as usize),
+    }
+}
+
 fn run_repl() {
     let cli_welcome = format!(":: ham REPL ({}) ::", env!("CARGO_PKG_VERSION"));
     let cli_tip = "Use Ctrl+C to exit.";
@@ -64,7 +233,7 @@ fn run_repl() {
                 let tree = Mutex::new(Expression::new());
 
                 // Tree
-                ham_core::move_tokens_into_ast(tokens, &tree, cwd.clone());
+                ham_core::move_tokens_into_ast(tokens, &tree, cwd.clone(), &stack);
 
                 // Run the code
                 ham_core::run_ast(&tree, &stack);
@@ -76,55 +245,139 @@ fn run_repl() {
     }
 }
 
+/*
+ * Path of the file to run, the project's `src/main.ham` is used if no file is given
+ */
+fn entry_file(filename: Option<&str>) -> String {
+    let cwd = std::env::current_dir().unwrap().display().to_string();
+
+    if let Some(filename) = filename {
+        format!("{}/{}", cwd, filename.to_string())
+    } else {
+        format!("{}/src/main.ham", cwd)
+    }
+}
+
+/*
+ * Load the variables of the project's `.env` file, only the ones the code can read
+ */
+fn load_env_file(filename: Option<&str>, stack: &Mutex<Stack>) {
+    let filename = entry_file(filename);
+    let filedir = Path::new(&filename).parent().unwrap();
+    let permissions = stack.lock().unwrap().permissions.clone();
+
+    if let Err(error) = ham_core::stdlib::env::load_env_file(filedir, &permissions) {
+        eprintln!("Could not load the .env file: {}", error);
+    }
+}
+
+/*
+ * Parse a file into the given context, the project's `src/main.ham` is used if no file is given
+ */
+fn parse_file(
+    filename: Option<&str>,
+    global_context: Expression,
+    stack: &Mutex<Stack>,
+) -> Mutex<Expression> {
+    // CWD
+    let cwd = std::env::current_dir().unwrap().display().to_string();
+
+    // Manifest file
+    let _manifest = {
+        let manifest_file = format!("{}/ham.yml", cwd);
+
+        Manifest::from_file(manifest_file.as_str())
+    };
+
+    // Main file
+    let filename = entry_file(filename);
+
+    // Main file content
+    let filecontent =
+        fs::read_to_string(filename.as_str()).expect("Something went wrong reading the file");
+
+    // Ast tree root
+    let tree = Mutex::new(global_context);
+
+    // Tree
+    ham_core::modules::parse_entry_file(Path::new(&filename), filecontent, &tree, stack);
+
+    tree
+}
+
+/*
+ * Read an AST Tree previously saved as JSON
+ */
+fn read_ast(filename: &str) -> Mutex<Expression> {
+    let content = fs::read_to_string(filename).unwrap_or_else(|error| {
+        eprintln!("Could not read '{}': {}", filename, error);
+        process::exit(1)
+    });
+
+    let tree = serde_json::from_str(&content).unwrap_or_else(|error| {
+        eprintln!("'{}' is not a valid AST Tree: {}", filename, error);
+        process::exit(1)
+    });
+
+    Mutex::new(tree)
+}
+
 fn main() {
     let matches = commands();
 
     match matches.subcommand() {
-        Some(("run", run_matches)) => {
-            // CWD
-            let cwd = std::env::current_dir().unwrap().display().to_string();
+        Some(("check", check_matches)) => {
+            // Global context
+            let global_context = Expression::new();
 
-            let filename = run_matches.value_of("file");
+            // Memory stack
+            let stack = Mutex::new(Stack::new(global_context.expr_id));
 
-            // Manifest file
-            let _manifest = {
-                let manifest_file = format!("{}/ham.yml", cwd);
+            // Errors are collected to know if any was found, including the ones found while parsing
+            let errors = MemoryStream::default();
+            stack.lock().unwrap().streams.stderr = errors.handle();
 
-                Manifest::from_file(manifest_file.as_str())
-            };
+            // Tree
+            let tree = parse_file(check_matches.value_of("file"), global_context, &stack);
 
-            // Main file
-            let filename = if let Some(filename) = filename {
-                format!("{}/{}", cwd, filename.to_string())
-            } else {
-                format!("{}/src/main.ham", cwd)
-            };
+            ham_core::mutability::check_mutability(&tree, &stack);
+            ham_core::typecheck::check_types(&tree, &stack);
 
-            // Main file content
-            let filecontent = fs::read_to_string(filename.as_str())
-                .expect("Something went wrong reading the file");
+            let errors = errors.contents();
 
-            // Tokens
-            let tokens = ham_core::get_tokens(filecontent);
+            if !errors.is_empty() {
+                eprint!("{}", errors);
+                process::exit(1);
+            }
 
+            println!("No errors found.");
+        }
+        Some(("run", run_matches)) => {
             // Global context
             let global_context = Expression::new();
 
             // Memory stack
-            let stack = Mutex::new(Stack::new(global_context.expr_id.clone()));
-
-            // Ast tree root
-            let tree = Mutex::new(global_context);
+            let stack = Mutex::new(Stack::new(global_context.expr_id));
+            stack.lock().unwrap().limits = limits_from(run_matches);
+            stack.lock().unwrap().permissions = permissions_from(run_matches);
 
-            // File's folder
-            let mut filedir = Path::new(filename.as_str()).ancestors();
-            filedir.next().unwrap();
+            if let Some(args) = run_matches.values_of("args") {
+                stack.lock().unwrap().args = args.map(|arg| arg.to_string()).collect();
+            }
 
-            let filedir = filedir.next().unwrap();
-            let filedir = filedir.to_str().unwrap().to_string();
+            if run_matches.is_present("deterministic") {
+                stack.lock().unwrap().set_deterministic();
+            }
 
             // Tree
-            ham_core::move_tokens_into_ast(tokens, &tree, filedir);
+            let tree = if let Some(ast_file) = run_matches.value_of("from_ast") {
+                read_ast(ast_file)
+            } else {
+                // Variables of the project's `.env` file
+                load_env_file(run_matches.value_of("file"), &stack);
+
+                parse_file(run_matches.value_of("file"), global_context, &stack)
+            };
 
             if run_matches.is_present("show_ast_tree") {
                 println!(
@@ -133,7 +386,42 @@ fn main() {
                 );
             }
 
-            ham_core::run_ast(&tree, &stack);
+            // Reject mutations of bindings which are not mutable
+            if !ham_core::mutability::check_mutability(&tree, &stack) {
+                process::exit(1);
+            }
+
+            // Optimize the tree
+            ham_core::optimize::optimize_ast(&tree);
+
+            if run_matches.is_present("show_optimized_ast_tree") {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&tree.lock().unwrap().clone()).unwrap()
+                );
+            }
+
+            let res = if run_matches.is_present("vm") {
+                ham_core::vm::try_run_vm(&tree, &stack)
+            } else {
+                ham_core::try_run_ast(&tree, &stack)
+            };
+
+            match res {
+                // The code asked to stop
+                Err(Abort::Exit(code)) => process::exit(code),
+                // It was already reported
+                Err(Abort::Unhandled(_)) => process::exit(1),
+                _ => {}
+            }
+
+            if let Err(error) = res {
+                stack
+                    .lock()
+                    .unwrap()
+                    .raise_error(errors::CODES::ExecutionAborted, vec![error.to_string()]);
+                process::exit(1);
+            }
         }
         Some(("repl", _)) => {
             run_repl();
@@ -0,0 +1,53 @@
+mod common;
+
+use common::run;
+
+const CODE: &str = r#"
+fn greet(name, greeting = "Hello", end = format("{}!", name)) {
+    println(greeting, ", ", end)
+}
+
+greet("ham")
+greet("ham", "Hi")
+greet("ham", end = "you")
+greet(greeting = "Bye", name = "ham")
+
+fn count(first, ...rest) {
+    println(first, " ", rest.len(), " ", rest)
+}
+
+count(1)
+count(1, 2, "three")
+
+fn pair(a, b) {
+    println(a, b)
+}
+
+pair(1)
+pair(1, 2, 3)
+pair(1, c = 2)
+pair(1, a = 2)
+
+fn broken(a b) {
+}
+"#;
+
+/*
+ * Make sure arguments can be left out, given by name or collected, and wrong calls are rejected
+ */
+#[test]
+pub fn arguments_are_bound() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(CODE, with_vm);
+
+        assert_eq!(
+            "Hello, ham!\nHi, ham!\nHello, you\nBye, ham!\n1 0 []\n1 2 [2, three]\n",
+            output
+        );
+        assert!(errors.contains("Expected a comma before argument 'b' in line 27"));
+        assert!(errors.contains("Function 'pair' takes 2 argument(s) but 1 were given"));
+        assert!(errors.contains("Function 'pair' takes 2 argument(s) but 3 were given"));
+        assert!(errors.contains("Function 'pair' has no argument named 'c'"));
+        assert!(errors.contains("Argument 'a' of function 'pair' was given more than once"));
+    }
+}
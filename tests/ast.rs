@@ -0,0 +1,68 @@
+mod common;
+
+use common::Program;
+use ham_core::ast_types::expression::Expression;
+
+const CODE: &str = r#"
+fn count(mut from, to = 3, ...rest) -> generator {
+    while from != to {
+        yield from
+        from.mut_sum(1)
+    }
+}
+
+for n in count(1) {
+    if n == 2 {
+        println("two")
+    }
+    println(n)
+}
+
+let mut total = 0
+total = total.sum(15)
+println(format("{}", total))
+
+let task = spawn count(0, to = 2)
+println(task.await().next())
+"#;
+
+/*
+ * Make sure a tree read back from JSON is the same and runs the same
+ */
+#[test]
+pub fn ast_is_read_back_from_json() {
+    for with_vm in [false, true] {
+        let program = Program::default();
+        program.parse(CODE);
+        let json = serde_json::to_string(&program.tree.lock().unwrap().clone()).unwrap();
+
+        let read_tree: Expression = serde_json::from_str(&json).unwrap();
+        assert_eq!(json, serde_json::to_string(&read_tree).unwrap());
+
+        program.run(with_vm).unwrap();
+        assert_eq!("1\ntwo\n2\n15\n0\n", program.stdout.contents());
+
+        let read_program = Program::default();
+        *read_program.tree.lock().unwrap() = read_tree;
+        read_program.run(with_vm).unwrap();
+        assert_eq!("1\ntwo\n2\n15\n0\n", read_program.stdout.contents());
+    }
+
+    // Nodes are tagged with their type
+    let tree: Result<Expression, _> =
+        serde_json::from_str(r#"{ "body": [{ "Number": 1 }], "token_type": "Expression" }"#);
+    assert!(tree
+        .unwrap_err()
+        .to_string()
+        .contains("Number is not a statement"));
+
+    // Values and conditions are not read as statements, they can't be run
+    for body in [
+        r#"[{ "LeftAssign": { "interface": "Number", "value": 1 } }]"#,
+        r#"[{ "IfConditional": { "conditions": [], "body": [{ "ResExpression": { "relation": "EqualCondition", "left": { "interface": "Number", "value": 1 }, "right": { "interface": "Number", "value": 1 } } }] } }]"#,
+    ] {
+        let json = format!(r#"{{ "body": {}, "token_type": "Expression" }}"#, body);
+        let tree: Result<Expression, _> = serde_json::from_str(&json);
+        assert!(tree.unwrap_err().to_string().contains("is not a statement"));
+    }
+}
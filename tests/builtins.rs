@@ -0,0 +1,46 @@
+mod common;
+
+use common::run;
+
+/*
+ * Make sure sums bigger than the biggest number are reported instead of panicking
+ */
+#[test]
+pub fn overflowing_sums_are_reported() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            let mut n = 18446744073709551615
+            let big = n.sum(1)
+            n.mut_sum(2)
+            println(n)
+            "#,
+            with_vm,
+        );
+
+        assert_eq!("18446744073709551615\n", output);
+        assert!(errors
+            .contains("The sum of 18446744073709551615 and 1 is bigger than the biggest number"));
+        assert!(errors
+            .contains("The sum of 18446744073709551615 and 2 is bigger than the biggest number"));
+    }
+}
+
+/*
+ * Make sure `format` reports a missing template instead of panicking
+ */
+#[test]
+pub fn format_needs_a_template() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            let empty = format()
+            println(format("{} {}", "a", "b"))
+            "#,
+            with_vm,
+        );
+
+        assert_eq!("a b\n", output);
+        assert!(errors.contains("Function 'format' takes at least 1 argument(s) but 0 were given"));
+    }
+}
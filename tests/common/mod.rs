@@ -0,0 +1,118 @@
+// Every test file builds its own copy of these helpers and only uses some of them
+#![allow(dead_code)]
+
+use ham_core::{
+    ast_types::expression::{
+        Expression,
+        ExpressionBase,
+    },
+    get_tokens,
+    limits::Abort,
+    move_tokens_into_ast,
+    stack::Stack,
+    streams::{
+        MemoryStream,
+        Streams,
+    },
+    try_run_ast,
+    vm::try_run_vm,
+};
+use std::{
+    io::Cursor,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+/*
+ * Some code together with the stack it runs in, its output is kept in memory
+ */
+pub struct Program {
+    pub tree: Mutex<Expression>,
+    pub stack: Mutex<Stack>,
+    pub stdout: MemoryStream,
+    pub stderr: MemoryStream,
+}
+
+impl Default for Program {
+    fn default() -> Self {
+        let stdout = MemoryStream::default();
+        let stderr = MemoryStream::default();
+
+        let global_context = Expression::new();
+        let stack = Mutex::new(Stack::new(global_context.expr_id));
+        stack.lock().unwrap().streams = Streams::new(
+            stdout.handle(),
+            stderr.handle(),
+            Arc::new(Mutex::new(Cursor::new(""))),
+        );
+
+        Self {
+            tree: Mutex::new(global_context),
+            stack,
+            stdout,
+            stderr,
+        }
+    }
+}
+
+impl Program {
+    /*
+     * Parse some code, its imports are resolved from the given directory
+     */
+    pub fn parse_in(&self, code: &str, directory: &str) {
+        move_tokens_into_ast(
+            get_tokens(code.to_string()),
+            &self.tree,
+            directory.to_string(),
+            &self.stack,
+        );
+    }
+
+    pub fn parse(&self, code: &str) {
+        self.parse_in(code, ".");
+    }
+
+    /*
+     * Run the parsed code with the tree-walking interpreter or the VM
+     */
+    pub fn run(&self, with_vm: bool) -> Result<(), Abort> {
+        let res = if with_vm {
+            try_run_vm(&self.tree, &self.stack)
+        } else {
+            try_run_ast(&self.tree, &self.stack)
+        };
+
+        res.map(|_| ())
+    }
+
+    /*
+     * Everything written to stdout and stderr so far
+     */
+    pub fn output(&self) -> (String, String) {
+        (self.stdout.contents(), self.stderr.contents())
+    }
+}
+
+/*
+ * Run some code with the tree-walking interpreter or the VM, returns its output and errors
+ */
+pub fn run(code: &str, with_vm: bool) -> (String, String) {
+    let program = Program::default();
+    program.parse(code);
+    // Aborted executions are checked by the tests about them
+    program.run(with_vm).ok();
+    program.output()
+}
+
+/*
+ * Absolute path of a folder inside the examples
+ */
+pub fn examples(folder: &str) -> String {
+    format!(
+        "{}/examples{}",
+        std::env::current_dir().unwrap().display(),
+        folder
+    )
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::Program;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+const CODE: &str = r#"
+let messages = channel()
+
+fn ticker(name, pause) {
+    let mut ticks = 0
+    while ticks != 3 {
+        wait(pause)
+        messages.send(format("{} {}", name, ticks))
+        ticks.mut_sum(1)
+    }
+}
+
+spawn ticker("hours", 3600000)
+spawn ticker("minutes", 60000)
+spawn ticker("tie", 60000)
+
+let mut received = 0
+while received != 9 {
+    println(messages.recv())
+    received.mut_sum(1)
+}
+"#;
+
+/*
+ * Run the code in deterministic mode with the tree-walking interpreter or the VM, returns
+ * its output and the AST Tree as JSON
+ */
+fn run(with_vm: bool) -> (String, String) {
+    let program = Program::default();
+    program.stack.lock().unwrap().set_deterministic();
+    program.parse(CODE);
+
+    let ast = serde_json::to_string(&program.tree.lock().unwrap().clone()).unwrap();
+
+    program.run(with_vm).unwrap();
+
+    (program.stdout.contents(), ast)
+}
+
+/*
+ * Make sure time only passes when the code waits, and runs give the same output
+ */
+#[test]
+pub fn deterministic_runs_are_reproducible() {
+    for with_vm in [false, true] {
+        let started = Instant::now();
+        let (output, ast) = run(with_vm);
+
+        // Hours of waiting happen instantly
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(
+            "minutes 0\ntie 0\nminutes 1\ntie 1\nminutes 2\ntie 2\nhours 0\nhours 1\nhours 2\n",
+            output
+        );
+        assert_eq!((output, ast.clone()), run(with_vm));
+
+        // Internal IDs are not part of the output
+        assert!(!ast.contains("expr_id"));
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::run;
+
+const CODE: &str = r#"
+fn numbers(from, to) {
+    let mut n = from
+    while n != to {
+        yield n
+        n.mut_sum(1)
+    }
+}
+
+for n in numbers(1, 4) {
+    println(n)
+}
+
+let gen = numbers(5, 7)
+println(gen.next())
+println(gen.done())
+println(gen.next())
+println(gen.done())
+
+fn pairs() {
+    for a in numbers(0, 2) {
+        for b in numbers(0, 2) {
+            yield format("{}{}", a, b)
+        }
+    }
+}
+
+for pair in pairs() {
+    if pair == "11" {
+        break
+    }
+    println(pair)
+}
+
+fn list(...items) {
+    return items
+}
+
+for item in list("a", "b") {
+    println(item)
+}
+
+gen.next()
+yield 3
+for x in 5 {
+}
+"#;
+
+/*
+ * Make sure generators are resumed lazily and can be iterated
+ */
+#[test]
+pub fn generators_yield_their_values() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(CODE, with_vm);
+
+        assert_eq!("1\n2\n3\n5\nfalse\n6\ntrue\n00\n01\n10\na\nb\n", output);
+        assert!(errors.contains("The generator has no more values"));
+        assert!(errors.contains("Cannot yield '3' outside of a function"));
+        assert!(errors.contains("Cannot iterate over a value of type 'number'"));
+    }
+}
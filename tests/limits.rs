@@ -0,0 +1,178 @@
+mod common;
+
+use common::Program;
+use ham_core::limits::{
+    Abort,
+    LimitError,
+    Limits,
+    DEFAULT_MAX_CALL_DEPTH,
+    MAX_CALL_DEPTH,
+};
+use std::time::{
+    Duration,
+    Instant,
+};
+
+/*
+ * Run some code with the given limits, with the tree-walking interpreter or the VM
+ */
+fn run_with_limits(code: &str, limits: Limits, with_vm: bool) -> Result<(), Abort> {
+    let program = Program::default();
+    program.stack.lock().unwrap().limits = limits;
+    program.parse(code);
+    program.run(with_vm)
+}
+
+/*
+ * Make sure never-ending code is aborted with the limit that was hit
+ */
+#[test]
+pub fn limits_abort_the_execution() {
+    const LOOP: &str = "let c = 0 while c != 1 { let b = 2 }";
+    const EMPTY_LOOP: &str = "let c = 0 while c != 1 {}";
+    // Not a tail call, so every call is nested
+    const RECURSION: &str = "fn calc(v){ let r = calc(v) return r } let _ = calc(0)";
+
+    for with_vm in [false, true] {
+        let res = run_with_limits(
+            LOOP,
+            Limits {
+                max_instructions: Some(100),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Limit(LimitError::Instructions(100))), res);
+
+        let timeout = Duration::from_millis(50);
+        let res = run_with_limits(
+            LOOP,
+            Limits {
+                timeout: Some(timeout),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Limit(LimitError::Timeout(timeout))), res);
+
+        // Loops which don't evaluate anything are stopped too
+        let res = run_with_limits(
+            EMPTY_LOOP,
+            Limits {
+                max_instructions: Some(100),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Limit(LimitError::Instructions(100))), res);
+
+        let res = run_with_limits(
+            EMPTY_LOOP,
+            Limits {
+                timeout: Some(timeout),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Limit(LimitError::Timeout(timeout))), res);
+
+        let res = run_with_limits(
+            RECURSION,
+            Limits {
+                max_call_depth: Some(50),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Limit(LimitError::CallDepth(50))), res);
+
+        // Recursions are aborted even when no limit is given, instead of overflowing the native stack
+        let res = run_with_limits(RECURSION, Limits::default(), with_vm);
+        assert_eq!(
+            Err(Abort::Limit(LimitError::CallDepth(DEFAULT_MAX_CALL_DEPTH))),
+            res
+        );
+
+        // Deeper limits than the interpreter can hold, or none at all, are the most it can hold
+        for max_call_depth in [Some(1_000_000), None] {
+            let res = run_with_limits(
+                RECURSION,
+                Limits {
+                    max_call_depth,
+                    ..Limits::default()
+                },
+                with_vm,
+            );
+            assert_eq!(Err(Abort::Limit(LimitError::CallDepth(MAX_CALL_DEPTH))), res);
+        }
+
+        let res = run_with_limits(
+            RECURSION,
+            Limits {
+                max_variables: Some(10),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Limit(LimitError::Variables(10))), res);
+
+        // Exiting is not a limit
+        let res = run_with_limits(
+            r#"import process from "std:process" process.exit(3)"#,
+            Limits::default(),
+            with_vm,
+        );
+        assert_eq!(Err(Abort::Exit(3)), res);
+
+        // Code which finishes is not affected
+        let res = run_with_limits(
+            "let c = 0",
+            Limits {
+                max_instructions: Some(100),
+                ..Limits::default()
+            },
+            with_vm,
+        );
+        assert_eq!(Ok(()), res);
+    }
+}
+
+/*
+ * Make sure code waiting for something is stopped once the timeout is hit, instead of when it's done waiting
+ */
+#[test]
+pub fn timeout_stops_waiting_code() {
+    const WAIT: &str = "wait(5000)";
+    const SLEEP: &str = r#"import time from "std:time" time.sleep(5000)"#;
+    const RECV: &str = r#"
+let messages = channel()
+
+fn slow() {
+    wait(5000)
+    messages.send("done")
+}
+
+spawn slow()
+
+let message = messages.recv()
+"#;
+
+    let timeout = Duration::from_millis(50);
+
+    for with_vm in [false, true] {
+        for code in [WAIT, SLEEP, RECV] {
+            let started = Instant::now();
+            let res = run_with_limits(
+                code,
+                Limits {
+                    timeout: Some(timeout),
+                    ..Limits::default()
+                },
+                with_vm,
+            );
+
+            assert_eq!(Err(Abort::Limit(LimitError::Timeout(timeout))), res);
+            assert!(started.elapsed() < Duration::from_secs(1));
+        }
+    }
+}
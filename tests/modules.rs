@@ -0,0 +1,207 @@
+mod common;
+
+use common::{
+    examples,
+    Program,
+};
+use ham_core::{
+    limits::{
+        Abort,
+        LimitError,
+        DEFAULT_MAX_CALL_DEPTH,
+    },
+    modules::parse_entry_file,
+};
+use std::{
+    fs,
+    path::Path,
+};
+
+/*
+ * Run some code from the modules example project with the tree-walking interpreter or the VM,
+ * returns its output and errors
+ */
+fn run(code: &str, with_vm: bool) -> (String, String) {
+    let program = Program::default();
+    program.parse_in(code, &examples("/2_modules/src"));
+    program.run(with_vm).ok();
+    program.output()
+}
+
+/*
+ * Run a file of the modules example project as the entry file of the program
+ */
+fn run_file(path: &Path, with_vm: bool) -> (String, String) {
+    let program = Program::default();
+    let code = fs::read_to_string(path).unwrap();
+    parse_entry_file(path, code, &program.tree, &program.stack);
+    program.run(with_vm).ok();
+    program.output()
+}
+
+/*
+ * Make sure modules are found relative to the importing file and the project root
+ */
+#[test]
+pub fn imports_are_resolved_from_the_importing_file() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"import greet from "./lib/greet" greet.hello("World")"#,
+            with_vm,
+        );
+        assert_eq!("Hello World!\n", output);
+        assert_eq!("", errors);
+
+        let (output, _) = run(
+            r#"import shout from "@/src/text/shout" println(shout.loud("Hey"))"#,
+            with_vm,
+        );
+        assert_eq!("Hey!\n", output);
+
+        let (_, errors) = run(r#"import words from "./words""#, with_vm);
+        assert!(errors.contains("There is no module './words'"));
+        assert!(errors.contains("2_modules/src/words, "));
+        assert!(errors.contains("2_modules/src/words.ham"));
+    }
+}
+
+/*
+ * Make sure modules are parsed once, and import cycles are reported
+ */
+#[test]
+pub fn modules_are_parsed_once() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import a from "./cycle/a"
+            import again from "./cycle/a.ham"
+            println(again.name())
+            "#,
+            with_vm,
+        );
+        assert_eq!("a\n", output);
+
+        // The cycle is only found the first time
+        assert_eq!(1, errors.matches("Modules import each other").count());
+        assert!(errors.contains("cycle/a.ham -> "));
+        assert!(errors.contains("cycle/b.ham -> "));
+    }
+}
+
+/*
+ * Make sure cycles through the entry file are found from it, instead of running it again as a module
+ */
+#[test]
+pub fn entry_file_is_part_of_import_cycles() {
+    let a = fs::canonicalize("examples/2_modules/src/cycle/a.ham").unwrap();
+    let b = fs::canonicalize("examples/2_modules/src/cycle/b.ham").unwrap();
+
+    for with_vm in [false, true] {
+        let (_, errors) = run_file(&a, with_vm);
+
+        assert_eq!(1, errors.matches("Modules import each other").count());
+        assert!(errors.contains(&format!(
+            "{} -> {} -> {}",
+            a.display(),
+            b.display(),
+            a.display()
+        )));
+    }
+}
+
+/*
+ * Make sure only the public items are exported, and the code of modules runs once
+ */
+#[test]
+pub fn modules_export_public_items() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import counter from "./lib/counter"
+            import again from "./lib/counter"
+
+            println(counter.increment())
+            println(again.increment())
+            println(counter.count)
+
+            again.add(1)
+            let hidden = counter.hidden
+            "#,
+            with_vm,
+        );
+
+        assert_eq!("counter loaded\n1\n2\n2\n", output);
+        assert!(errors.contains("Function 'add' was not found"));
+        assert!(errors.contains("Variable 'counter.hidden' was not found"));
+    }
+}
+
+/*
+ * Make sure the items of modules can be imported by their name, with an alias or all together
+ */
+#[test]
+pub fn modules_items_are_imported_by_name() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import { increment, count as total } from "./lib/counter"
+            import * as counter from "./lib/counter"
+
+            let first = increment()
+            println(counter.increment())
+            println(total)
+            "#,
+            with_vm,
+        );
+
+        assert_eq!("counter loaded\n2\n2\n", output);
+        assert_eq!("", errors);
+
+        let (_, errors) = run(r#"import { hidden } from "./lib/counter""#, with_vm);
+        assert!(errors.contains("'hidden' is not exported by the module"));
+    }
+}
+
+/*
+ * Make sure functions of modules see their module's variables when they are tail called
+ */
+#[test]
+pub fn tail_calls_enter_the_module() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import { increment } from "./lib/counter"
+
+            fn next() {
+                return increment()
+            }
+
+            println(next())
+            println(next())
+            "#,
+            with_vm,
+        );
+
+        assert_eq!("counter loaded\n1\n2\n", output);
+        assert_eq!("", errors);
+    }
+}
+
+/*
+ * Make sure recursions into functions of modules are aborted by the call depth limit
+ */
+#[test]
+pub fn module_recursions_are_limited() {
+    for with_vm in [false, true] {
+        let program = Program::default();
+        program.parse_in(
+            r#"import { dive } from "./lib/recursion" let deepest = dive(0)"#,
+            &examples("/2_modules/src"),
+        );
+
+        assert_eq!(
+            Err(Abort::Limit(LimitError::CallDepth(DEFAULT_MAX_CALL_DEPTH))),
+            program.run(with_vm)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::Program;
+use ham_core::mutability::check_mutability;
+
+/*
+ * Check some code, returns the reported errors if it was rejected
+ */
+fn check(code: &str) -> Option<String> {
+    let program = Program::default();
+    program.parse(code);
+
+    if check_mutability(&program.tree, &program.stack) {
+        None
+    } else {
+        Some(program.stderr.contents())
+    }
+}
+
+/*
+ * Make sure only mutable bindings can be mutated
+ */
+#[test]
+pub fn immutable_bindings_are_rejected() {
+    const VALID: &str = r#"
+const LIMIT = 3
+let mut a = 1
+a = 2
+a.mut_sum(1)
+
+fn inc(mut value){
+    value.mut_sum(1)
+    return value
+}
+
+fn reset(pointer){
+    *pointer = 0
+}
+
+reset(&mut a)
+let b = &mut a
+*b = 5
+"#;
+    assert_eq!(None, check(VALID));
+
+    let errors = check("let a = 1\n\na = 2").unwrap();
+    assert!(errors.contains("it was declared in line 1"));
+    assert!(errors.contains("let mut a = ..."));
+
+    let errors = check("fn inc(value){ value.mut_sum(1) }").unwrap();
+    assert!(errors.contains("fn inc(mut value)"));
+
+    // Constants can't become mutable, they are copied instead
+    let errors = check("const LIMIT = 3 LIMIT = 4").unwrap();
+    assert!(errors.contains("it was declared in line 1"));
+    assert!(errors.contains("let mut limit = LIMIT"));
+    assert!(errors.contains("Help: Constants can't be mutated, copy it into a mutable variable."));
+    assert!(!errors.contains("let mut LIMIT"));
+
+    let errors = check("fn reset(pointer){ *pointer = 0 } let mut a = 1 reset(&a)").unwrap();
+    assert!(errors.contains("reset(&mut a)"));
+
+    let errors = check("let mut a = 1 let b = &a *b = 2").unwrap();
+    assert!(errors.contains("let b = &mut a"));
+    assert!(errors.contains("Help: Point to it with &mut."));
+
+    // Pointing with `&mut` is not enough when the target is not mutable either
+    let errors = check("let a = 1 let b = &a *b = 2").unwrap();
+    assert!(errors.contains("let mut a = ...\n    let b = &mut a"));
+    assert!(errors.contains("Help: Declare it as mutable and point to it with &mut."));
+
+    let errors = check("fn reset(pointer){ *pointer = 0 } let a = 1 reset(&a)").unwrap();
+    assert!(errors.contains("let mut a = ...\n    reset(&mut a)"));
+
+    let errors = check("const LIMIT = 3 let b = &LIMIT *b = 2").unwrap();
+    assert!(errors.contains("let mut limit = LIMIT\n    let b = &mut limit"));
+    assert!(errors.contains("Help: Constants can't be mutated, point to a mutable copy of it."));
+
+    // `&mut` needs a mutable variable
+    assert!(check("let a = 1 let b = &mut a").is_some());
+
+    assert!(check("if true { const LIMIT = 3 }").is_some());
+}
+
+/*
+ * Make sure imported variables keep the mutability they were exported with
+ */
+#[test]
+pub fn imported_bindings_keep_their_mutability() {
+    const MODULE: &str = "./examples/2_modules/src/lib/settings.ham";
+
+    let valid = format!(
+        "import {{ retries as tries }} from \"{0}\" tries = 1 import settings from \"{0}\" settings.retries = 2",
+        MODULE
+    );
+    assert_eq!(None, check(&valid));
+
+    let errors = check(&format!(
+        "import {{ fixed, LIMIT }} from \"{}\" fixed = 2 LIMIT.mut_sum(1)",
+        MODULE
+    ))
+    .unwrap();
+    assert!(errors.contains("Cannot mutate 'fixed'"));
+    assert!(errors.contains("Cannot mutate 'LIMIT'"));
+    assert!(errors.contains("pub let mut fixed = ..."));
+    assert!(errors.contains("let mut limit = LIMIT"));
+
+    let errors = check(&format!(
+        "import settings from \"{}\" settings.fixed = 2",
+        MODULE
+    ))
+    .unwrap();
+    assert!(errors.contains("Cannot mutate 'settings.fixed'"));
+}
+
+/*
+ * Make sure pointers whose origin can't be known before running can only write if they were created with `&mut`
+ */
+#[test]
+pub fn immutable_pointers_cannot_write_at_runtime() {
+    const CODE: &str = r#"
+let x = 1
+let mut y = 1
+
+fn id(pointer){
+    return pointer
+}
+
+fn point(){
+    let hidden = 2
+    return &hidden
+}
+
+let p = id(&x)
+*p = 7
+
+let q = point()
+*q = 8
+
+let r = id(&mut y)
+*r = 3
+
+println(x)
+println(*q)
+println(y)
+"#;
+
+    for with_vm in [false, true] {
+        let program = Program::default();
+        program.parse(CODE);
+
+        // Passes the check, the pointers come from calls
+        assert!(check_mutability(&program.tree, &program.stack));
+
+        program.run(with_vm).ok();
+        let (output, errors) = program.output();
+
+        assert_eq!("1\n2\n3\n", output);
+        assert!(errors.contains("Cannot write through '*p'"));
+        assert!(errors.contains("Cannot write through '*q'"));
+    }
+}
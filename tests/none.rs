@@ -0,0 +1,171 @@
+mod common;
+
+use common::{
+    run,
+    Program,
+};
+use ham_core::limits::Abort;
+
+/*
+ * Like `run`, but also returns whether the execution was aborted
+ */
+fn try_run(code: &str, with_vm: bool) -> (String, String, Result<(), Abort>) {
+    let program = Program::default();
+    program.parse(code);
+
+    let res = program.run(with_vm);
+    let (output, errors) = program.output();
+
+    (output, errors, res)
+}
+
+/*
+ * Make sure `none` can be used as a value and is what functions without a return give
+ */
+#[test]
+pub fn none_is_a_value() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            fn nothing(){
+                let a = 1
+            }
+
+            let empty = none
+            let result = nothing()
+            if result == empty {
+                println(result)
+            }
+
+            import env from "std:env"
+            let unset = env.get("HAM_TEST_NEVER_SET")
+            println(unset)
+            "#,
+            with_vm,
+        );
+        assert_eq!("none\nnone\n", output);
+        assert_eq!("", errors);
+    }
+}
+
+/*
+ * Make sure `?` returns none and errors early and keeps going with the other values
+ */
+#[test]
+pub fn try_returns_early() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import fs from "std:fs"
+
+            fn lookup(path, key){
+                let info = fs.metadata(path)?
+                let value = info.get(key)?
+                return value
+            }
+
+            fn read(path){
+                let text = fs.read_to_string(path)?
+                return text
+            }
+
+            fn check(path){
+                fs.read_to_string(path)?
+                println("unreachable")
+            }
+
+            let missing = lookup(".", "port")
+            let found = lookup(".", "is_dir")
+            println(missing, " ", found)
+
+            let failed = read("ham_missing_file.txt")
+            let message = failed.message()
+            println(message)
+
+            let checked = check("ham_missing_file.txt")
+            let same = checked.message()
+            println(same)
+
+            let stopped = none?
+            println("unreachable")
+            "#,
+            with_vm,
+        );
+        assert_eq!(
+            "none true\nham_missing_file.txt: No such file or directory (os error 2)\nham_missing_file.txt: No such file or directory (os error 2)\n",
+            output
+        );
+        assert_eq!("", errors);
+    }
+}
+
+/*
+ * Make sure using `?` on an error outside of a function reports it and aborts the execution,
+ * which ends the program with a failure
+ */
+#[test]
+pub fn try_outside_functions_reports_errors() {
+    for with_vm in [false, true] {
+        let (output, errors, res) = try_run(
+            r#"
+            import fs from "std:fs"
+
+            let text = fs.read_to_string("ham_missing_file.txt")?
+            println("unreachable")
+            "#,
+            with_vm,
+        );
+        assert_eq!("", output);
+        assert!(errors.contains(
+            "Unhandled error: ham_missing_file.txt: No such file or directory (os error 2)"
+        ));
+        assert_eq!(
+            Err(Abort::Unhandled(
+                "ham_missing_file.txt: No such file or directory (os error 2)".to_string()
+            )),
+            res
+        );
+
+        // `none` just stops it
+        let (_, _, res) = try_run("let stopped = none?", with_vm);
+        assert_eq!(Ok(()), res);
+    }
+}
+
+/*
+ * Make sure every value can tell if it's none or an error, and be replaced by a default when it is
+ */
+#[test]
+pub fn values_are_checked_without_try() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import fs from "std:fs"
+
+            let text = fs.read_to_string("ham_missing_file.txt")
+            let empty = none
+            let number = 3
+
+            if text.is_error() == true {
+                println("failed")
+            }
+
+            if empty.is_none() == true {
+                println("empty")
+            }
+
+            println(number.is_none(), " ", number.is_error(), " ", text.is_none())
+
+            let fallback = text.unwrap_or("default")
+            let nothing = empty.unwrap_or(0)
+            let same = number.unwrap_or(0)
+            println(fallback, " ", nothing, " ", same)
+
+            let wrong = number.unwrap_or()
+            "#,
+            with_vm,
+        );
+        assert_eq!("failed\nempty\nfalse false false\ndefault 0 3\n", output);
+        assert!(errors.contains("Function 'unwrap_or' takes 1 argument(s) but 0 were given"));
+    }
+}
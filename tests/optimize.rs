@@ -0,0 +1,139 @@
+mod common;
+
+use common::{
+    examples,
+    Program,
+};
+use ham_core::optimize::optimize_ast;
+
+const CODE: &str = r#"
+fn o(){
+    return 10
+}
+
+fn log(value){
+    println(value)
+    return value
+}
+
+let a = o().sum(1).sum(1).sum(3)
+let b = log(5).sum(1)
+
+if false {
+    println("never")
+}
+
+while 1 != 1 {
+    println("never")
+}
+
+fn stop(){
+    return 0
+    println("never")
+}
+
+println(a)
+println(b)
+println(format("{} {}", "done", stop()))
+"#;
+
+/*
+ * Run some code, optionally optimizing it first, returns its output, its errors and its AST tree
+ */
+fn run(code: &str, optimize: bool) -> (String, String, String) {
+    run_in(code, ".", optimize)
+}
+
+/*
+ * Like `run`, but imports are resolved from the given directory
+ */
+fn run_in(code: &str, directory: &str, optimize: bool) -> (String, String, String) {
+    let program = Program::default();
+    program.parse_in(code, directory);
+
+    if optimize {
+        optimize_ast(&program.tree);
+    }
+
+    let ast = serde_json::to_string(&program.tree.lock().unwrap().clone()).unwrap();
+
+    program.run(false).ok();
+
+    let (output, errors) = program.output();
+    (output, errors, ast)
+}
+
+/*
+ * Make sure the optimized code does less but behaves the same way
+ */
+#[test]
+pub fn optimizer_folds_values_and_removes_dead_code() {
+    let (output, _, ast) = run(CODE, false);
+    let (optimized_output, _, optimized_ast) = run(CODE, true);
+
+    assert_eq!(output, optimized_output);
+    assert_eq!("5\n15\n6\ndone 0\n", optimized_output);
+
+    // Pure chains are computed
+    assert!(optimized_ast
+        .contains(r#"{"def_name":"a","assignment":{"interface":"Number","value":15},"#));
+
+    // Functions with side effects are still called
+    assert!(optimized_ast.contains(r#""fn_name":"log""#));
+
+    // `if false`, `while` blocks which never run and code after `return` are gone
+    assert!(ast.contains("never"));
+    assert!(!optimized_ast.contains("never"));
+}
+
+/*
+ * Make sure values whose computation fails are left as they are, for the runtime to report the errors
+ */
+#[test]
+pub fn optimizer_keeps_failing_values() {
+    const FAILING: &str = r#"
+    fn overflow(){
+        let n = 18446744073709551615
+        return n.sum(1)
+    }
+
+    let big = overflow()
+    let empty = format()
+    println("done")
+    "#;
+
+    for optimize in [false, true] {
+        let (output, errors, ast) = run(FAILING, optimize);
+
+        assert_eq!("done\n", output);
+        assert!(errors
+            .contains("The sum of 18446744073709551615 and 1 is bigger than the biggest number"));
+        assert!(errors.contains("Function 'format' takes at least 1 argument(s) but 0 were given"));
+        assert!(ast.contains(r#""fn_name":"overflow""#));
+    }
+}
+
+/*
+ * Make sure imported functions are not folded as the local or native functions they shadow
+ */
+#[test]
+pub fn optimizer_respects_imported_functions() {
+    const SHADOWED: &str = r#"
+    fn a(){
+        return 1
+    }
+
+    import { a, format } from "./lib/shadow"
+
+    println(a())
+    println(format("std"))
+    "#;
+
+    for optimize in [false, true] {
+        let (output, errors, ast) = run_in(SHADOWED, &examples("/2_modules/src"), optimize);
+
+        assert_eq!("", errors);
+        assert_eq!("2\nmine\n", output);
+        assert!(ast.contains(r#""fn_name":"format""#));
+    }
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{
+    examples,
+    Program,
+};
+use ham_core::permissions::{
+    Capability,
+    Grant,
+    Permissions,
+};
+use std::{
+    env,
+    fs,
+};
+
+/*
+ * Run some code from the examples folder with the given permissions, returns its output and errors
+ */
+fn run_with_permissions(code: &str, permissions: Permissions) -> (String, String) {
+    let program = Program::default();
+    program.stack.lock().unwrap().permissions = permissions;
+    program.parse_in(code, &examples(""));
+    program.run(false).ok();
+    program.output()
+}
+
+/*
+ * Make sure imports can only read the allowed modules
+ */
+#[test]
+pub fn imports_require_read_permission() {
+    const CODE: &str = "import module from \"./module.ham\" module.x(\"World\")";
+
+    let (stdout, _) = run_with_permissions(CODE, Permissions::allow_all());
+    assert_eq!("Hello World", stdout);
+
+    let (stdout, stderr) = run_with_permissions(CODE, Permissions::deny_all());
+    assert_eq!("", stdout);
+    assert!(stderr.contains("Permission denied to read"));
+
+    let mut permissions = Permissions::deny_all();
+    permissions.grant(
+        Capability::Read,
+        Grant::Only(vec!["./examples".to_string()]),
+    );
+    let (stdout, _) = run_with_permissions(CODE, permissions);
+    assert_eq!("Hello World", stdout);
+
+    let mut permissions = Permissions::deny_all();
+    permissions.grant(Capability::Read, Grant::Only(vec!["./src".to_string()]));
+    let (_, stderr) = run_with_permissions(CODE, permissions);
+    assert!(stderr.contains("Permission denied to read"));
+}
+
+/*
+ * Make sure links inside an allowed folder can't be used to reach what's outside of it
+ */
+#[cfg(unix)]
+#[test]
+pub fn links_cannot_leave_the_allowed_paths() {
+    use std::os::unix::fs::symlink;
+
+    let dir = env::temp_dir().join(format!("ham_links_{}", std::process::id()));
+    let data = dir.join("data");
+    let outside = dir.join("outside");
+    fs::create_dir_all(&data).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(data.join("module.ham"), "pub fn x(){ print(\"inside\") }").unwrap();
+    fs::write(
+        outside.join("module.ham"),
+        "pub fn x(){ print(\"outside\") }",
+    )
+    .unwrap();
+    symlink(&outside, data.join("link")).unwrap();
+    symlink(dir.join("nowhere"), data.join("broken")).unwrap();
+    symlink(&data, dir.join("alias")).unwrap();
+
+    let path = |path: &str| format!("{}/{}", dir.display(), path);
+
+    let mut permissions = Permissions::deny_all();
+    permissions.grant(Capability::Read, Grant::Only(vec![path("data")]));
+
+    assert!(permissions.is_allowed(Capability::Read, &path("data/module.ham")));
+    assert!(permissions.is_allowed(Capability::Read, &path("alias/new.ham")));
+    assert!(!permissions.is_allowed(Capability::Read, &path("data/link/module.ham")));
+    // Going up from a link goes up from where it leads
+    assert!(!permissions.is_allowed(Capability::Read, &path("data/link/../outside/module.ham")));
+    // A link to nothing might be created outside later
+    assert!(!permissions.is_allowed(Capability::Read, &path("data/broken")));
+
+    // Granting a link grants what it points to
+    let mut link_permissions = Permissions::deny_all();
+    link_permissions.grant(Capability::Read, Grant::Only(vec![path("alias")]));
+    assert!(link_permissions.is_allowed(Capability::Read, &path("data/module.ham")));
+
+    let code = |module: &str| format!("import m from \"{}\" m.x()", path(module));
+
+    let (stdout, stderr) = run_with_permissions(&code("data/link/module.ham"), permissions.clone());
+    assert_eq!("", stdout);
+    assert!(stderr.contains("Permission denied to read"));
+
+    let (stdout, _) = run_with_permissions(&code("data/module.ham"), permissions);
+    assert_eq!("inside", stdout);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::run;
+
+const CODE: &str = r#"
+fn create(){
+    let mut val = 6
+    return &mut val
+}
+
+let mut a = create()
+println(*a)
+
+let b = &mut a
+**b = 7
+println(*a)
+
+let mut c = 1
+*b = &mut c
+c = 2
+println(**b)
+
+**b = 3
+println(c)
+
+let d = 4
+let e = *d
+
+*b = b
+"#;
+
+/*
+ * Make sure pointers keep their values alive and can be dereferenced
+ */
+#[test]
+pub fn pointers_share_their_values() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(CODE, with_vm);
+
+        assert_eq!("6\n7\n2\n3\n", output);
+        assert!(errors.contains("'*d' does not point to any value"));
+        assert!(errors.contains("Assigning to '*b' would make a pointer point to itself"));
+    }
+}
@@ -0,0 +1,523 @@
+mod common;
+
+use common::Program;
+use ham_core::{
+    ast_types::boxed_val::BoxedValue,
+    modules::{
+        check_arguments,
+        NativeModule,
+    },
+    permissions::{
+        Capability,
+        Grant,
+        Permissions,
+    },
+    primitive_values::string::StringVal,
+    runtime::value_to_string,
+    stdlib::env::load_env_file,
+    utils::Ops,
+};
+use std::{
+    env,
+    fs,
+};
+
+/*
+ * Module registered by the program embedding Ham
+ */
+fn host_module() -> NativeModule {
+    NativeModule::default()
+        .function("greet", &["name"], |function, args_vals, stack| {
+            if !check_arguments(function, &args_vals, &[Ops::String], stack) {
+                return None;
+            }
+            let name = value_to_string(args_vals[0].clone()).unwrap();
+            Some(BoxedValue {
+                interface: Ops::String,
+                value: Box::new(StringVal(format!("Hello {}", name))),
+            })
+        })
+        .value(
+            "name",
+            BoxedValue {
+                interface: Ops::String,
+                value: Box::new(StringVal("host".to_string())),
+            },
+        )
+}
+
+/*
+ * Run some code with the tree-walking interpreter or the VM, returns its output and errors
+ */
+fn run(code: &str, with_vm: bool) -> (String, String) {
+    run_with_permissions(code, with_vm, Permissions::default())
+}
+
+fn run_with_permissions(code: &str, with_vm: bool, permissions: Permissions) -> (String, String) {
+    let program = Program::default();
+    {
+        let mut stack = program.stack.lock().unwrap();
+        stack.set_deterministic();
+        stack.permissions = permissions;
+        stack.register_module("host:greetings", host_module());
+    }
+    program.parse(code);
+    program.run(with_vm).ok();
+    program.output()
+}
+
+/*
+ * Make sure the modules of the standard library can be imported in every way
+ */
+#[test]
+pub fn std_modules_are_imported() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import math from "std:math"
+            import { join, basename as base } from "std:path"
+            import * as time from "std:time"
+
+            let biggest = math.max(3, 9)
+            let root = math.sqrt(10)
+            let file = join("src", "main.ham")
+            let name = base(file)
+            time.sleep(50)
+            let now = time.now()
+
+            println(biggest, " ", root, " ", file, " ", name, " ", now)
+            "#,
+            with_vm,
+        );
+        assert_eq!("9 3 src/main.ham main.ham 50\n", output);
+        assert_eq!("", errors);
+
+        let (_, errors) = run(
+            r#"
+            import math from "std:math"
+            let power = math.pow(2)
+            let root = math.sqrt("16")
+            "#,
+            with_vm,
+        );
+        assert!(errors.contains("Function 'pow' takes 2 argument(s) but 1 were given"));
+        assert!(errors.contains("Expected the argument 'value' of 'sqrt' to be 'number'"));
+
+        let (output, errors) = run(
+            r#"
+            import math from "std:math"
+            let small = math.pow(2, 10)
+            let big = math.pow(2, 100)
+            println(small)
+            "#,
+            with_vm,
+        );
+        assert_eq!("1024\n", output);
+        assert!(errors.contains("2 to the power of 100 is bigger than the biggest number"));
+
+        let (_, errors) = run(r#"import { random } from "std:math""#, with_vm);
+        assert!(errors.contains("'random' is not exported by the module 'std:math'"));
+
+        let (_, errors) = run(r#"import nope from "std:nope""#, with_vm);
+        assert!(errors.contains("There is no native module 'std:nope'"));
+    }
+}
+
+/*
+ * Make sure programs embedding Ham can add their own modules
+ */
+#[test]
+pub fn host_modules_are_imported() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import greetings from "host:greetings"
+            let greeting = greetings.greet("World")
+            println(greeting, " from ", greetings.name)
+            "#,
+            with_vm,
+        );
+        assert_eq!("Hello World from host\n", output);
+        assert_eq!("", errors);
+    }
+}
+
+/*
+ * Make sure std:fs works with files, and returns errors as values
+ */
+#[test]
+pub fn fs_module_works_with_files() {
+    for with_vm in [false, true] {
+        let dir = env::temp_dir().join(format!("ham_fs_{}_{}", std::process::id(), with_vm));
+        let dir = dir.display().to_string();
+
+        let (output, errors) = run(
+            &r#"
+            import fs from "std:fs"
+
+            fs.create_dir_all("DIR/notes")
+            fs.write("DIR/notes/a.txt", "Hello")
+            fs.append("DIR/notes/a.txt", " World")
+            fs.copy("DIR/notes/a.txt", "DIR/notes/b.txt")
+            fs.rename("DIR/notes/b.txt", "DIR/notes/c.md")
+
+            let text = fs.read_to_string("DIR/notes/a.txt")
+            let names = fs.read_dir("DIR/notes")
+            let texts = fs.glob("DIR/**/*.txt")
+            let info = fs.metadata("DIR/notes/a.txt")
+            let size = info.get("size")
+            println(text, " ", names, " ", texts.len(), " ", size)
+
+            for key in info {
+                print(key, "")
+            }
+
+            let missing = fs.read_to_string("DIR/missing.txt")
+            let message = missing.message()
+            println(message)
+
+            fs.remove("DIR")
+            let exists = fs.exists("DIR")
+            println(exists)
+            "#
+            .replace("DIR", &dir),
+            with_vm,
+        );
+
+        assert_eq!(
+            format!(
+                "Hello World [a.txt, c.md] 1 11\nsize is_file is_dir readonly {}/missing.txt: No such file or directory (os error 2)\nfalse\n",
+                dir
+            ),
+            output
+        );
+        assert_eq!("", errors);
+        assert!(fs::metadata(&dir).is_err());
+    }
+}
+
+/*
+ * Make sure std:fs follows the permissions of the script
+ */
+#[test]
+pub fn fs_module_needs_permissions() {
+    for with_vm in [false, true] {
+        let mut permissions = Permissions::deny_all();
+        permissions.grant(Capability::Read, Grant::All);
+
+        let (_, errors) = run_with_permissions(
+            r#"
+            import fs from "std:fs"
+            fs.write("notes.txt", "Hello")
+            "#,
+            with_vm,
+            permissions,
+        );
+        assert!(errors.contains("Permission denied to write 'notes.txt'"));
+        assert!(fs::metadata("notes.txt").is_err());
+    }
+}
+
+/*
+ * Make sure std:fs can't follow links out of the granted folders
+ */
+#[cfg(unix)]
+#[test]
+pub fn fs_module_does_not_follow_links_out_of_the_permissions() {
+    for with_vm in [false, true] {
+        let dir = env::temp_dir().join(format!("ham_fs_links_{}_{}", std::process::id(), with_vm));
+        fs::create_dir_all(dir.join("data")).unwrap();
+        fs::create_dir_all(dir.join("outside")).unwrap();
+        fs::write(dir.join("data/notes.txt"), "inside").unwrap();
+        fs::write(dir.join("outside/secret.txt"), "secret").unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("data/link")).unwrap();
+
+        let data = dir.join("data").display().to_string();
+        let mut permissions = Permissions::deny_all();
+        permissions.grant(Capability::Read, Grant::Only(vec![data.clone()]));
+        permissions.grant(Capability::Write, Grant::Only(vec![data]));
+
+        let (output, errors) = run_with_permissions(
+            &r#"
+            import fs from "std:fs"
+
+            let notes = fs.read_to_string("DIR/data/notes.txt")
+            let secret = fs.read_to_string("DIR/data/link/secret.txt")
+            fs.write("DIR/data/link/new.txt", "Hello")
+            let names = fs.read_dir("DIR/data/link")
+            let files = fs.glob("DIR/data/**/*.txt")
+            println(notes, " ", files.len())
+            "#
+            .replace("DIR", &dir.display().to_string()),
+            with_vm,
+            permissions,
+        );
+
+        assert_eq!("inside 1\n", output);
+        assert!(errors.contains("Permission denied to read"));
+        assert!(errors.contains("link/secret.txt'"));
+        assert!(errors.contains("Permission denied to write"));
+        assert!(errors.contains("link/new.txt'"));
+        assert!(errors.contains("link'"));
+        assert!(fs::metadata(dir.join("outside/new.txt")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+/*
+ * Make sure std:json reads and writes values, keeps the numbers Ham can't hold as strings,
+ * and says where malformed texts are wrong
+ */
+#[test]
+pub fn json_module_reads_and_writes_values() {
+    for with_vm in [false, true] {
+        // Ham strings can't have quotes, so the texts are read from files
+        let dir = env::temp_dir().join(format!("ham_json_{}_{}", std::process::id(), with_vm));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("config.json"),
+            r#"{"name": "ham", "tags": ["fast", "small"], "port": 8080, "debug": false, "db": {"user": "root", "pool": 4.0}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("broken.json"), "{\n  \"port\": \n}").unwrap();
+        fs::write(
+            dir.join("numbers.json"),
+            r#"{"t": -1.5, "offset": -2, "ratio": 0.25, "count": 3}"#,
+        )
+        .unwrap();
+
+        let (output, errors) = run(
+            &r#"
+            import json from "std:json"
+            import fs from "std:fs"
+
+            let text = fs.read_to_string("DIR/config.json")
+            let config = json.parse(text)
+            let port = config.get("port")
+            println(config)
+            println(port)
+
+            let compact = json.stringify(config)
+            println(compact)
+            let tags = config.get("tags")
+            let pretty = json.stringify(tags, true)
+            println(pretty)
+
+            let broken_text = fs.read_to_string("DIR/broken.json")
+            let broken = json.parse(broken_text)
+            println(broken)
+            let numbers_text = fs.read_to_string("DIR/numbers.json")
+            let numbers = json.parse(numbers_text)
+            let t = numbers.get("t")
+            let numbers_json = json.stringify(numbers)
+            println(numbers, " ", t)
+            println(numbers_json)
+            let unwritable = json.stringify(broken)
+            println(unwritable)
+            "#
+            .replace("DIR", &dir.display().to_string()),
+            with_vm,
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            concat!(
+                "{name: ham, tags: [fast, small], port: 8080, debug: false, db: {user: root, pool: 4}}\n",
+                "8080\n",
+                r#"{"name":"ham","tags":["fast","small"],"port":8080,"debug":false,"db":{"user":"root","pool":4}}"#,
+                "\n[\n  \"fast\",\n  \"small\"\n]\n",
+                "error: invalid JSON: expected value at line 3 column 1\n",
+                "{t: -1.5, offset: -2, ratio: 0.25, count: 3} -1.5\n",
+                r#"{"t":"-1.5","offset":"-2","ratio":"0.25","count":3}"#,
+                "\n",
+                "error: values of type 'error' can't be written as JSON\n",
+            ),
+            output
+        );
+        assert_eq!("", errors);
+
+        let (_, errors) = run(
+            r#"
+            import json from "std:json"
+            let text = json.stringify()
+            let pretty = json.stringify("a", "yes")
+            "#,
+            with_vm,
+        );
+        assert!(errors.contains("Function 'stringify' takes 1 to 2 argument(s) but 0 were given"));
+        assert!(errors.contains("Expected the argument 'pretty' of 'stringify' to be 'boolean'"));
+    }
+}
+
+/*
+ * Make sure std:process runs subprocesses, and stops the script with an exit code
+ */
+#[cfg(unix)]
+#[test]
+pub fn process_module_runs_subprocesses() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import process from "std:process"
+
+            let output = process.run("sh", "-c", "echo hi; echo oops >&2; exit 3")
+            let code = output.get("code")
+            let stderr = output.get("stderr")
+            print(code, stderr)
+
+            let reader = process.start("sh", "-c", "read line; echo got $line; echo bye")
+            reader.write("hello")
+            reader.close()
+            let line = reader.read_line()
+            let rest = reader.wait()
+            println(line, " ", rest)
+
+            let missing = process.run("ham-missing-command")
+            let message = missing.message()
+            println(message)
+
+            process.exit(2)
+            println("unreachable")
+            "#,
+            with_vm,
+        );
+        assert_eq!(
+            "3 oops\ngot hello {code: 0, stdout: bye\n, stderr: }\nham-missing-command: No such file or directory (os error 2)\n",
+            output
+        );
+        assert_eq!("", errors);
+    }
+}
+
+/*
+ * Make sure std:json reads big objects, a key given twice keeps its first position and last value
+ */
+#[test]
+pub fn json_module_reads_big_objects() {
+    for with_vm in [false, true] {
+        let dir = env::temp_dir().join(format!("ham_big_json_{}_{}", std::process::id(), with_vm));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut entries: Vec<String> = (0..50_000).map(|i| format!("\"k{}\": {}", i, i)).collect();
+        entries.push("\"k0\": \"last\"".to_string());
+        fs::write(dir.join("big.json"), format!("{{{}}}", entries.join(", "))).unwrap();
+
+        let (output, errors) = run(
+            &r#"
+            import json from "std:json"
+            import fs from "std:fs"
+
+            let text = fs.read_to_string("DIR/big.json")
+            let big = json.parse(text)
+            let size = big.len()
+            let first = big.get("k0")
+            let keys = big.keys()
+            let first_key = keys.get(0)
+            let last = big.get("k49999")
+            println(size, " ", first_key, " ", first, " ", last)
+            "#
+            .replace("DIR", &dir.display().to_string()),
+            with_vm,
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!("50000 k0 last 49999\n", output);
+        assert_eq!("", errors);
+    }
+}
+
+/*
+ * Make sure exit codes which don't fit in an exit status are not truncated
+ */
+#[test]
+pub fn process_exit_checks_its_code() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(
+            r#"
+            import process from "std:process"
+
+            process.exit(4294967296)
+            println("still running")
+            "#,
+            with_vm,
+        );
+        assert_eq!("still running\n", output);
+        assert!(errors.contains("Exit code 4294967296 is bigger than the biggest exit code"));
+    }
+}
+
+/*
+ * Make sure std:env reads and changes the environment variables the script can use
+ */
+#[test]
+pub fn env_module_uses_variables() {
+    for with_vm in [false, true] {
+        let mut permissions = Permissions::deny_all();
+        permissions.grant(
+            Capability::Env,
+            Grant::Only(vec![
+                "HAM_TEST_MODE".to_string(),
+                "HAM_TEST_UNSET".to_string(),
+            ]),
+        );
+
+        let (output, errors) = run_with_permissions(
+            r#"
+            import env from "std:env"
+
+            env.set("HAM_TEST_MODE", "release")
+            let mode = env.get("HAM_TEST_MODE")
+            let vars = env.vars()
+            let names = vars.keys()
+            println(mode, " ", names)
+
+            let unset = env.get("HAM_TEST_UNSET")
+            println(unset)
+
+            let home = env.get("HOME")
+            "#,
+            with_vm,
+            permissions,
+        );
+        assert_eq!("release [HAM_TEST_MODE]\nnone\n", output);
+        assert!(errors.contains("Permission denied to env 'HOME'"));
+    }
+}
+
+/*
+ * Make sure the `.env` file of a project is loaded, without changing the variables already set
+ * or the ones the script can't read
+ */
+#[test]
+pub fn env_file_is_loaded() {
+    let project = env::temp_dir().join(format!("ham_env_{}", std::process::id()));
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("ham.yml"), "name: project").unwrap();
+    fs::write(
+        project.join(".env"),
+        "# Settings\nHAM_TEST_GREETING=\"Hello there\"\nHAM_TEST_KEPT=new\nHAM_TEST_SECRET=1\nHAM_TEST_HIDDEN=1\n",
+    )
+    .unwrap();
+
+    // Nothing is loaded without the permission
+    load_env_file(&project.join("src"), &Permissions::deny_all()).unwrap();
+    assert!(env::var("HAM_TEST_GREETING").is_err());
+
+    let mut permissions = Permissions::deny_all();
+    permissions.grant(Capability::Env, Grant::Only(vec!["HAM_TEST_SECRET".to_string()]));
+    load_env_file(&project.join("src"), &permissions).unwrap();
+    assert_eq!("1", env::var("HAM_TEST_SECRET").unwrap());
+    assert!(env::var("HAM_TEST_HIDDEN").is_err());
+    assert!(env::var("HAM_TEST_GREETING").is_err());
+
+    env::set_var("HAM_TEST_KEPT", "old");
+    load_env_file(&project.join("src"), &Permissions::allow_all()).unwrap();
+
+    assert_eq!("Hello there", env::var("HAM_TEST_GREETING").unwrap());
+    assert_eq!("old", env::var("HAM_TEST_KEPT").unwrap());
+
+    fs::remove_dir_all(project).unwrap();
+}
@@ -0,0 +1,50 @@
+use ham_core::{
+    ast_types::expression::{
+        Expression,
+        ExpressionBase,
+    },
+    get_tokens,
+    move_tokens_into_ast,
+    run_ast,
+    stack::Stack,
+    streams::{
+        MemoryStream,
+        Streams,
+    },
+};
+use std::{
+    io::Cursor,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+/*
+ * Make sure the script's output and errors end up in the streams provided by the host
+ */
+#[test]
+pub fn output_is_captured() {
+    // Sample code
+    const CODE: &str = "println(format(\"Hello {}\", \"World\")) print(1) missing()";
+
+    let stdout = MemoryStream::default();
+    let stderr = MemoryStream::default();
+
+    let global_context = Expression::new();
+    let stack = Mutex::new(Stack::new(global_context.expr_id));
+    stack.lock().unwrap().streams = Streams::new(
+        stdout.handle(),
+        stderr.handle(),
+        Arc::new(Mutex::new(Cursor::new(""))),
+    );
+
+    let tree = Mutex::new(global_context);
+    move_tokens_into_ast(get_tokens(CODE.to_string()), &tree, ".".to_string(), &stack);
+    run_ast(&tree, &stack);
+
+    assert_eq!("Hello World\n1", stdout.contents());
+    assert!(stderr
+        .contents()
+        .contains("Function 'missing' was not found"));
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::Program;
+
+const CODE: &str = r#"
+fn count(mut n){
+    if n == 1000000 {
+        return n
+    }
+    n.mut_sum(1)
+    return count(n)
+}
+
+println(count(0))
+"#;
+
+/*
+ * Run the code with the tree-walking interpreter or the VM, returns its output
+ */
+fn run(with_vm: bool) -> String {
+    let program = Program::default();
+    program.parse(CODE);
+    program.run(with_vm).unwrap();
+
+    assert!(program.stack.lock().unwrap().variables.is_empty());
+
+    program.stdout.contents()
+}
+
+/*
+ * Make sure returning a function call doesn't nest the calls
+ */
+#[test]
+pub fn tail_calls_do_not_overflow() {
+    assert_eq!("1000000\n", run(false));
+    assert_eq!("1000000\n", run(true));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::run;
+
+const CODE: &str = r#"
+let messages = channel()
+
+fn worker(name, pause) {
+    wait(pause)
+    messages.send(format("{} started", name))
+    wait(pause)
+    messages.send(format("{} finished", name))
+    return name
+}
+
+let slow = spawn worker("slow", 100)
+let fast = spawn worker("fast", 10)
+
+let mut received = 0
+while received != 4 {
+    println(messages.recv())
+    received.mut_sum(1)
+}
+
+println(join(slow, fast))
+
+let task = spawn fn() {
+    println("in a task")
+}
+println(task.done())
+task.await()
+println(task.done())
+
+spawn fn() {
+    println("after the code")
+}
+
+let never = channel()
+let nothing = never.recv()
+"#;
+
+/*
+ * Make sure tasks run while others wait, and can talk through channels
+ */
+#[test]
+pub fn tasks_run_concurrently() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(CODE, with_vm);
+
+        assert_eq!(
+            "fast started\nfast finished\nslow started\nslow finished\n[slow, fast]\nfalse\nin a task\ntrue\nafter the code\n",
+            output
+        );
+        assert!(errors.contains("Waiting forever"));
+    }
+}
+
+/*
+ * Make sure waiting without a number of milliseconds is reported instead of crashing
+ */
+#[test]
+pub fn wait_checks_its_argument() {
+    for with_vm in [false, true] {
+        let (output, errors) = run(r#"wait() wait("x") println("done")"#, with_vm);
+
+        assert_eq!("done\n", output);
+        assert!(errors.contains("takes 1 argument(s) but 0 were given"));
+        assert!(errors.contains("the argument 'milliseconds' of 'wait'"));
+    }
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use common::Program;
+use ham_core::typecheck::check_types;
+
+/*
+ * Check some code, returns the reported errors if it was rejected
+ */
+fn check(code: &str) -> Option<String> {
+    let program = Program::default();
+    program.parse(code);
+
+    if check_types(&program.tree, &program.stack) {
+        None
+    } else {
+        Some(program.stderr.contents())
+    }
+}
+
+/*
+ * Make sure annotated and known types are checked, and unannotated code is still valid
+ */
+#[test]
+pub fn types_are_checked() {
+    const VALID: &str = r#"
+fn add(a: number, b: number) -> number {
+    return a.sum(b)
+}
+
+fn untyped(value){
+    return value
+}
+
+let total: number = add(1, 2)
+let text: string = format("{}", total)
+let anything = untyped("hi")
+anything.whatever()
+"#;
+    assert_eq!(None, check(VALID));
+
+    let errors =
+        check("fn add(a: number, b: number) -> number { return a } let c = add(1)").unwrap();
+    assert!(errors.contains("Function 'add' takes 2 argument(s) but 1 were given"));
+
+    let errors = check("fn add(a, b = 1, ...rest) { } add() add(b = 2, c = 3)").unwrap();
+    assert!(errors.contains("Function 'add' takes at least 1 argument(s) but 0 were given"));
+    assert!(errors.contains("Function 'add' has no argument named 'c'"));
+
+    let errors =
+        check(r#"fn add(a: number, b: number = 1) { } add(1, a = 2) add(1, b = "two")"#).unwrap();
+    assert!(errors.contains("Argument 'a' of function 'add' was given more than once"));
+    assert!(errors.contains("Expected argument 'b' of 'add' to be 'number' but found 'string'"));
+
+    let errors = check("let c = 5 let d = c.sum()").unwrap();
+    assert!(errors.contains("Function 'sum' takes 1 argument(s) but 0 were given"));
+
+    let errors = check(r#"let text = "ham" text.mut_sum(1)"#).unwrap();
+    assert!(errors.contains("Method 'mut_sum' was not found for type 'string'"));
+
+    let errors = check(r#"fn name() -> string { return 5 }"#).unwrap();
+    assert!(
+        errors.contains("Expected the value returned by 'name' to be 'string' but found 'number'")
+    );
+
+    let errors = check(r#"let mut count: number = 1 count = "one""#).unwrap();
+    assert!(errors.contains("Expected variable 'count' to be 'number' but found 'string'"));
+
+    let errors = check(r#"let mut count: number = 1 count = none"#).unwrap();
+    assert!(errors.contains("Expected variable 'count' to be 'number' but found 'none'"));
+
+    let errors = check(r#"import math from "std:math" math.round(1) math.max(1)"#).unwrap();
+    assert!(errors.contains("Function 'round' was not found"));
+    assert!(errors.contains("Function 'max' takes 2 argument(s) but 1 were given"));
+
+    let errors = check(r#"import json from "std:json" json.stringify(1, true, 2)"#).unwrap();
+    assert!(errors.contains("Function 'stringify' takes 1 to 2 argument(s) but 3 were given"));
+
+    // Unknown types are reported while parsing, along with every type that can be used
+    let program = Program::default();
+    program.parse("fn h(x: bool) { }");
+    assert!(program.stderr.contents().contains(
+        "expected number, string, boolean, pointer, list, generator, task, channel, map, error, process or none"
+    ));
+}
+
+/*
+ * Make sure functions with a return type can't end without returning a value
+ */
+#[test]
+pub fn missing_returns_are_reported() {
+    const VALID: &str = r#"
+fn name(id: number) -> string {
+    if id == 1 {
+        return "one"
+    }
+    return "other"
+}
+
+fn forever() -> number {
+    while 1 == 1 {
+        if 1 != 1 {
+            return 0
+        }
+    }
+}
+
+fn nothing() -> none {
+    let a = 1
+}
+"#;
+    assert_eq!(None, check(VALID));
+
+    let errors = check(r#"fn name(id: number) -> string { if id == 1 { return "one" } }"#).unwrap();
+    assert!(errors
+        .contains("Function 'name' returns 'string' but it can end without returning a value"));
+
+    let errors = check("fn stops() -> number { while 1 == 1 { break } }").unwrap();
+    assert!(errors
+        .contains("Function 'stops' returns 'number' but it can end without returning a value"));
+}
+
+/*
+ * Make sure type errors point to the line of the call, statement or declaration they are found in
+ */
+#[test]
+pub fn type_errors_report_their_line() {
+    const CODE: &str = r#"
+fn add(a: number, b: number) -> number {
+    return a.sum(b)
+}
+
+fn name() -> string {
+    return 5
+}
+
+fn maybe(id: number) -> string {
+    if id == 1 {
+        return "one"
+    }
+}
+
+add(1)
+let total = add(1, "two")
+let c = 5
+let d = c.sum()
+let mut count: number = 1
+count = "one"
+"#;
+
+    let errors = check(CODE).unwrap();
+    assert!(errors.contains(
+        "Expected the value returned by 'name' to be 'string' but found 'number', in line 7"
+    ));
+    assert!(errors.contains(
+        "Function 'maybe' returns 'string' but it can end without returning a value, in line 10"
+    ));
+    assert!(errors.contains("Function 'add' takes 2 argument(s) but 1 were given, in line 16"));
+    assert!(errors
+        .contains("Expected argument 'b' of 'add' to be 'number' but found 'string', in line 17"));
+    assert!(errors.contains("Function 'sum' takes 1 argument(s) but 0 were given, in line 19"));
+    assert!(errors
+        .contains("Expected variable 'count' to be 'number' but found 'string', in line 21"));
+}
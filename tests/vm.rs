@@ -0,0 +1,63 @@
+mod common;
+
+use common::{
+    examples,
+    run,
+    Program,
+};
+use std::fs;
+
+/*
+ * Run an example with the tree-walking interpreter or the VM, returns its output and errors
+ */
+fn run_example(file: &str, with_vm: bool) -> (String, String) {
+    let code = fs::read_to_string(format!("{}/{}", examples(""), file)).unwrap();
+
+    let program = Program::default();
+    program.parse_in(&code, &examples(""));
+    program.run(with_vm).ok();
+    program.output()
+}
+
+/*
+ * Make sure the VM behaves like the tree-walking interpreter
+ */
+#[test]
+pub fn vm_matches_interpreter() {
+    for example in &[
+        "demo.ham",
+        "loop.ham",
+        "pointer.ham",
+        "external.ham",
+        "errors.ham",
+    ] {
+        let (output, errors) = run_example(example, false);
+
+        assert!(!output.is_empty() || !errors.is_empty());
+        assert_eq!((output, errors), run_example(example, true), "{}", example);
+    }
+}
+
+/*
+ * Make sure the VM reports the same errors as the tree-walking interpreter
+ */
+#[test]
+pub fn vm_matches_interpreter_errors() {
+    const CODE: &str = r#"
+let v = foo()
+let n = 1
+let w = n.nope()
+let x = n.nope().sum(1)
+let y = n.sum(1).nope()
+println(missing)
+fee()
+n.sum(1)
+println("end")
+"#;
+
+    let (output, errors) = run(CODE, false);
+
+    assert_eq!("\nend\n", output);
+    assert!(!errors.contains("'_'"));
+    assert_eq!((output, errors), run(CODE, true));
+}